@@ -1,14 +1,19 @@
 use crate::error::TGVError;
 use crate::intervals::{GenomeInterval, Region};
-use crate::message::{AlignmentFilter, AlignmentSort};
+use crate::message::{AlignmentFilter, AlignmentGroupBy, AlignmentSort};
 use crate::sequence::Sequence;
 use crate::{
     alignment::{
         coverage::{BaseCoverage, DEFAULT_COVERAGE, calculate_basewise_coverage},
-        read::{AlignedRead, ReadPair, calculate_paired_context},
+        downsample::DownsampledReads,
+        junction::{SpliceJunction, calculate_junctions},
+        methylation::{MethylationSite, calculate_methylation, sites_in},
+        read::{AlignedRead, ReadPair, calculate_paired_context, matches_base},
     },
     message::AlignmentDisplayOption,
 };
+use itertools::Itertools;
+use std::cmp::{Ordering, Reverse};
 use std::collections::{BTreeMap, HashMap, hash_map::Entry};
 
 /// A alignment region on a contig.
@@ -21,6 +26,16 @@ pub struct Alignment {
     /// Coverage at each position. Keys are 1-based, inclusive.
     coverage: BTreeMap<u64, BaseCoverage>,
 
+    /// Splice junctions of the shown reads, sorted by start.
+    junctions: Vec<SpliceJunction>,
+
+    /// 5mC calls of the shown reads per CpG, sorted by position.
+    methylation: Vec<MethylationSite>,
+
+    /// Group label -> 5mC calls of the shown reads in the group, when reads are grouped.
+    /// Reads without a group value are skipped. For allele-specific methylation.
+    group_methylation: Vec<(String, Vec<MethylationSite>)>,
+
     /// The left bound of region with complete data.
     /// 1-based, inclusive.
     data_complete_left_bound: u64,
@@ -48,6 +63,30 @@ pub struct Alignment {
 
     /// Whether to show the pair
     pub show_pairs: Option<Vec<bool>>,
+
+    /// Read group ID -> sample name (SM), from the alignment file header
+    pub read_group_samples: HashMap<String, String>,
+
+    /// Reference sequence ID -> contig name, from the alignment file header
+    pub reference_sequence_names: Vec<String>,
+
+    /// Grouping of the stacked reads. None: not grouped.
+    group_by: Option<AlignmentGroupBy>,
+
+    /// y of the label track above each group -> group label
+    pub group_labels: Vec<(usize, String)>,
+
+    /// Expected range of absolute insert sizes, from the loaded pairs.
+    insert_size_bounds: Option<(u64, u64)>,
+
+    /// Reads dropped by downsampling. Not in reads, but counted in the coverage.
+    downsampled: DownsampledReads,
+
+    /// Whether a :filter is applied.
+    filtered: bool,
+
+    /// Bases below this base quality are not counted in the coverage.
+    min_base_quality: u8,
 }
 
 impl Alignment {
@@ -73,6 +112,79 @@ impl Alignment {
         }
     }
 
+    /// The most variable position in [left, right] and its alleles, from the most to the least common.
+    /// Collapsed view: the first allele is the consensus.
+    /// 1-based, inclusive.
+    pub fn alleles_in(&self, left: u64, right: u64) -> Option<(u64, Vec<(u8, usize)>)> {
+        self.coverage
+            .range(left..=right)
+            .map(|(position, coverage)| (*position, coverage.alleles()))
+            .filter(|(_, alleles)| !alleles.is_empty())
+            .max_by_key(|(position, alleles)| {
+                let minor_depth: usize = alleles.iter().skip(1).map(|(_, count)| count).sum();
+                (minor_depth, Reverse(*position))
+            })
+    }
+
+    /// Expected range of absolute insert sizes: the 0.5th and 99.5th percentiles of the loaded pairs.
+    /// None: no pairs with an insert size.
+    pub fn insert_size_bounds(&self) -> Option<(u64, u64)> {
+        self.insert_size_bounds
+    }
+
+    /// Positions whose alt-allele frequency is at least threshold, in order.
+    /// 1-based.
+    pub fn alt_allele_positions(
+        &self,
+        threshold: f64,
+        quality_weighted: bool,
+    ) -> impl Iterator<Item = u64> + '_ {
+        self.coverage
+            .iter()
+            .filter(move |(_, coverage)| {
+                coverage.total > 0 && coverage.alt_allele_frequency(quality_weighted) >= threshold
+            })
+            .map(|(position, _)| *position)
+    }
+
+    /// Number of reads dropped by downsampling.
+    pub fn downsampled_reads(&self) -> usize {
+        self.downsampled.count
+    }
+
+    /// 5mC calls per CpG in [left, right], sorted by position.
+    /// 1-based, inclusive.
+    pub fn methylation_in(&self, left: u64, right: u64) -> &[MethylationSite] {
+        sites_in(&self.methylation, left, right)
+    }
+
+    /// Labels of the read groups with 5mC calls, e.g. "HP: 1". Empty if reads are not grouped.
+    pub fn methylation_groups(&self) -> impl Iterator<Item = &str> {
+        self.group_methylation
+            .iter()
+            .map(|(label, _)| label.as_str())
+    }
+
+    /// 5mC calls per CpG in [left, right] of each read group (e.g. haplotype), in the group order.
+    /// Empty if reads are not grouped.
+    /// 1-based, inclusive.
+    pub fn group_methylation_in(
+        &self,
+        left: u64,
+        right: u64,
+    ) -> impl Iterator<Item = (&str, &[MethylationSite])> {
+        self.group_methylation
+            .iter()
+            .map(move |(label, sites)| (label.as_str(), sites_in(sites, left, right)))
+    }
+
+    /// Splice junctions supported by at least min_count reads, sorted by start.
+    pub fn junctions(&self, min_count: usize) -> impl Iterator<Item = &SpliceJunction> {
+        self.junctions
+            .iter()
+            .filter(move |junction| junction.count >= min_count)
+    }
+
     /// Return the read at x_coordinate, yth track
     pub fn read_at(&self, x_coordinate: u64, y: usize) -> Option<&AlignedRead> {
         if y >= self.depth() {
@@ -91,23 +203,69 @@ impl Alignment {
         }
         self.build_mate_rendering_contexts()?;
 
-        // build y index
-        let paired_ys = stack_tracks_for_paired_reads(
-            self.read_pairs.as_ref().unwrap(),
-            self.show_pairs.as_ref().unwrap(),
-        );
+        let n_pairs = self.read_pairs.as_ref().map_or(0, |pairs| pairs.len());
+        self.stack_pairs(0..n_pairs)
+    }
+
+    /// Stack reads in the given read order.
+    fn stack_reads(
+        &mut self,
+        order: impl IntoIterator<Item = usize>,
+    ) -> Result<&mut Self, TGVError> {
+        let (ys, group_labels) = match &self.group_by {
+            Some(group_by) => {
+                let groups = split_groups(order.into_iter().filter(|i| self.show_read[*i]), |i| {
+                    self.group_key(group_by, &self.reads[i])
+                });
+                stack_groups(groups, self.reads.len(), group_by, |group| {
+                    stack_tracks_for_reads(&self.reads, &self.show_read, group.iter().copied())
+                })
+            }
+            None => (
+                stack_tracks_for_reads(&self.reads, &self.show_read, order),
+                Vec::new(),
+            ),
+        };
+        self.ys = ys;
+        self.group_labels = group_labels;
+        self.build_y_index()
+    }
+
+    /// Stack read pairs in the given pair order. Both reads in a pair share the same y.
+    /// Pairs are grouped by the first read.
+    fn stack_pairs(
+        &mut self,
+        order: impl IntoIterator<Item = usize>,
+    ) -> Result<&mut Self, TGVError> {
+        let (Some(read_pairs), Some(show_pairs)) = (&self.read_pairs, &self.show_pairs) else {
+            return Err(TGVError::StateError("Read pairs are not built".to_string()));
+        };
+
+        let (paired_ys, group_labels) = match &self.group_by {
+            Some(group_by) => {
+                let groups = split_groups(order.into_iter().filter(|i| show_pairs[*i]), |i| {
+                    self.group_key(group_by, &self.reads[read_pairs[i].read_1_index])
+                });
+                stack_groups(groups, read_pairs.len(), group_by, |group| {
+                    stack_tracks_for_paired_reads(read_pairs, show_pairs, group.iter().copied())
+                })
+            }
+            None => (
+                stack_tracks_for_paired_reads(read_pairs, show_pairs, order),
+                Vec::new(),
+            ),
+        };
 
         let mut ys = vec![0; self.reads.len()];
-        for (pair, y) in self.read_pairs.as_ref().unwrap().iter().zip(paired_ys) {
+        for (pair, y) in read_pairs.iter().zip(paired_ys) {
             ys[pair.read_1_index] = y;
             if let Some(read_2_index) = pair.read_2_index {
                 ys[read_2_index] = y;
             }
         }
         self.ys = ys;
-        self.build_y_index()?;
-
-        Ok(self)
+        self.group_labels = group_labels;
+        self.build_y_index()
     }
 
     /// Return the read at x_coordinate, yth track
@@ -134,14 +292,20 @@ impl Alignment {
         reads: Vec<AlignedRead>,
         contig_index: usize,
         data_complete_bound: (u64, u64),
+        downsampled: DownsampledReads,
+        min_base_quality: u8,
         reference_sequence: &Sequence,
     ) -> Result<Self, TGVError> {
         let show_reads = vec![true; reads.len()];
-        let ys = stack_tracks_for_reads(&reads, &show_reads);
+        let ys = stack_tracks_for_reads(&reads, &show_reads, 0..reads.len());
+        let insert_size_bounds = calculate_insert_size_bounds(&reads);
         let mut alignment = Self {
             reads,
             contig_index: contig_index,
             coverage: BTreeMap::new(),
+            junctions: Vec::new(),
+            methylation: Vec::new(),
+            group_methylation: Vec::new(),
             data_complete_left_bound: data_complete_bound.0,
             data_complete_right_bound: data_complete_bound.1,
             ys: ys.clone(),
@@ -151,6 +315,14 @@ impl Alignment {
             mate_map: None,
             read_pairs: None,
             show_pairs: None,
+            read_group_samples: HashMap::new(),
+            reference_sequence_names: Vec::new(),
+            group_by: None,
+            group_labels: Vec::new(),
+            insert_size_bounds,
+            downsampled,
+            filtered: false,
+            min_base_quality,
         };
         alignment
             .build_y_index()?
@@ -236,19 +408,29 @@ impl Alignment {
         options: &Vec<AlignmentDisplayOption>,
         reference_sequence: &Sequence,
     ) -> Result<&mut Self, TGVError> {
+        // Grouping applies to all stackings, so it is set before other options.
+        if let Some(group_by) = options.iter().rev().find_map(|option| match option {
+            AlignmentDisplayOption::GroupBy(group_by) => Some(group_by),
+            _ => None,
+        }) {
+            self.group_by = Some(group_by.clone());
+            self.stack_reads(0..self.reads.len())?;
+            self.group_methylation = self.calculate_group_methylation(reference_sequence);
+        }
+
         options
             .iter()
             .try_fold(self, |alignment, option| match option {
                 AlignmentDisplayOption::Filter(filter) => {
                     alignment.filter(filter, reference_sequence)
                 }
-                AlignmentDisplayOption::Sort(sort) => {
-                    // TODO
-                    alignment.sort(sort)
-                }
+                AlignmentDisplayOption::Sort(sort) => alignment.sort(sort),
                 AlignmentDisplayOption::ViewAsPairs => alignment.view_as_pairs(),
-                // ShowBaseModifications only affects rendering; no alignment state change needed.
-                AlignmentDisplayOption::ShowBaseModifications => Ok(alignment),
+                AlignmentDisplayOption::GroupBy(_) => Ok(alignment),
+                // These only affect rendering; no alignment state change needed.
+                AlignmentDisplayOption::ShowBaseModifications(_)
+                | AlignmentDisplayOption::MarkSplitReads
+                | AlignmentDisplayOption::ColorBy(_) => Ok(alignment),
             })
     }
 
@@ -257,6 +439,11 @@ impl Alignment {
         // TODO: reference sequence could be empty.
         self.ys = self.default_ys.clone();
         self.show_read = vec![true; self.reads.len()];
+        self.read_pairs = None;
+        self.show_pairs = None;
+        self.group_by = None;
+        self.group_labels = Vec::new();
+        self.filtered = false;
 
         self.build_y_index()?.build_coverage(reference_sequence)
     }
@@ -312,7 +499,8 @@ impl Alignment {
             let read_coverage = calculate_basewise_coverage(
                 read.start,
                 &read.cigar,
-                &read.read.sequence(),
+                &read.read,
+                self.min_base_quality,
                 reference_sequence,
             )?; // TODO: seq() is called twice. Optimize this in the future.
             for (i, coverage) in read_coverage.into_iter() {
@@ -325,35 +513,385 @@ impl Alignment {
             }
         }
 
+        // Reads dropped by downsampling still count. Only their coverage is kept, so they can't be
+        // filtered: while a filter is applied, the coverage counts the shown reads only.
+        if !self.filtered {
+            for (i, coverage) in self.downsampled.coverage.iter() {
+                match coverage_hashmap.entry(*i) {
+                    Entry::Occupied(mut oe) => oe.get_mut().add(coverage),
+                    Entry::Vacant(ve) => {
+                        ve.insert(coverage.clone());
+                    }
+                }
+            }
+        }
+
         let mut coverage: BTreeMap<u64, BaseCoverage> = BTreeMap::new();
         for (k, v) in coverage_hashmap.into_iter() {
             coverage.insert(k, v);
         }
 
         self.coverage = coverage;
+        self.junctions = calculate_junctions(&self.reads, &self.show_read, self.contig_index);
+        self.methylation = calculate_methylation(&self.reads, &self.show_read, reference_sequence);
+        self.group_methylation = self.calculate_group_methylation(reference_sequence);
 
         Ok(self)
     }
 
+    /// 5mC calls of the shown reads of each group. Groups without calls are skipped.
+    fn calculate_group_methylation(
+        &self,
+        reference_sequence: &Sequence,
+    ) -> Vec<(String, Vec<MethylationSite>)> {
+        let Some(group_by) = &self.group_by else {
+            return Vec::new();
+        };
+
+        split_groups((0..self.reads.len()).filter(|i| self.show_read[*i]), |i| {
+            self.group_key(group_by, &self.reads[i])
+        })
+        .into_iter()
+        .filter_map(|(key, reads)| {
+            let key = key?;
+            let mut show_read = vec![false; self.reads.len()];
+            for i in reads {
+                show_read[i] = true;
+            }
+            let sites = calculate_methylation(&self.reads, &show_read, reference_sequence);
+            (!sites.is_empty()).then(|| (format!("{}: {}", group_by, key), sites))
+        })
+        .collect()
+    }
+
     pub fn filter(
         &mut self,
         filter: &AlignmentFilter,
         reference_sequence: &Sequence,
     ) -> Result<&mut Self, TGVError> {
+        // Multiple filters are combined with AND.
+        self.filtered = true;
         for (i, read) in self.reads.iter().enumerate() {
-            self.show_read[i] = read.passes_filter(filter)
+            self.show_read[i] = self.show_read[i] && read.passes_filter(filter)
         }
 
-        self.ys = stack_tracks_for_reads(&self.reads, &self.show_read);
-        self.build_y_index()?.build_coverage(reference_sequence);
+        if self.read_pairs.is_some() {
+            // Keep the paired view.
+            self.view_as_pairs()?;
+        } else {
+            self.stack_reads(0..self.reads.len())?;
+        }
+        self.build_coverage(reference_sequence)?;
 
         Ok(self)
     }
 
+    /// Sort alignment tracks.
+    ///
+    /// If the sort refers to a position (e.g. BASE(123)), tracks are reordered by the reads covering
+    /// the position (same as IGV); tracks without reads at the position go to the bottom.
+    /// Otherwise, reads are restacked in the sorted order, so that top tracks are filled first.
     pub fn sort(&mut self, option: &AlignmentSort) -> Result<&mut Self, TGVError> {
-        // FIXME
-        todo!();
-        Ok(self)
+        match option.position() {
+            Some(position) => self.sort_tracks_at(option, position),
+            None => {
+                let mut order = (0..self.reads.len()).collect_vec();
+                order.sort_by(|a, b| self.compare_reads(option, &self.reads[*a], &self.reads[*b]));
+
+                match &self.read_pairs {
+                    Some(read_pairs) => {
+                        let mut pair_order = (0..read_pairs.len()).collect_vec();
+                        pair_order.sort_by(|a, b| {
+                            self.compare_reads(
+                                option,
+                                &self.reads[read_pairs[*a].read_1_index],
+                                &self.reads[read_pairs[*b].read_1_index],
+                            )
+                        });
+                        self.stack_pairs(pair_order)
+                    }
+                    None => self.stack_reads(order),
+                }
+            }
+        }
+    }
+
+    /// Reorder tracks by the reads covering a position (1-based).
+    /// This keeps the stacking (and read pairs) intact. Tracks stay in their groups.
+    fn sort_tracks_at(
+        &mut self,
+        option: &AlignmentSort,
+        position: u64,
+    ) -> Result<&mut Self, TGVError> {
+        let representatives = self
+            .ys_index
+            .iter()
+            .map(|read_indexes| {
+                read_indexes
+                    .iter()
+                    .find(|i_read| self.reads[**i_read].full_read_covers(position))
+                    .copied()
+            })
+            .collect_vec();
+
+        // Track 0 is left empty by the stacking algorithm. Keep it at the top.
+        // In groups, the label track and track 0 of the group stay in place.
+        let mut group_starts = self.group_labels.iter().map(|(y, _)| y + 2).collect_vec();
+        if group_starts.is_empty() {
+            group_starts.push(1);
+        }
+        let group_ends = self
+            .group_labels
+            .iter()
+            .skip(1)
+            .map(|(y, _)| *y)
+            .chain([self.ys_index.len()])
+            .collect_vec();
+
+        let mut new_ys = (0..self.ys_index.len()).collect_vec();
+        for (group_start, group_end) in group_starts.into_iter().zip(group_ends) {
+            let mut tracks = (group_start..group_end).collect_vec();
+            tracks.sort_by(|a, b| match (representatives[*a], representatives[*b]) {
+                (Some(a), Some(b)) => self.compare_reads(option, &self.reads[a], &self.reads[b]),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            });
+            for (new_y, old_y) in tracks.into_iter().enumerate() {
+                new_ys[old_y] = group_start + new_y;
+            }
+        }
+
+        self.ys.iter_mut().for_each(|y| {
+            if let Some(new_y) = new_ys.get(*y) {
+                *y = *new_y
+            }
+        });
+        self.build_y_index()
+    }
+
+    /// Compare two reads by a sort option.
+    fn compare_reads(&self, option: &AlignmentSort, a: &AlignedRead, b: &AlignedRead) -> Ordering {
+        match option {
+            AlignmentSort::Default | AlignmentSort::Start => a.start.cmp(&b.start),
+
+            AlignmentSort::StrandAt(_) | AlignmentSort::StrandAtCurrentBase => a
+                .flags
+                .is_reverse_complemented()
+                .cmp(&b.flags.is_reverse_complemented()),
+
+            AlignmentSort::BaseAt(position) => self
+                .base_sort_key(a, *position)
+                .cmp(&self.base_sort_key(b, *position)),
+
+            // Should be translated upstream.
+            AlignmentSort::BaseAtCurrentPosition => Ordering::Equal,
+
+            AlignmentSort::MappingQuality => b.mapping_quality().cmp(&a.mapping_quality()),
+
+            AlignmentSort::Sample => compare_missing_last(self.sample_of(a), self.sample_of(b)),
+
+            AlignmentSort::ReadGroup => compare_missing_last(a.read_group(), b.read_group()),
+
+            AlignmentSort::ReadOrder => read_order(a).cmp(&read_order(b)),
+
+            AlignmentSort::ReadName => a.read.name().cmp(&b.read.name()),
+
+            AlignmentSort::AlignedReadLength => (b.end - b.start).cmp(&(a.end - a.start)),
+
+            AlignmentSort::InsertSize => b
+                .read
+                .template_length()
+                .unsigned_abs()
+                .cmp(&a.read.template_length().unsigned_abs()),
+
+            AlignmentSort::ChromosomeOfMate => mate_contig_key(a).cmp(&mate_contig_key(b)),
+
+            AlignmentSort::Tag(tag) => match <&[u8; 2]>::try_from(tag.as_bytes()) {
+                Ok(tag) => compare_tag_values(a.tag_value(tag), b.tag_value(tag)),
+                Err(_) => Ordering::Equal,
+            },
+
+            AlignmentSort::Then(first, second) => self
+                .compare_reads(first, a, b)
+                .then_with(|| self.compare_reads(second, a, b)),
+
+            AlignmentSort::Reverse(option) => self.compare_reads(option, a, b).reverse(),
+        }
+    }
+
+    /// Sort key of a read by the base at a position (1-based):
+    /// alternative bases (more frequent first), reference bases, softclips, deletions, then others.
+    fn base_sort_key(&self, read: &AlignedRead, position: u64) -> (u8, Reverse<usize>, u8) {
+        match read.base_at(position) {
+            Some(base) => {
+                let coverage = self.coverage_at(position);
+                if matches_base(base, coverage.reference_base) {
+                    (1, Reverse(0), 0)
+                } else {
+                    (0, Reverse(coverage.count(base)), base.to_ascii_uppercase())
+                }
+            }
+            None if read.is_softclip_at(position) => (2, Reverse(0), 0),
+            None if read.is_deletion_at(position) => (3, Reverse(0), 0),
+            None => (4, Reverse(0), 0),
+        }
+    }
+
+    /// Sample name of the read, looked up by the read group.
+    fn sample_of(&self, read: &AlignedRead) -> Option<String> {
+        read.read_group()
+            .and_then(|read_group| self.read_group_samples.get(&read_group).cloned())
+    }
+
+    /// Group of a read. None: the read doesn't have the grouped value.
+    fn group_key(&self, group_by: &AlignmentGroupBy, read: &AlignedRead) -> Option<String> {
+        match group_by {
+            AlignmentGroupBy::Tag(tag) => <&[u8; 2]>::try_from(tag.as_bytes())
+                .ok()
+                .and_then(|tag| read.tag_value(tag)),
+            AlignmentGroupBy::ReadGroup => read.read_group(),
+            AlignmentGroupBy::Sample => self.sample_of(read),
+            AlignmentGroupBy::Strand => Some(read.strand().to_string()),
+            AlignmentGroupBy::BaseAt(position) => match read.base_at(*position) {
+                Some(base) => Some((base.to_ascii_uppercase() as char).to_string()),
+                None if read.is_deletion_at(*position) => Some("DEL".to_string()),
+                None => None,
+            },
+            // Translated to BaseAt before grouping.
+            AlignmentGroupBy::BaseAtCurrentPosition => None,
+            AlignmentGroupBy::ChromosomeOfMate => {
+                if !read.flags.is_segmented() || read.flags.is_mate_unmapped() {
+                    return None;
+                }
+                let mate_contig = read.read.mate_reference_sequence_id()?.ok()?;
+                Some(
+                    self.reference_sequence_names
+                        .get(mate_contig)
+                        .cloned()
+                        .unwrap_or_else(|| mate_contig.to_string()),
+                )
+            }
+        }
+    }
+}
+
+/// Split items into groups by key, keeping the item order within groups.
+/// Groups are sorted by key (numerically if possible). Items without a key go last.
+fn split_groups(
+    items: impl IntoIterator<Item = usize>,
+    key: impl Fn(usize) -> Option<String>,
+) -> Vec<(Option<String>, Vec<usize>)> {
+    let mut groups: Vec<(Option<String>, Vec<usize>)> = Vec::new();
+    let mut group_indexes: HashMap<Option<String>, usize> = HashMap::new();
+    for item in items {
+        let key = key(item);
+        match group_indexes.entry(key.clone()) {
+            Entry::Occupied(oe) => groups[*oe.get()].1.push(item),
+            Entry::Vacant(ve) => {
+                ve.insert(groups.len());
+                groups.push((key, vec![item]));
+            }
+        }
+    }
+
+    groups.sort_by(|(a, _), (b, _)| compare_tag_values(a.clone(), b.clone()));
+    groups
+}
+
+/// Stack groups one below another. Each group starts with a label track.
+/// stack: items of a group -> ys of all items (only ys of the group items are used).
+/// Returns item ys and (label y, label) of each group.
+fn stack_groups(
+    groups: Vec<(Option<String>, Vec<usize>)>,
+    n_items: usize,
+    group_by: &AlignmentGroupBy,
+    stack: impl Fn(&[usize]) -> Vec<usize>,
+) -> (Vec<usize>, Vec<(usize, String)>) {
+    let mut ys = vec![0; n_items];
+    let mut labels = Vec::new();
+    let mut offset = 0;
+    for (key, items) in groups {
+        labels.push((
+            offset,
+            format!("{}: {}", group_by, key.as_deref().unwrap_or("none")),
+        ));
+
+        let group_ys = stack(&items);
+        let mut group_depth = 0;
+        for item in items {
+            ys[item] = offset + 1 + group_ys[item];
+            group_depth = usize::max(group_depth, group_ys[item] + 1);
+        }
+        offset += group_depth + 1;
+    }
+
+    (ys, labels)
+}
+
+/// Expected range of absolute insert sizes: the 0.5th and 99.5th percentiles.
+fn calculate_insert_size_bounds(reads: &[AlignedRead]) -> Option<(u64, u64)> {
+    let insert_sizes = reads
+        .iter()
+        .filter_map(|read| read.insert_size())
+        .sorted()
+        .collect_vec();
+    if insert_sizes.is_empty() {
+        return None;
+    }
+
+    let percentile =
+        |p: usize| insert_sizes[usize::min(insert_sizes.len() * p / 1000, insert_sizes.len() - 1)];
+    Some((percentile(5), percentile(995)))
+}
+
+/// Compare optional values. Missing values go last.
+fn compare_missing_last<T: Ord>(a: Option<T>, b: Option<T>) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+/// Compare tag values numerically if both are numbers, otherwise as strings.
+fn compare_tag_values(a: Option<String>, b: Option<String>) -> Ordering {
+    if let (Some(a), Some(b)) = (&a, &b)
+        && let (Ok(a), Ok(b)) = (a.parse::<f64>(), b.parse::<f64>())
+    {
+        return a.partial_cmp(&b).unwrap_or(Ordering::Equal);
+    }
+    compare_missing_last(a, b)
+}
+
+/// First in pair, second in pair, then unpaired.
+fn read_order(read: &AlignedRead) -> u8 {
+    if !read.flags.is_segmented() {
+        2
+    } else if read.flags.is_first_segment() {
+        0
+    } else {
+        1
+    }
+}
+
+/// Mates on other contigs first (grouped by the mate contig), then mates on the same contig,
+/// then unpaired reads or reads with unmapped mates.
+fn mate_contig_key(read: &AlignedRead) -> (u8, usize) {
+    if !read.flags.is_segmented() || read.flags.is_mate_unmapped() {
+        return (2, 0);
+    }
+
+    match (
+        read.read.reference_sequence_id().and_then(|id| id.ok()),
+        read.read
+            .mate_reference_sequence_id()
+            .and_then(|id| id.ok()),
+    ) {
+        (Some(contig), Some(mate_contig)) if contig != mate_contig => (0, mate_contig),
+        (_, Some(_)) => (1, 0),
+        _ => (2, 0),
     }
 }
 
@@ -380,51 +918,52 @@ pub fn calculate_mate_map(reads: &Vec<AlignedRead>) -> Result<Vec<usize>, TGVErr
     Ok(output)
 }
 
-fn stack_tracks_for_reads(reads: &Vec<AlignedRead>, show_reads: &Vec<bool>) -> Vec<usize> {
+/// Stack reads into tracks. Reads are placed in the given order, so that earlier reads take upper tracks.
+fn stack_tracks_for_reads(
+    reads: &[AlignedRead],
+    show_reads: &[bool],
+    order: impl IntoIterator<Item = usize>,
+) -> Vec<usize> {
     let mut track_left_bounds: Vec<u64> = Vec::new();
     let mut track_right_bounds: Vec<u64> = Vec::new();
 
-    let ys = reads
-        .iter()
-        .zip(show_reads.iter())
-        .map(|(read, show_read)| {
-            if *show_read {
-                find_track(
-                    read.stacking_start(),
-                    read.stacking_end(),
-                    &mut track_left_bounds,
-                    &mut track_right_bounds,
-                    3,
-                )
-            } else {
-                0
-            }
-        })
-        .collect::<Vec<usize>>();
+    let mut ys = vec![0; reads.len()];
+    for i in order {
+        if show_reads[i] {
+            ys[i] = find_track(
+                reads[i].stacking_start(),
+                reads[i].stacking_end(),
+                &mut track_left_bounds,
+                &mut track_right_bounds,
+                3,
+            );
+        }
+    }
 
     ys
 }
-fn stack_tracks_for_paired_reads(reads: &Vec<ReadPair>, show_reads: &Vec<bool>) -> Vec<usize> {
+
+/// Stack read pairs into tracks. Pairs are placed in the given order.
+fn stack_tracks_for_paired_reads(
+    reads: &[ReadPair],
+    show_reads: &[bool],
+    order: impl IntoIterator<Item = usize>,
+) -> Vec<usize> {
     let mut track_left_bounds: Vec<u64> = Vec::new();
     let mut track_right_bounds: Vec<u64> = Vec::new();
 
-    let ys = reads
-        .iter()
-        .zip(show_reads.iter())
-        .map(|(read, show_read)| {
-            if *show_read {
-                find_track(
-                    read.stacking_start,
-                    read.stacking_end,
-                    &mut track_left_bounds,
-                    &mut track_right_bounds,
-                    10, // larger gap to make viewing easier
-                )
-            } else {
-                0
-            }
-        })
-        .collect::<Vec<usize>>();
+    let mut ys = vec![0; reads.len()];
+    for i in order {
+        if show_reads[i] {
+            ys[i] = find_track(
+                reads[i].stacking_start,
+                reads[i].stacking_end,
+                &mut track_left_bounds,
+                &mut track_right_bounds,
+                10, // larger gap to make viewing easier
+            );
+        }
+    }
 
     ys
 }
@@ -455,3 +994,289 @@ fn find_track(
     track_right_bounds.push(end);
     track_left_bounds.len()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{self, modification_data, sequence};
+    use noodles::core::Position;
+    use noodles::sam::{
+        self,
+        alignment::{
+            record::{
+                Flags, MappingQuality,
+                cigar::{Op, op::Kind},
+            },
+            record_buf::{Cigar, Sequence as SequenceBuf},
+        },
+    };
+    use rstest::rstest;
+
+    const REFERENCE: &[u8] = b"ACGTACGTACGTACGTACGT";
+
+    /// Read with a 10M alignment starting at position 1.
+    fn read(index: usize, name: &str, sequence: &[u8], mapq: u8, reverse: bool) -> AlignedRead {
+        let flags = if reverse {
+            Flags::REVERSE_COMPLEMENTED
+        } else {
+            Flags::empty()
+        };
+        let record_buf = sam::alignment::RecordBuf::builder()
+            .set_name(name)
+            .set_flags(flags)
+            .set_alignment_start(Position::MIN)
+            .set_mapping_quality(MappingQuality::new(mapq).unwrap())
+            .set_cigar(Cigar::from(vec![Op::new(Kind::Match, sequence.len())]))
+            .set_sequence(SequenceBuf::from(sequence))
+            .build();
+        aligned_read(index, record_buf)
+    }
+
+    /// Forward read with a 10M alignment starting at position 1 and modification calls.
+    fn modified_read(index: usize, sequence: &[u8], mm: &str, ml: Vec<u8>) -> AlignedRead {
+        let record_buf = sam::alignment::RecordBuf::builder()
+            .set_name(format!("m{}", index))
+            .set_alignment_start(Position::MIN)
+            .set_cigar(Cigar::from(vec![Op::new(Kind::Match, sequence.len())]))
+            .set_sequence(SequenceBuf::from(sequence))
+            .set_data(modification_data(mm, ml))
+            .build();
+        aligned_read(index, record_buf)
+    }
+
+    fn aligned_read(index: usize, record_buf: sam::alignment::RecordBuf) -> AlignedRead {
+        test_utils::aligned_read(index, &record_buf, &reference())
+    }
+
+    fn reference() -> Sequence {
+        sequence(REFERENCE)
+    }
+
+    /// r0: reference, MAPQ 10, forward
+    /// r1: C>T at 2, MAPQ 60, reverse
+    /// r2: reference, MAPQ 30, reverse
+    /// r3: C>G at 2, MAPQ 20, forward
+    /// r4: C>T at 2, MAPQ 40, forward
+    fn alignment() -> Alignment {
+        let reads = vec![
+            read(0, "r0", b"ACGTACGTAC", 10, false),
+            read(1, "r1", b"ATGTACGTAC", 60, true),
+            read(2, "r2", b"ACGTACGTAC", 30, true),
+            read(3, "r3", b"AGGTACGTAC", 20, false),
+            read(4, "r4", b"ATGTACGTAC", 40, false),
+        ];
+        Alignment::from_aligned_reads(
+            reads,
+            0,
+            (1, REFERENCE.len() as u64),
+            DownsampledReads::default(),
+            0,
+            &reference(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_strand_coverage() {
+        let coverage = alignment().coverage_at(2).clone();
+        assert_eq!(
+            [b'C', b'T', b'G']
+                .map(|base| (coverage.count_forward(base), coverage.count_reverse(base))),
+            [(1, 1), (1, 1), (1, 0)]
+        );
+    }
+
+    #[test]
+    fn test_downsampled_coverage_while_filtered() {
+        let mut dropped = BaseCoverage::new(b'C');
+        dropped.C = 3;
+        dropped.total = 3;
+        let mut alignment = alignment();
+        alignment.downsampled = DownsampledReads {
+            count: 3,
+            coverage: HashMap::from([(2, dropped)]),
+        };
+        alignment.build_coverage(&reference()).unwrap();
+        assert_eq!(alignment.coverage_at(2).total, 8);
+
+        // Dropped reads can't be filtered, so they are left out.
+        alignment
+            .filter(&AlignmentFilter::Base(2, 'T'), &reference())
+            .unwrap();
+        assert_eq!(alignment.coverage_at(2).total, 2);
+
+        alignment.reset(&reference()).unwrap();
+        assert_eq!(alignment.coverage_at(2).total, 8);
+    }
+
+    #[rstest]
+    #[case(AlignmentSort::Default, vec![1, 2, 3, 4, 5])]
+    #[case(AlignmentSort::MappingQuality, vec![5, 1, 3, 4, 2])]
+    #[case(AlignmentSort::MappingQuality.reverse(), vec![1, 5, 3, 2, 4])]
+    #[case(AlignmentSort::ReadName.reverse(), vec![5, 4, 3, 2, 1])]
+    #[case(AlignmentSort::BaseAt(2), vec![4, 1, 5, 3, 2])]
+    #[case(AlignmentSort::BaseAt(1), vec![1, 2, 3, 4, 5])]
+    #[case(AlignmentSort::StrandAt(2).then(AlignmentSort::MappingQuality), vec![3, 4, 5, 2, 1])]
+    #[case(AlignmentSort::BaseAt(2).then(AlignmentSort::StrandAt(2)), vec![4, 2, 5, 3, 1])]
+    fn test_sort(#[case] sort: AlignmentSort, #[case] expected_ys: Vec<usize>) {
+        let mut alignment = alignment();
+        alignment.sort(&sort).unwrap();
+
+        assert_eq!(alignment.ys, expected_ys);
+        for (y, read_indexes) in alignment.ys_index.iter().enumerate() {
+            for i_read in read_indexes {
+                assert_eq!(alignment.ys[*i_read], y);
+            }
+        }
+    }
+
+    #[test]
+    fn test_sort_after_filter() {
+        let mut alignment = alignment();
+        alignment
+            .filter(&AlignmentFilter::Base(2, 'T'), &reference())
+            .unwrap()
+            .sort(&AlignmentSort::MappingQuality.reverse())
+            .unwrap();
+
+        assert_eq!(alignment.ys_index, vec![vec![], vec![4], vec![1]]);
+    }
+
+    #[rstest]
+    #[case(AlignmentSort::Default, vec![2, 7, 8, 3, 4])]
+    #[case(AlignmentSort::MappingQuality, vec![4, 7, 8, 3, 2])]
+    #[case(AlignmentSort::BaseAt(2), vec![4, 7, 8, 3, 2])]
+    fn test_sort_in_groups(#[case] sort: AlignmentSort, #[case] expected_ys: Vec<usize>) {
+        let mut alignment = alignment();
+        alignment
+            .apply_options(
+                &vec![
+                    AlignmentDisplayOption::Sort(sort),
+                    AlignmentDisplayOption::GroupBy(AlignmentGroupBy::Strand),
+                ],
+                &reference(),
+            )
+            .unwrap();
+
+        assert_eq!(alignment.ys, expected_ys);
+        assert_eq!(
+            alignment.group_labels,
+            vec![(0, "Strand: +".to_string()), (5, "Strand: -".to_string())]
+        );
+
+        alignment.reset(&reference()).unwrap();
+        assert!(alignment.group_labels.is_empty());
+    }
+
+    #[test]
+    fn test_group_by_base() {
+        let mut alignment = alignment();
+        alignment
+            .apply_options(
+                &vec![AlignmentDisplayOption::GroupBy(AlignmentGroupBy::BaseAt(2))],
+                &reference(),
+            )
+            .unwrap();
+
+        assert_eq!(
+            alignment.group_labels,
+            vec![
+                (0, "Base at 2: C".to_string()),
+                (4, "Base at 2: G".to_string()),
+                (7, "Base at 2: T".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_group_methylation() {
+        // The C allele at 2 is methylated at the CpG at 6, the T allele is not.
+        let reads = vec![
+            modified_read(0, b"ACGTACGTAC", "C+m?,1;", vec![250]),
+            modified_read(1, b"ATGTACGTAC", "C+m?,0;", vec![10]),
+            modified_read(2, b"ACGTACGTAC", "C+m?,1;", vec![200]),
+        ];
+        let mut alignment = Alignment::from_aligned_reads(
+            reads,
+            0,
+            (1, REFERENCE.len() as u64),
+            DownsampledReads::default(),
+            0,
+            &reference(),
+        )
+        .unwrap();
+        assert_eq!(alignment.group_methylation_in(1, 10).count(), 0);
+
+        alignment
+            .apply_options(
+                &vec![AlignmentDisplayOption::GroupBy(AlignmentGroupBy::BaseAt(2))],
+                &reference(),
+            )
+            .unwrap();
+        let site = |modified, valid_coverage| MethylationSite {
+            position: 6,
+            modified,
+            valid_coverage,
+        };
+        assert_eq!(
+            alignment.group_methylation_in(1, 10).collect::<Vec<_>>(),
+            vec![
+                ("Base at 2: C", &[site(2, 2)][..]),
+                ("Base at 2: T", &[site(0, 1)][..]),
+            ]
+        );
+        assert_eq!(alignment.group_methylation_in(7, 10).next().unwrap().1, &[]);
+
+        alignment.reset(&reference()).unwrap();
+        assert_eq!(alignment.group_methylation_in(1, 10).count(), 0);
+    }
+
+    #[rstest]
+    #[case(1, 1, Some((1, vec![(b'A', 5)])))]
+    #[case(2, 2, Some((2, vec![(b'C', 2), (b'T', 2), (b'G', 1)])))]
+    #[case(1, 4, Some((2, vec![(b'C', 2), (b'T', 2), (b'G', 1)])))]
+    #[case(3, 4, Some((3, vec![(b'G', 5)])))]
+    #[case(11, 20, None)]
+    fn test_alleles_in(
+        #[case] left: u64,
+        #[case] right: u64,
+        #[case] expected: Option<(u64, Vec<(u8, usize)>)>,
+    ) {
+        assert_eq!(alignment().alleles_in(left, right), expected);
+    }
+
+    #[rstest]
+    #[case(0.2, vec![2])]
+    #[case(0.6, vec![2])]
+    #[case(0.7, vec![])]
+    fn test_alt_allele_positions(#[case] threshold: f64, #[case] expected: Vec<u64>) {
+        let alignment = alignment();
+        assert_eq!(
+            alignment
+                .alt_allele_positions(threshold, false)
+                .collect::<Vec<_>>(),
+            expected
+        );
+        // No base qualities: falls back to counts.
+        assert_eq!(
+            alignment
+                .alt_allele_positions(threshold, true)
+                .collect::<Vec<_>>(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_split_groups() {
+        let keys = [Some("10"), Some("2"), None, Some("2"), Some("1")];
+        assert_eq!(
+            split_groups(0..keys.len(), |i| keys[i].map(|key| key.to_string())),
+            vec![
+                (Some("1".to_string()), vec![4]),
+                (Some("2".to_string()), vec![1, 3]),
+                (Some("10".to_string()), vec![0]),
+                (None, vec![2]),
+            ]
+        );
+    }
+}
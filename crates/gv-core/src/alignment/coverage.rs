@@ -1,15 +1,18 @@
 use crate::error::TGVError;
 use crate::sequence::Sequence;
-use noodles::bam::record::{self};
+use noodles::bam::Record;
 use noodles::sam::alignment::record::cigar::{Op, op::Kind};
+use std::cmp::Reverse;
 use std::collections::HashMap;
 use std::default::Default;
 
+/// Bases with a known base quality below min_base_quality are not counted.
 /// See: https://samtools.github.io/hts-specs/SAMv1.pdf
 pub fn calculate_basewise_coverage(
     reference_start: u64, // 1-based. Alignment start, not softclip start
     cigars: &Vec<Op>,
-    sequence: &record::Sequence,
+    record: &Record,
+    min_base_quality: u8,
     reference_sequence: &Sequence,
 ) -> Result<HashMap<u64, BaseCoverage>, TGVError> {
     let mut output: HashMap<u64, BaseCoverage> = HashMap::new();
@@ -17,6 +20,11 @@ pub fn calculate_basewise_coverage(
         return Ok(output);
     }
 
+    let sequence = record.sequence();
+    let quality_scores = record.quality_scores();
+    let quality_scores: &[u8] = quality_scores.as_ref();
+    let is_reverse = record.flags().is_reverse_complemented();
+
     let mut reference_pivot: usize = reference_start as usize;
     let mut query_pivot: usize = 1; // 1-based. # bases on the sequence. Note that need to substract leading softclips to get aligned base coordinate.
 
@@ -91,6 +99,13 @@ pub fn calculate_basewise_coverage(
             Kind::SequenceMismatch | Kind::SequenceMatch | Kind::Match => {
                 for i in 0..len {
                     let base_coordinate = reference_pivot + i;
+                    let quality = quality_scores
+                        .get(query_pivot + i - 1)
+                        .copied()
+                        .filter(|quality| *quality != 255);
+                    if quality.is_some_and(|quality| quality < min_base_quality) {
+                        continue;
+                    }
                     output
                         .entry(base_coordinate as u64)
                         .or_insert(BaseCoverage::new(
@@ -99,7 +114,11 @@ pub fn calculate_basewise_coverage(
                                 .base_at(base_coordinate as u64)
                                 .unwrap_or(b'N'),
                         ))
-                        .update(sequence.get(query_pivot + i - 1).unwrap())
+                        .update(
+                            sequence.get(query_pivot + i - 1).unwrap(),
+                            quality,
+                            is_reverse,
+                        )
                 }
             }
             Kind::HardClip | Kind::Pad => {}
@@ -122,18 +141,31 @@ pub struct BaseCoverage {
 
     pub N: usize,
 
+    // Reverse-strand counts. Forward-strand counts are the differences.
+    pub A_reverse: usize,
+    pub T_reverse: usize,
+    pub C_reverse: usize,
+    pub G_reverse: usize,
+    pub N_reverse: usize,
+
     // total coverage, exluding softclips
     pub total: usize,
 
     // Softclip count
     pub softclip: usize,
 
+    // Sum of base qualities, excluding softclips, and of the reference bases among them
+    pub quality: usize,
+    pub reference_quality: usize,
+
     // reference_base
     pub reference_base: u8,
 }
 
 impl BaseCoverage {
-    pub const MAX_DISPLAY_ALLELE_FREQUENCY_RECIPROCOL: usize = 100;
+    /// IGV default: highlight positions where at least 20% of the bases are not the reference.
+    pub const DEFAULT_ALLELE_FREQUENCY_THRESHOLD: f64 = 0.2;
+
     pub fn new(reference_base: u8) -> Self {
         Self {
             A: 0,
@@ -141,13 +173,22 @@ impl BaseCoverage {
             C: 0,
             G: 0,
             N: 0,
+            A_reverse: 0,
+            T_reverse: 0,
+            C_reverse: 0,
+            G_reverse: 0,
+            N_reverse: 0,
             total: 0,
             softclip: 0,
+            quality: 0,
+            reference_quality: 0,
             reference_base,
         }
     }
 
-    pub fn update(&mut self, base: u8) {
+    /// quality: Phred base quality. None or 255: unknown.
+    /// is_reverse: whether the read is on the reverse strand.
+    pub fn update(&mut self, base: u8, quality: Option<u8>, is_reverse: bool) {
         match base {
             b'A' | b'a' => self.A += 1,
             b'T' | b't' => self.T += 1,
@@ -157,7 +198,25 @@ impl BaseCoverage {
             _ => self.N += 1,
         }
 
+        if is_reverse {
+            match base {
+                b'A' | b'a' => self.A_reverse += 1,
+                b'T' | b't' => self.T_reverse += 1,
+                b'C' | b'c' => self.C_reverse += 1,
+                b'G' | b'g' => self.G_reverse += 1,
+
+                _ => self.N_reverse += 1,
+            }
+        }
+
         self.total += 1;
+
+        if let Some(quality) = quality.filter(|quality| *quality != 255) {
+            self.quality += quality as usize;
+            if base.eq_ignore_ascii_case(&self.reference_base) {
+                self.reference_quality += quality as usize;
+            }
+        }
     }
 
     pub fn update_softclip(&mut self, base: u8) {
@@ -169,8 +228,82 @@ impl BaseCoverage {
         self.T += other.T;
         self.C += other.C;
         self.G += other.G;
+        self.N += other.N;
+        self.A_reverse += other.A_reverse;
+        self.T_reverse += other.T_reverse;
+        self.C_reverse += other.C_reverse;
+        self.G_reverse += other.G_reverse;
+        self.N_reverse += other.N_reverse;
         self.total += other.total;
         self.softclip += other.softclip;
+        self.quality += other.quality;
+        self.reference_quality += other.reference_quality;
+    }
+
+    /// Fraction of the bases that are not the reference. 0 if the reference base is unknown.
+    /// quality_weighted: weigh each base by its base quality, as IGV does. Falls back to counts
+    /// without base qualities.
+    pub fn alt_allele_frequency(&self, quality_weighted: bool) -> f64 {
+        if !matches!(
+            self.reference_base.to_ascii_uppercase(),
+            b'A' | b'T' | b'C' | b'G'
+        ) {
+            return 0.0;
+        }
+
+        let (reference, total) = if quality_weighted && self.quality > 0 {
+            (self.reference_quality, self.quality)
+        } else {
+            (self.count(self.reference_base), self.total)
+        };
+        if total == 0 {
+            0.0
+        } else {
+            (total - reference) as f64 / total as f64
+        }
+    }
+
+    /// Depth of a base (case-insensitive). Other characters are counted as N.
+    pub fn count(&self, base: u8) -> usize {
+        match base {
+            b'A' | b'a' => self.A,
+            b'T' | b't' => self.T,
+            b'C' | b'c' => self.C,
+            b'G' | b'g' => self.G,
+            _ => self.N,
+        }
+    }
+
+    /// Reverse-strand depth of a base (case-insensitive). Other characters are counted as N.
+    pub fn count_reverse(&self, base: u8) -> usize {
+        match base {
+            b'A' | b'a' => self.A_reverse,
+            b'T' | b't' => self.T_reverse,
+            b'C' | b'c' => self.C_reverse,
+            b'G' | b'g' => self.G_reverse,
+            _ => self.N_reverse,
+        }
+    }
+
+    /// Forward-strand depth of a base (case-insensitive). Other characters are counted as N.
+    pub fn count_forward(&self, base: u8) -> usize {
+        self.count(base) - self.count_reverse(base)
+    }
+
+    /// Covered bases, from the most to the least common. Ties: the reference base first.
+    pub fn alleles(&self) -> Vec<(u8, usize)> {
+        let mut alleles: Vec<(u8, usize)> = [b'A', b'T', b'C', b'G', b'N']
+            .into_iter()
+            .map(|base| (base, self.count(base)))
+            .filter(|(_, count)| *count > 0)
+            .collect();
+        alleles.sort_by_key(|(base, count)| {
+            (
+                Reverse(*count),
+                !base.eq_ignore_ascii_case(&self.reference_base),
+            )
+        });
+        alleles
     }
 
     pub fn max_alt_depth(&self) -> Option<usize> {
@@ -183,11 +316,23 @@ impl BaseCoverage {
         }
     }
 
+    /// Base counts, with forward / reverse counts of the covered bases to show strand bias.
     pub fn describe(&self) -> String {
-        format!(
-            "A:{}, T:{}, C:{}, G:{}, N:{}, total:{}",
-            self.A, self.T, self.C, self.G, self.N, self.total
-        )
+        let counts = [b'A', b'T', b'C', b'G', b'N']
+            .into_iter()
+            .map(|base| match self.count(base) {
+                0 => format!("{}:0", base as char),
+                count => format!(
+                    "{}:{} (+{}/-{})",
+                    base as char,
+                    count,
+                    self.count_forward(base),
+                    self.count_reverse(base)
+                ),
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!("{}, total:{}", counts, self.total)
     }
 }
 
@@ -203,7 +348,32 @@ pub static DEFAULT_COVERAGE: BaseCoverage = BaseCoverage {
     C: 0,
     G: 0,
     N: 0,
+    A_reverse: 0,
+    T_reverse: 0,
+    C_reverse: 0,
+    G_reverse: 0,
+    N_reverse: 0,
     total: 0,
     softclip: 0,
+    quality: 0,
+    reference_quality: 0,
     reference_base: b'N',
 };
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(&[], "A:0, T:0, C:0, G:0, N:0, total:0")]
+    #[case(&[(b'A', false), (b'A', true), (b'A', true)], "A:3 (+1/-2), T:0, C:0, G:0, N:0, total:3")]
+    #[case(&[(b'C', false), (b'n', true)], "A:0, T:0, C:1 (+1/-0), G:0, N:1 (+0/-1), total:2")]
+    fn test_describe(#[case] bases: &[(u8, bool)], #[case] expected: &str) {
+        let mut coverage = BaseCoverage::new(b'A');
+        for (base, is_reverse) in bases {
+            coverage.update(*base, None, *is_reverse);
+        }
+        assert_eq!(coverage.describe(), expected);
+    }
+}
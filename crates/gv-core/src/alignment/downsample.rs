@@ -0,0 +1,175 @@
+use crate::alignment::coverage::{BaseCoverage, calculate_basewise_coverage};
+use crate::error::TGVError;
+use crate::sequence::Sequence;
+use noodles::bam::Record;
+use noodles::sam::alignment::record::cigar::Op;
+use std::collections::{HashMap, HashSet, hash_map::Entry};
+
+/// Downsampling of deep alignments: at most max_reads_per_window reads starting in each window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Downsampling {
+    pub max_reads_per_window: usize,
+
+    /// Window width in bases.
+    pub window: u64,
+}
+
+impl Downsampling {
+    pub const DEFAULT_WINDOW: u64 = 50;
+
+    /// Whether to keep each read, given its start (1-based) and name.
+    /// Each window keeps the reads with the smallest read-name hashes, so sampling is deterministic.
+    /// A name kept in any window is kept everywhere, so mates stay together even in different
+    /// windows. This can exceed the maximum in some windows.
+    pub fn sample<'a>(&self, reads: impl IntoIterator<Item = (u64, &'a [u8])>) -> Vec<bool> {
+        let mut windows: HashMap<u64, Vec<u64>> = HashMap::new();
+        let mut hashes = Vec::new();
+        for (start, name) in reads {
+            let hash = name_hash(name);
+            windows
+                .entry(start.saturating_sub(1) / self.window)
+                .or_default()
+                .push(hash);
+            hashes.push(hash);
+        }
+
+        let mut kept_names: HashSet<u64> = HashSet::new();
+        for window_hashes in windows.values_mut() {
+            window_hashes.sort_unstable();
+            let max_hash = window_hashes
+                [usize::min(self.max_reads_per_window, window_hashes.len()).saturating_sub(1)];
+            kept_names.extend(window_hashes.iter().take_while(|hash| **hash <= max_hash));
+        }
+
+        hashes
+            .iter()
+            .map(|hash| kept_names.contains(hash))
+            .collect()
+    }
+}
+
+/// FNV-1a hash of a read name. Stable across runs and platforms.
+fn name_hash(name: &[u8]) -> u64 {
+    name.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Reads dropped by downsampling. They are not displayed, but still count towards the coverage.
+#[derive(Debug, Default)]
+pub struct DownsampledReads {
+    pub count: usize,
+
+    /// Coverage of the dropped reads. Keys are 1-based.
+    pub coverage: HashMap<u64, BaseCoverage>,
+}
+
+impl DownsampledReads {
+    pub fn add(
+        &mut self,
+        record: &Record,
+        min_base_quality: u8,
+        reference_sequence: &Sequence,
+    ) -> Result<(), TGVError> {
+        self.count += 1;
+
+        let Some(Ok(start)) = record.alignment_start() else {
+            return Ok(());
+        };
+        let cigars = record
+            .cigar()
+            .iter()
+            .collect::<Result<Vec<Op>, _>>()
+            .map_err(|e| TGVError::IOError(e.to_string()))?;
+
+        for (position, coverage) in calculate_basewise_coverage(
+            start.get() as u64,
+            &cigars,
+            record,
+            min_base_quality,
+            reference_sequence,
+        )? {
+            match self.coverage.entry(position) {
+                Entry::Occupied(mut oe) => oe.get_mut().add(&coverage),
+                Entry::Vacant(ve) => {
+                    ve.insert(coverage);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(2, 10, vec![true; 4])]
+    #[case(1, 100, vec![false, false, false, true])]
+    #[case(1, 10, vec![true, false, false, true])]
+    fn test_sample(
+        #[case] max_reads_per_window: usize,
+        #[case] window: u64,
+        #[case] expected: Vec<bool>,
+    ) {
+        let downsampling = Downsampling {
+            max_reads_per_window,
+            window,
+        };
+        let reads: Vec<(u64, &[u8])> =
+            vec![(1, b"read1"), (5, b"read2"), (15, b"read3"), (18, b"read4")];
+        assert_eq!(downsampling.sample(reads.clone()), expected);
+
+        // Mates share the name: both are kept or dropped.
+        let mates: Vec<(u64, &[u8])> = reads
+            .iter()
+            .flat_map(|(start, name)| [(*start, *name), (*start + 1, *name)])
+            .collect();
+        assert_eq!(
+            downsampling
+                .sample(mates)
+                .chunks(2)
+                .map(|pair| pair[0] == pair[1])
+                .collect::<Vec<_>>(),
+            vec![true; 4]
+        );
+    }
+
+    #[test]
+    fn test_sample_keeps_distant_mates_together() {
+        let downsampling = Downsampling {
+            max_reads_per_window: 2,
+            window: 50,
+        };
+        let mut names: Vec<String> = (0..22).map(|i| format!("read{}", i)).collect();
+        names.sort_by_key(|name| name_hash(name.as_bytes()));
+        let (unpaired, pairs) = names.split_at(2);
+
+        // Crowded windows an insert size apart. Unpaired reads outrank the pairs in the first
+        // window, so the pairs kept in the second window are dropped in the first by rank alone.
+        let reads: Vec<(u64, &[u8])> = unpaired
+            .iter()
+            .map(|name| (1, name.as_bytes()))
+            .chain(
+                pairs
+                    .iter()
+                    .flat_map(|name| [(1, name.as_bytes()), (301, name.as_bytes())]),
+            )
+            .collect();
+
+        let keep = downsampling.sample(reads);
+        assert_eq!(&keep[..2], &[true, true]);
+        let pair_keep: Vec<bool> = keep[2..]
+            .chunks(2)
+            .map(|pair| {
+                assert_eq!(pair[0], pair[1]);
+                pair[0]
+            })
+            .collect();
+        assert_eq!(&pair_keep[..2], &[true, true]);
+        assert!(pair_keep[2..].iter().all(|keep| !keep));
+    }
+}
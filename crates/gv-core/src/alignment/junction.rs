@@ -0,0 +1,228 @@
+use crate::{alignment::read::AlignedRead, intervals::GenomeInterval, strand::Strand};
+use std::collections::HashMap;
+
+/// An intron shared by spliced reads (CIGAR N operations).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpliceJunction {
+    pub contig_index: usize,
+
+    /// First intron base. 1-based, inclusive.
+    pub start: u64,
+
+    /// Last intron base. 1-based, inclusive.
+    pub end: u64,
+
+    /// Transcript strand from the XS / ts tags. None: unknown.
+    pub strand: Option<Strand>,
+
+    /// Number of reads spanning the junction.
+    pub count: usize,
+}
+
+impl SpliceJunction {
+    pub fn describe(&self) -> String {
+        let mut description = format!("Splice junction: {}-{}", self.start, self.end);
+        if let Some(strand) = &self.strand {
+            description += &format!(" ({})", strand);
+        }
+        description += &format!(" {} reads, {}bp", self.count, self.end - self.start + 1);
+        description
+    }
+}
+
+impl GenomeInterval for SpliceJunction {
+    fn contig_index(&self) -> usize {
+        self.contig_index
+    }
+
+    fn start(&self) -> u64 {
+        self.start
+    }
+
+    fn end(&self) -> u64 {
+        self.end
+    }
+}
+
+/// Aggregate introns of the shown reads into junctions, sorted by start.
+/// Reads with different transcript strands are counted in different junctions.
+pub fn calculate_junctions(
+    reads: &[AlignedRead],
+    show_read: &[bool],
+    contig_index: usize,
+) -> Vec<SpliceJunction> {
+    let mut counts: HashMap<(u64, u64, Option<Strand>), usize> = HashMap::new();
+    for (read, show_read) in reads.iter().zip(show_read.iter()) {
+        if !*show_read {
+            continue;
+        }
+        let introns = read.introns();
+        if introns.is_empty() {
+            continue;
+        }
+        let strand = read.transcript_strand();
+        for (start, end) in introns {
+            *counts.entry((start, end, strand.clone())).or_insert(0) += 1;
+        }
+    }
+
+    let mut junctions: Vec<SpliceJunction> = counts
+        .into_iter()
+        .map(|((start, end, strand), count)| SpliceJunction {
+            contig_index,
+            start,
+            end,
+            strand,
+            count,
+        })
+        .collect();
+    junctions.sort_by_key(|junction| {
+        let strand_order = match junction.strand {
+            Some(Strand::Forward) => 0,
+            Some(Strand::Reverse) => 1,
+            None => 2,
+        };
+        (junction.start, junction.end, strand_order)
+    });
+    junctions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sequence::Sequence;
+    use crate::test_utils::aligned_read;
+    use noodles::core::Position;
+    use noodles::sam::{
+        self,
+        alignment::{
+            record::{
+                Flags,
+                cigar::{Op, op::Kind},
+                data::field::Tag,
+            },
+            record_buf::{Cigar, Sequence as SequenceBuf, data::field::Value},
+        },
+    };
+    use rstest::rstest;
+
+    /// Read at position 1 with a CIGAR and an optional strand tag.
+    fn read(
+        index: usize,
+        cigar: Vec<Op>,
+        reverse: bool,
+        tag: Option<([u8; 2], u8)>,
+    ) -> AlignedRead {
+        let flags = if reverse {
+            Flags::REVERSE_COMPLEMENTED
+        } else {
+            Flags::empty()
+        };
+        let read_length = cigar
+            .iter()
+            .filter(|op| op.kind().consumes_read())
+            .map(|op| op.len())
+            .sum::<usize>();
+        let record_buf = sam::alignment::RecordBuf::builder()
+            .set_name("read")
+            .set_flags(flags)
+            .set_alignment_start(Position::MIN)
+            .set_cigar(Cigar::from(cigar))
+            .set_sequence(SequenceBuf::from(vec![b'A'; read_length]))
+            .set_data(
+                tag.into_iter()
+                    .map(|(tag, value)| (Tag::from(tag), Value::Character(value)))
+                    .collect(),
+            )
+            .build();
+        aligned_read(index, &record_buf, &Sequence::default())
+    }
+
+    fn spliced(skip: usize) -> Vec<Op> {
+        vec![
+            Op::new(Kind::SoftClip, 2),
+            Op::new(Kind::Match, 10),
+            Op::new(Kind::Skip, skip),
+            Op::new(Kind::Match, 5),
+            Op::new(Kind::Deletion, 2),
+            Op::new(Kind::Match, 5),
+        ]
+    }
+
+    #[test]
+    fn test_introns() {
+        let read = read(
+            0,
+            vec![
+                Op::new(Kind::Match, 10),
+                Op::new(Kind::Skip, 100),
+                Op::new(Kind::Insertion, 3),
+                Op::new(Kind::Match, 5),
+                Op::new(Kind::Skip, 50),
+                Op::new(Kind::Match, 5),
+            ],
+            false,
+            None,
+        );
+        assert_eq!(read.introns(), vec![(11, 110), (116, 165)]);
+    }
+
+    #[rstest]
+    #[case(false, None, None)]
+    #[case(false, Some((*b"XS", b'-')), Some(Strand::Reverse))]
+    #[case(true, Some((*b"XS", b'+')), Some(Strand::Forward))]
+    #[case(false, Some((*b"ts", b'+')), Some(Strand::Forward))]
+    #[case(true, Some((*b"ts", b'+')), Some(Strand::Reverse))]
+    #[case(true, Some((*b"ts", b'-')), Some(Strand::Forward))]
+    #[case(false, Some((*b"XS", b'?')), None)]
+    fn test_transcript_strand(
+        #[case] reverse: bool,
+        #[case] tag: Option<([u8; 2], u8)>,
+        #[case] expected: Option<Strand>,
+    ) {
+        assert_eq!(
+            read(0, spliced(100), reverse, tag).transcript_strand(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_calculate_junctions() {
+        let reads = vec![
+            read(0, spliced(100), false, Some((*b"XS", b'+'))),
+            read(1, spliced(100), true, Some((*b"XS", b'+'))),
+            read(2, spliced(100), false, Some((*b"XS", b'-'))),
+            read(3, spliced(50), false, None),
+            read(4, spliced(50), false, None),
+            read(5, vec![Op::new(Kind::Match, 20)], false, None),
+        ];
+
+        let junctions = calculate_junctions(&reads, &[true; 6], 0);
+        assert_eq!(
+            junctions
+                .iter()
+                .map(|junction| (
+                    junction.start,
+                    junction.end,
+                    junction.strand.clone(),
+                    junction.count
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                (11, 60, None, 2),
+                (11, 110, Some(Strand::Forward), 2),
+                (11, 110, Some(Strand::Reverse), 1),
+            ]
+        );
+
+        // Hidden reads are not counted.
+        let junctions = calculate_junctions(&reads, &[false, true, true, false, false, true], 0);
+        assert_eq!(
+            junctions
+                .iter()
+                .map(|junction| junction.count)
+                .collect::<Vec<_>>(),
+            vec![1, 1]
+        );
+    }
+}
@@ -0,0 +1,198 @@
+use crate::{alignment::read::AlignedRead, modification::ModificationType, sequence::Sequence};
+use std::collections::BTreeMap;
+
+/// 5mC calls of the shown reads at a CpG, with both strands combined.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MethylationSite {
+    /// C of the CpG. 1-based.
+    pub position: u64,
+
+    /// Calls with a modification probability of at least 50%.
+    pub modified: usize,
+
+    /// All calls, modified or not.
+    pub valid_coverage: usize,
+}
+
+impl MethylationSite {
+    /// Probability from the ML tag at which a call is counted as modified (50%).
+    pub const MIN_MODIFIED_PROBABILITY: u8 = 128;
+
+    pub fn fraction_modified(&self) -> f64 {
+        if self.valid_coverage == 0 {
+            0.0
+        } else {
+            self.modified as f64 / self.valid_coverage as f64
+        }
+    }
+
+    /// Calls of sites pooled: (modified, valid coverage).
+    pub fn pool(sites: &[MethylationSite]) -> (usize, usize) {
+        sites
+            .iter()
+            .fold((0, 0), |(modified, valid_coverage), site| {
+                (
+                    modified + site.modified,
+                    valid_coverage + site.valid_coverage,
+                )
+            })
+    }
+}
+
+/// Sites in [left, right] of sites sorted by position.
+/// 1-based, inclusive.
+pub fn sites_in(sites: &[MethylationSite], left: u64, right: u64) -> &[MethylationSite] {
+    let first = sites.partition_point(|site| site.position < left);
+    let last = sites.partition_point(|site| site.position <= right);
+    &sites[first..usize::max(first, last)]
+}
+
+/// Aggregate 5mC calls of the shown reads per CpG, sorted by position.
+/// Calls on the G of a CpG (reverse-strand reads and opposite-strand calls) count towards its C.
+/// Calls outside CpGs, or where the reference is not loaded, are skipped.
+pub fn calculate_methylation(
+    reads: &[AlignedRead],
+    show_read: &[bool],
+    reference_sequence: &Sequence,
+) -> Vec<MethylationSite> {
+    let mut sites: BTreeMap<u64, (usize, usize)> = BTreeMap::new();
+    for (read, show_read) in reads.iter().zip(show_read.iter()) {
+        if !*show_read {
+            continue;
+        }
+        for (position, modifications) in read.base_modifications.iter() {
+            let Some(position) = cpg_position(*position, reference_sequence) else {
+                continue;
+            };
+            for modification in modifications
+                .iter()
+                .filter(|modification| modification.modification_type == ModificationType::FiveMC)
+            {
+                let (modified, valid_coverage) = sites.entry(position).or_default();
+                if modification.probability >= MethylationSite::MIN_MODIFIED_PROBABILITY {
+                    *modified += 1;
+                }
+                *valid_coverage += 1;
+            }
+        }
+    }
+
+    sites
+        .into_iter()
+        .map(|(position, (modified, valid_coverage))| MethylationSite {
+            position,
+            modified,
+            valid_coverage,
+        })
+        .collect()
+}
+
+/// Position of the C of the CpG at position. None: not a CpG, or the reference is not loaded.
+fn cpg_position(position: u64, reference_sequence: &Sequence) -> Option<u64> {
+    let base_at = |position: u64| {
+        reference_sequence
+            .base_at(position)
+            .map(|base| base.to_ascii_uppercase())
+    };
+    match base_at(position) {
+        Some(b'C') if base_at(position + 1) == Some(b'G') => Some(position),
+        Some(b'G') if position > 1 && base_at(position - 1) == Some(b'C') => Some(position - 1),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{aligned_read, modification_data, sequence};
+    use noodles::core::Position;
+    use noodles::sam::{
+        self,
+        alignment::{
+            record::{
+                Flags,
+                cigar::{Op, op::Kind},
+            },
+            record_buf::{Cigar, Sequence as SequenceBuf},
+        },
+    };
+    use rstest::rstest;
+
+    /// CpGs at 2 and 6.
+    const REFERENCE: &[u8] = b"ACGTACGTAC";
+
+    fn reference() -> Sequence {
+        sequence(REFERENCE)
+    }
+
+    /// Read of the whole reference with modification calls.
+    fn read(index: usize, reverse: bool, mm: &str, ml: Vec<u8>) -> AlignedRead {
+        let flags = if reverse {
+            Flags::REVERSE_COMPLEMENTED
+        } else {
+            Flags::empty()
+        };
+        let record_buf = sam::alignment::RecordBuf::builder()
+            .set_name("read")
+            .set_flags(flags)
+            .set_alignment_start(Position::MIN)
+            .set_cigar(Cigar::from(vec![Op::new(Kind::Match, REFERENCE.len())]))
+            .set_sequence(SequenceBuf::from(REFERENCE))
+            .set_data(modification_data(mm, ml))
+            .build();
+        aligned_read(index, &record_buf, &reference())
+    }
+
+    #[rstest]
+    // Forward reads call the C of each CpG.
+    #[case(reference(), vec![true, true], vec![(2, 1, 2), (6, 2, 2)])]
+    // Hidden reads are not counted.
+    #[case(reference(), vec![false, true], vec![(2, 0, 1), (6, 1, 1)])]
+    // Without a reference, CpGs are unknown.
+    #[case(Sequence::default(), vec![true, true], vec![])]
+    fn test_calculate_methylation(
+        #[case] reference_sequence: Sequence,
+        #[case] show_read: Vec<bool>,
+        #[case] expected: Vec<(u64, usize, usize)>,
+    ) {
+        let reads = vec![
+            read(0, false, "C+m?,0,0;", vec![200, 250]),
+            read(1, false, "C+m?,0,0;", vec![10, 128]),
+        ];
+        let sites = calculate_methylation(&reads, &show_read, &reference_sequence);
+        assert_eq!(
+            sites
+                .iter()
+                .map(|site| (site.position, site.modified, site.valid_coverage))
+                .collect::<Vec<_>>(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_strands_are_combined() {
+        // Reverse read: the C of the original read is the G of the CpG on the reference.
+        // Opposite-strand calls on the G of the forward read, and a non-CpG C (the last base).
+        let reads = vec![
+            read(0, true, "C+m?,0;", vec![255]),
+            read(1, false, "G-m?,0;C+m?,2;", vec![255, 255]),
+        ];
+        let sites = calculate_methylation(&reads, &[true, true], &reference());
+        assert_eq!(
+            sites,
+            vec![
+                MethylationSite {
+                    position: 2,
+                    modified: 1,
+                    valid_coverage: 1,
+                },
+                MethylationSite {
+                    position: 6,
+                    modified: 1,
+                    valid_coverage: 1,
+                },
+            ]
+        );
+        assert_eq!(sites[0].fraction_modified(), 1.0);
+    }
+}
@@ -1,12 +1,23 @@
 mod alignment;
 mod coverage;
+mod downsample;
+mod junction;
+mod methylation;
 mod read;
 mod repository;
 
 pub use alignment::Alignment;
 pub use coverage::BaseCoverage;
-pub use read::{AlignedRead, RenderingContext, RenderingContextKind, RenderingContextModifier};
-pub use repository::AlignmentRepositoryEnum;
+pub use downsample::{DownsampledReads, Downsampling};
+pub use junction::SpliceJunction;
+pub use methylation::MethylationSite;
+pub use read::{
+    AlignedRead, PairAnomaly, PairOrientation, RenderingContext, RenderingContextKind,
+    RenderingContextModifier, SupplementaryAlignment,
+};
+pub use repository::{AlignmentRepositoryEnum, is_cram};
 
 // Re-export modification types used by the renderer.
-pub use crate::modification::{BaseModification, ModificationType};
+pub use crate::modification::{
+    BaseModification, ModificationMotif, ModificationThresholds, ModificationType,
+};
@@ -3,6 +3,7 @@ use crate::intervals::{GenomeInterval, Region};
 use crate::message::AlignmentFilter;
 use crate::modification::{BaseModification, parse_modification_data};
 use crate::sequence::Sequence;
+use crate::strand::Strand;
 // use rust_htslib::bam::{record::Seq, Read, Record};
 //
 use itertools::Itertools;
@@ -12,6 +13,7 @@ use noodles::sam::alignment::record::{
     cigar::{Op, op::Kind},
 };
 use std::collections::HashMap;
+use strum::Display;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum RenderingContextModifier {
@@ -107,6 +109,102 @@ pub struct AlignedRead {
     pub base_modifications: HashMap<u64, Vec<BaseModification>>,
 }
 
+/// Orientation of a read pair on the same contig, in the IGV notation.
+/// The first letter is for the leftmost read, the second for the rightmost read.
+/// L: forward strand, R: reverse strand.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Display)]
+pub enum PairOrientation {
+    /// Reads point towards each other (expected for Illumina libraries).
+    LR,
+
+    /// Reads point away from each other.
+    RL,
+
+    /// Both reads on the forward strand.
+    LL,
+
+    /// Both reads on the reverse strand.
+    RR,
+}
+
+/// Another alignment of a chimeric read, from the SA tag.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SupplementaryAlignment {
+    pub contig_name: String,
+
+    /// 1-based, inclusive
+    pub start: u64,
+
+    pub strand: Strand,
+
+    pub cigar: String,
+
+    pub mapping_quality: u8,
+
+    /// NM
+    pub edit_distance: u32,
+}
+
+impl SupplementaryAlignment {
+    /// Parse a segment of the SA tag: rname,pos,strand,CIGAR,mapQ,NM.
+    fn parse(segment: &str) -> Option<Self> {
+        let fields = segment.split(',').collect_vec();
+        if fields.len() != 6 {
+            return None;
+        }
+
+        Some(Self {
+            contig_name: fields[0].to_string(),
+            start: fields[1].parse().ok()?,
+            strand: Strand::from_str(fields[2].to_string()).ok()?,
+            cigar: fields[3].to_string(),
+            mapping_quality: fields[4].parse().ok()?,
+            edit_distance: fields[5].parse().ok()?,
+        })
+    }
+
+    /// 1-based, inclusive. Computed from the reference-consuming CIGAR operations.
+    pub fn end(&self) -> u64 {
+        let mut reference_length = 0;
+        let mut op_length = 0;
+        for c in self.cigar.chars() {
+            match c.to_digit(10) {
+                Some(digit) => op_length = op_length * 10 + digit as u64,
+                None => {
+                    if matches!(c, 'M' | 'D' | 'N' | '=' | 'X') {
+                        reference_length += op_length;
+                    }
+                    op_length = 0;
+                }
+            }
+        }
+        self.start + reference_length.saturating_sub(1)
+    }
+
+    pub fn describe(&self) -> String {
+        format!(
+            "{}:{}({}) {} MAPQ={}",
+            self.contig_name, self.start, self.strand, self.cigar, self.mapping_quality
+        )
+    }
+}
+
+/// Why a read pair is discordant.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PairAnomaly {
+    /// Insert size above the expected range (e.g. deletions)
+    LargeInsert,
+
+    /// Insert size below the expected range (e.g. insertions)
+    SmallInsert,
+
+    /// Mate on another contig (e.g. translocations)
+    MateOnOtherContig,
+
+    /// Orientation other than LR (e.g. inversions, duplications)
+    Orientation(PairOrientation),
+}
+
 #[derive(Clone, Debug)]
 pub struct ReadPair {
     /// Read 1 index in the alignment
@@ -166,14 +264,46 @@ impl AlignedRead {
         // AS = 0
         // Hidden tags: MDLocation = chr20:78,249
         // Base = C @ QV 30
-        Ok(format!(
+        let mut description = format!(
             "{}  Flags={:?}  Start={}  MAPQ={}  Cigar={:?}", // TODO
             self.read.name().unwrap(),
             self.flags,
             self.start,
             self.read.mapping_quality().unwrap().get(),
             self.cigar
-        ))
+        );
+
+        let supplementary_alignments = self.supplementary_alignments();
+        if !supplementary_alignments.is_empty() {
+            description += &format!(
+                "  SA={}",
+                supplementary_alignments
+                    .iter()
+                    .map(|alignment| alignment.describe())
+                    .join("; ")
+            );
+        }
+        Ok(description)
+    }
+
+    /// Other alignments of a chimeric read (SA tag), in the tag order.
+    pub fn supplementary_alignments(&self) -> Vec<SupplementaryAlignment> {
+        self.tag_value(b"SA")
+            .map(|value| {
+                value
+                    .split(';')
+                    .filter_map(SupplementaryAlignment::parse)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Lengths of the leading and trailing clips (soft and hard).
+    pub fn clip_lengths(&self) -> (usize, usize) {
+        (
+            clip_length(self.cigar.iter()),
+            clip_length(self.cigar.iter().rev()),
+        )
     }
 
     /// Whether the alignment segment (excluding softclips) covers a x_coordinate (1-based).
@@ -199,6 +329,30 @@ impl AlignedRead {
         self.flags.is_segmented() && !self.flags.is_supplementary() && !self.flags.is_secondary()
     }
 
+    /// MAPQ. 255 if unavailable.
+    pub fn mapping_quality(&self) -> u8 {
+        self.read.mapping_quality().map(u8::from).unwrap_or(255)
+    }
+
+    /// Value of an auxiliary tag, formatted as a string.
+    /// None if the tag is absent, malformed, or an array.
+    pub fn tag_value(&self, tag: &[u8; 2]) -> Option<String> {
+        use noodles::sam::alignment::record::data::field::Value;
+
+        match self.read.data().get(tag)? {
+            Ok(Value::Character(c)) => Some((c as char).to_string()),
+            Ok(Value::Float(f)) => Some(f.to_string()),
+            Ok(Value::String(s)) | Ok(Value::Hex(s)) => Some(s.to_string()),
+            Ok(Value::Array(_)) | Err(_) => None,
+            Ok(value) => value.as_int().map(|i| i.to_string()),
+        }
+    }
+
+    /// Read group (RG tag)
+    pub fn read_group(&self) -> Option<String> {
+        self.tag_value(b"RG")
+    }
+
     /// Return the base at coordinate.
     /// None: Not covered, deletion, softclip.
     /// Insertion: the inserted sequences are not returned.
@@ -329,6 +483,144 @@ impl AlignedRead {
         false
     }
 
+    /// Introns (CIGAR N operations) of the read.
+    /// 1-based, inclusive.
+    pub fn introns(&self) -> Vec<(u64, u64)> {
+        let mut introns = Vec::new();
+        let mut reference_pivot = self.start;
+        for op in self.cigar.iter() {
+            if op.kind() == Kind::Skip {
+                introns.push((reference_pivot, reference_pivot + op.len() as u64 - 1));
+            }
+            if op.kind().consumes_reference() {
+                reference_pivot += op.len() as u64;
+            }
+        }
+        introns
+    }
+
+    /// Strand of the transcript the read is spliced from.
+    /// XS: transcript strand (STAR, HISAT2). ts: transcript strand relative to the read (minimap2).
+    pub fn transcript_strand(&self) -> Option<Strand> {
+        if let Some(strand) = self.tag_value(b"XS") {
+            return Strand::from_str(strand).ok();
+        }
+
+        match self.tag_value(b"ts")?.as_str() {
+            "+" => Some(self.strand()),
+            "-" => Some(self.strand().reverse()),
+            _ => None,
+        }
+    }
+
+    /// Strand of the read.
+    pub fn strand(&self) -> Strand {
+        if self.flags.is_reverse_complemented() {
+            Strand::Reverse
+        } else {
+            Strand::Forward
+        }
+    }
+
+    /// Whether the mate is mapped to another contig.
+    pub fn mate_on_other_contig(&self) -> bool {
+        if !self.flags.is_segmented() || self.flags.is_mate_unmapped() {
+            return false;
+        }
+        match (
+            self.read.reference_sequence_id().and_then(|id| id.ok()),
+            self.read
+                .mate_reference_sequence_id()
+                .and_then(|id| id.ok()),
+        ) {
+            (Some(contig), Some(mate_contig)) => contig != mate_contig,
+            _ => false,
+        }
+    }
+
+    /// Whether the read and its mate are mapped to the same contig.
+    fn has_mate_on_same_contig(&self) -> bool {
+        self.flags.is_segmented()
+            && !self.flags.is_unmapped()
+            && !self.flags.is_mate_unmapped()
+            && !self.mate_on_other_contig()
+    }
+
+    /// Absolute insert size (TLEN).
+    /// None: the mate is not on the same contig, or the insert size is not available.
+    pub fn insert_size(&self) -> Option<u64> {
+        if !self.has_mate_on_same_contig() {
+            return None;
+        }
+        match self.read.template_length().unsigned_abs() {
+            0 => None,
+            insert_size => Some(insert_size as u64),
+        }
+    }
+
+    /// Orientation of the read and its mate on the same contig.
+    pub fn pair_orientation(&self) -> Option<PairOrientation> {
+        if !self.has_mate_on_same_contig() {
+            return None;
+        }
+        let mate_start = self.read.mate_alignment_start()?.ok()?.get() as u64;
+
+        let (left_reverse, right_reverse) = if self.start <= mate_start {
+            (
+                self.flags.is_reverse_complemented(),
+                self.flags.is_mate_reverse_complemented(),
+            )
+        } else {
+            (
+                self.flags.is_mate_reverse_complemented(),
+                self.flags.is_reverse_complemented(),
+            )
+        };
+
+        Some(match (left_reverse, right_reverse) {
+            (false, true) => PairOrientation::LR,
+            (true, false) => PairOrientation::RL,
+            (false, false) => PairOrientation::LL,
+            (true, true) => PairOrientation::RR,
+        })
+    }
+
+    /// Insert size outside the expected range [lower, upper], or mate on another contig.
+    pub fn insert_size_anomaly(
+        &self,
+        insert_size_bounds: Option<(u64, u64)>,
+    ) -> Option<PairAnomaly> {
+        if self.mate_on_other_contig() {
+            return Some(PairAnomaly::MateOnOtherContig);
+        }
+        let (insert_size, (lower, upper)) = (self.insert_size()?, insert_size_bounds?);
+        if insert_size > upper {
+            Some(PairAnomaly::LargeInsert)
+        } else if insert_size < lower {
+            Some(PairAnomaly::SmallInsert)
+        } else {
+            None
+        }
+    }
+
+    /// Orientation other than LR.
+    pub fn orientation_anomaly(&self) -> Option<PairAnomaly> {
+        match self.pair_orientation()? {
+            PairOrientation::LR => None,
+            orientation => Some(PairAnomaly::Orientation(orientation)),
+        }
+    }
+
+    /// Why the pair is discordant. None: concordant or not paired.
+    /// Mates on other contigs come first, then orientations, then insert sizes (same as IGV).
+    pub fn pair_anomaly(&self, insert_size_bounds: Option<(u64, u64)>) -> Option<PairAnomaly> {
+        if self.mate_on_other_contig() {
+            return Some(PairAnomaly::MateOnOtherContig);
+        }
+        self.orientation_anomaly()
+            .or_else(|| self.insert_size_anomaly(insert_size_bounds))
+    }
+
     pub fn passes_filter(&self, filter: &AlignmentFilter) -> bool {
         match filter {
             AlignmentFilter::Default => true,
@@ -347,7 +639,47 @@ impl AlignedRead {
             AlignmentFilter::BaseAtCurrentPosition(_)
             | AlignmentFilter::BaseAtCurrentPositionSoftClip => true,
 
-            _ => true, // TODO
+            AlignmentFilter::False => false,
+
+            AlignmentFilter::StartsIn(left, right) => {
+                *left as u64 <= self.start && self.start <= *right as u64
+            }
+            AlignmentFilter::EndsIn(left, right) => {
+                *left as u64 <= self.end && self.end <= *right as u64
+            }
+            AlignmentFilter::Overlaps(left, right) => self.overlaps(*left as u64, *right as u64),
+
+            AlignmentFilter::Strand(strand) => match strand {
+                Strand::Forward => !self.flags.is_reverse_complemented(),
+                Strand::Reverse => self.flags.is_reverse_complemented(),
+            },
+
+            // Reads with unavailable MAPQ (255) fail MAPQ filters.
+            AlignmentFilter::MappingQualityGE(mapq) => self
+                .read
+                .mapping_quality()
+                .is_some_and(|quality| u8::from(quality) as u16 >= *mapq),
+            AlignmentFilter::MappingQualityLE(mapq) => self
+                .read
+                .mapping_quality()
+                .is_some_and(|quality| u8::from(quality) as u16 <= *mapq),
+
+            AlignmentFilter::FlagsAll(flags) => u32::from(self.flags.bits()) & flags == *flags,
+            AlignmentFilter::FlagsAny(flags) => u32::from(self.flags.bits()) & flags != 0,
+            AlignmentFilter::FlagsEqual(flags) => u32::from(self.flags.bits()) == *flags,
+
+            AlignmentFilter::Tag(tag, value) => match <&[u8; 2]>::try_from(tag.as_bytes()) {
+                Ok(tag) => self.tag_value(tag).as_ref() == Some(value),
+                Err(_) => false,
+            },
+
+            AlignmentFilter::Not(filter) => !self.passes_filter(filter),
+            AlignmentFilter::And(first, second) => {
+                self.passes_filter(first) && self.passes_filter(second)
+            }
+            AlignmentFilter::Or(first, second) => {
+                self.passes_filter(first) || self.passes_filter(second)
+            }
         }
     }
 
@@ -410,7 +742,8 @@ impl AlignedRead {
 }
 
 /// Parse base modification data from the MM and ML auxiliary tags of a BAM record.
-/// Returns an empty map if the record has no MM/ML tags or if parsing fails.
+/// Returns an empty map if the record has no MM/ML tags or if parsing fails, or if the MN tag
+/// shows that SEQ was hard-clipped after the modifications were called.
 fn extract_base_modifications(
     record: &Record,
     cigars: &[Op],
@@ -444,8 +777,22 @@ fn extract_base_modifications(
     let seq = record.sequence();
     let seq_bytes: Vec<u8> = (0..seq.len()).filter_map(|i| seq.get(i)).collect();
 
-    parse_modification_data(&mm_str, &ml_bytes, &seq_bytes, cigars, alignment_start)
-        .unwrap_or_default()
+    // MN: SEQ length the MM/ML tags apply to.
+    if let Some(Ok(mn)) = data.get(b"MN")
+        && mn.as_int() != Some(seq_bytes.len() as i64)
+    {
+        return HashMap::new();
+    }
+
+    parse_modification_data(
+        &mm_str,
+        &ml_bytes,
+        &seq_bytes,
+        cigars,
+        alignment_start,
+        record.flags().is_reverse_complemented(),
+    )
+    .unwrap_or_default()
 }
 
 /// See: https://samtools.github.io/hts-specs/SAMv1.pdf
@@ -923,6 +1270,13 @@ pub fn get_overlapped_pair_rendering_text(
     }
 }
 
+/// Total length of the clips at the start of CIGAR operations.
+fn clip_length<'a>(ops: impl Iterator<Item = &'a Op>) -> usize {
+    ops.take_while(|op| matches!(op.kind(), Kind::SoftClip | Kind::HardClip))
+        .map(|op| op.len())
+        .sum()
+}
+
 pub fn matches_base(base1: u8, base2: u8) -> bool {
     if base1 == base2 {
         return true;
@@ -961,16 +1315,12 @@ fn can_be_annotated_with_arrows(kind: &Kind) -> bool {
 mod tests {
 
     use super::*;
+    use crate::test_utils::bam_record;
     // use noodles::bam::record::{Cigar, CigarString};
-    use noodles::bam;
     use noodles::sam::{
         self,
-        alignment::{
-            io::Write,
-            record::cigar::{Op, op::Kind},
-        },
+        alignment::record::cigar::{Op, op::Kind},
     };
-    use std::io;
 
     use rstest::rstest;
 
@@ -1154,7 +1504,7 @@ mod tests {
         let record_buf = sam::alignment::RecordBuf::builder()
             .set_sequence(sam::alignment::record_buf::Sequence::from(seq))
             .build();
-        let record = serialize_as_bam_record(&header, &record_buf).unwrap();
+        let record = bam_record(&header, &record_buf);
 
         let contexts = calculate_rendering_contexts(
             reference_start,
@@ -1168,19 +1518,206 @@ mod tests {
         assert_eq!(contexts, expected_rendering_contexts)
     }
 
-    /// Helper function to create bam::Record test cases
-    fn serialize_as_bam_record(
-        header: &sam::Header,
-        record_buf: &sam::alignment::RecordBuf,
-    ) -> io::Result<bam::Record> {
-        let mut writer = bam::io::Writer::from(Vec::new());
-        writer.write_alignment_record(header, record_buf)?;
+    #[rstest]
+    #[case(AlignmentFilter::Default, true)]
+    #[case(AlignmentFilter::False, false)]
+    #[case(AlignmentFilter::StartsIn(1, 10), true)]
+    #[case(AlignmentFilter::StartsIn(11, 20), false)]
+    #[case(AlignmentFilter::EndsIn(12, 12), true)]
+    #[case(AlignmentFilter::Overlaps(12, 100), true)]
+    #[case(AlignmentFilter::Overlaps(13, 100), false)]
+    #[case(AlignmentFilter::Strand(Strand::Reverse), true)]
+    #[case(AlignmentFilter::Strand(Strand::Forward), false)]
+    #[case(AlignmentFilter::MappingQualityGE(30), true)]
+    #[case(AlignmentFilter::MappingQualityGE(31), false)]
+    #[case(AlignmentFilter::MappingQualityLE(29), false)]
+    #[case(AlignmentFilter::FlagsAll(1024 + 1), true)]
+    #[case(AlignmentFilter::FlagsAll(1024 + 2), false)]
+    #[case(AlignmentFilter::FlagsAny(1024 + 2), true)]
+    #[case(AlignmentFilter::FlagsAny(256), false)]
+    #[case(AlignmentFilter::FlagsEqual(1024 + 16 + 1), true)]
+    #[case(AlignmentFilter::FlagsEqual(1024 + 1), false)]
+    #[case(AlignmentFilter::Tag("HP".to_string(), "1".to_string()), true)]
+    #[case(AlignmentFilter::Tag("HP".to_string(), "2".to_string()), false)]
+    #[case(AlignmentFilter::Tag("RG".to_string(), "1".to_string()), false)]
+    #[case(AlignmentFilter::FlagsAny(1024).not(), false)]
+    #[case(AlignmentFilter::MappingQualityGE(20).and(AlignmentFilter::FlagsAny(1024).not()), false)]
+    #[case(AlignmentFilter::MappingQualityGE(20).or(AlignmentFilter::False), true)]
+    fn test_passes_filter(#[case] filter: AlignmentFilter, #[case] expected: bool) {
+        use noodles::core::Position;
+        use noodles::sam::alignment::record::{Flags, MappingQuality, data::field::Tag};
+        use noodles::sam::alignment::record_buf::data::field::Value;
+
+        let header = sam::Header::default();
+        let record_buf = sam::alignment::RecordBuf::builder()
+            .set_name("read")
+            .set_flags(Flags::SEGMENTED | Flags::REVERSE_COMPLEMENTED | Flags::DUPLICATE)
+            .set_alignment_start(Position::try_from(10).unwrap())
+            .set_mapping_quality(MappingQuality::new(30).unwrap())
+            .set_cigar([Op::new(Kind::Match, 3)].into_iter().collect())
+            .set_sequence(sam::alignment::record_buf::Sequence::from(b"ATT"))
+            .set_data(
+                [(Tag::from([b'H', b'P']), Value::from(1u8))]
+                    .into_iter()
+                    .collect(),
+            )
+            .build();
+        let record = bam_record(&header, &record_buf);
+        let read = AlignedRead::from_bam_record(0, record, &Sequence::default()).unwrap();
+
+        assert_eq!(read.passes_filter(&filter), expected);
+    }
+
+    #[rstest]
+    #[case(AlignmentFilter::MappingQualityGE(20), false)]
+    #[case(AlignmentFilter::MappingQualityGE(0), false)]
+    #[case(AlignmentFilter::MappingQualityLE(60), false)]
+    #[case(AlignmentFilter::MappingQualityGE(20).not(), true)]
+    fn test_unavailable_mapping_quality(#[case] filter: AlignmentFilter, #[case] expected: bool) {
+        use noodles::core::Position;
+
+        let header = sam::Header::default();
+        let record_buf = sam::alignment::RecordBuf::builder()
+            .set_name("read")
+            .set_alignment_start(Position::try_from(10).unwrap())
+            .set_cigar([Op::new(Kind::Match, 3)].into_iter().collect())
+            .set_sequence(sam::alignment::record_buf::Sequence::from(b"ATT"))
+            .build();
+        let record = bam_record(&header, &record_buf);
+        let read = AlignedRead::from_bam_record(0, record, &Sequence::default()).unwrap();
+
+        assert_eq!(read.mapping_quality(), 255);
+        assert_eq!(read.passes_filter(&filter), expected);
+    }
+
+    #[rstest]
+    #[case(false, true, 10, 200, PairOrientation::LR)]
+    #[case(true, false, 200, 10, PairOrientation::LR)]
+    #[case(true, false, 10, 200, PairOrientation::RL)]
+    #[case(false, false, 200, 10, PairOrientation::LL)]
+    #[case(true, true, 10, 200, PairOrientation::RR)]
+    fn test_pair_orientation(
+        #[case] reverse: bool,
+        #[case] mate_reverse: bool,
+        #[case] start: usize,
+        #[case] mate_start: usize,
+        #[case] expected: PairOrientation,
+    ) {
+        use noodles::core::Position;
+        use noodles::sam::alignment::record::Flags;
+        use noodles::sam::header::record::value::{Map, map::ReferenceSequence};
+        use std::num::NonZeroUsize;
+
+        let header = sam::Header::builder()
+            .add_reference_sequence(
+                "chr1",
+                Map::<ReferenceSequence>::new(NonZeroUsize::new(1000).unwrap()),
+            )
+            .build();
+        let mut flags = Flags::SEGMENTED;
+        if reverse {
+            flags |= Flags::REVERSE_COMPLEMENTED;
+        }
+        if mate_reverse {
+            flags |= Flags::MATE_REVERSE_COMPLEMENTED;
+        }
+        let record_buf = sam::alignment::RecordBuf::builder()
+            .set_name("read")
+            .set_flags(flags)
+            .set_reference_sequence_id(0)
+            .set_alignment_start(Position::try_from(start).unwrap())
+            .set_mate_reference_sequence_id(0)
+            .set_mate_alignment_start(Position::try_from(mate_start).unwrap())
+            .set_template_length(mate_start as i32 - start as i32)
+            .set_cigar([Op::new(Kind::Match, 3)].into_iter().collect())
+            .set_sequence(sam::alignment::record_buf::Sequence::from(b"ATT"))
+            .build();
+        let record = bam_record(&header, &record_buf);
+        let read = AlignedRead::from_bam_record(0, record, &Sequence::default()).unwrap();
+
+        assert_eq!(read.pair_orientation(), Some(expected));
+        assert_eq!(read.insert_size(), Some(190));
+
+        let expected_anomaly = match expected {
+            PairOrientation::LR => None,
+            orientation => Some(PairAnomaly::Orientation(orientation)),
+        };
+        assert_eq!(read.pair_anomaly(Some((100, 200))), expected_anomaly);
+        assert_eq!(
+            read.pair_anomaly(Some((100, 150))),
+            expected_anomaly.or(Some(PairAnomaly::LargeInsert))
+        );
+        assert_eq!(
+            read.insert_size_anomaly(Some((300, 500))),
+            Some(PairAnomaly::SmallInsert)
+        );
+        assert_eq!(read.insert_size_anomaly(None), None);
+        assert!(!read.mate_on_other_contig());
+    }
 
-        let src = writer.into_inner();
-        let mut reader = bam::io::Reader::from(&src[..]);
-        let mut record = bam::Record::default();
-        reader.read_record(&mut record)?;
+    #[test]
+    fn test_supplementary_alignments() {
+        use noodles::core::Position;
+        use noodles::sam::alignment::record::{MappingQuality, data::field::Tag};
+        use noodles::sam::alignment::record_buf::data::field::Value;
 
-        Ok(record)
+        let header = sam::Header::default();
+        let record_buf = sam::alignment::RecordBuf::builder()
+            .set_name("read")
+            .set_alignment_start(Position::try_from(10).unwrap())
+            .set_mapping_quality(MappingQuality::new(60).unwrap())
+            .set_cigar(
+                [
+                    Op::new(Kind::HardClip, 5),
+                    Op::new(Kind::SoftClip, 10),
+                    Op::new(Kind::Match, 3),
+                    Op::new(Kind::SoftClip, 20),
+                ]
+                .into_iter()
+                .collect(),
+            )
+            .set_sequence(sam::alignment::record_buf::Sequence::from(vec![b'A'; 33]))
+            .set_data(
+                [(
+                    Tag::from([b'S', b'A']),
+                    Value::from("chr2,1000,-,60M2D40S,60,3;chr5,200,+,30S70M,10,0;invalid;"),
+                )]
+                .into_iter()
+                .collect(),
+            )
+            .build();
+        let record = bam_record(&header, &record_buf);
+        let read = AlignedRead::from_bam_record(0, record, &Sequence::default()).unwrap();
+
+        let supplementary_alignments = read.supplementary_alignments();
+        assert_eq!(
+            supplementary_alignments,
+            vec![
+                SupplementaryAlignment {
+                    contig_name: "chr2".to_string(),
+                    start: 1000,
+                    strand: Strand::Reverse,
+                    cigar: "60M2D40S".to_string(),
+                    mapping_quality: 60,
+                    edit_distance: 3,
+                },
+                SupplementaryAlignment {
+                    contig_name: "chr5".to_string(),
+                    start: 200,
+                    strand: Strand::Forward,
+                    cigar: "30S70M".to_string(),
+                    mapping_quality: 10,
+                    edit_distance: 0,
+                },
+            ]
+        );
+        assert_eq!(supplementary_alignments[0].end(), 1061);
+        assert_eq!(supplementary_alignments[1].end(), 269);
+        assert_eq!(read.clip_lengths(), (15, 20));
+        assert!(
+            read.describe()
+                .unwrap()
+                .ends_with("SA=chr2:1000(-) 60M2D40S MAPQ=60; chr5:200(+) 30S70M MAPQ=10")
+        );
     }
 }
@@ -1,17 +1,20 @@
 use crate::{
-    alignment::{AlignedRead, Alignment},
+    alignment::{AlignedRead, Alignment, DownsampledReads, Downsampling},
     contig_header::ContigHeader,
     error::TGVError,
     intervals::{GenomeInterval, Region},
-    sequence::Sequence,
+    sequence::{Sequence, SequenceRepositoryEnum},
     settings::Settings,
 };
 
 use async_compat::{Compat, CompatExt};
 use itertools::Itertools;
 use noodles::bam::{self, bai};
-use noodles::sam::Header;
+use noodles::cram::{self, crai};
+use noodles::fasta;
+use noodles::sam::{self, Header};
 use opendal::{FuturesAsyncReader, Operator, services};
+use std::collections::HashMap;
 use std::path::Path;
 use tokio::fs::File;
 
@@ -26,10 +29,20 @@ pub struct BamRepository {
     reader: bam::r#async::io::Reader<noodles::bgzf::r#async::io::Reader<File>>,
 
     min_mapq: u8,
+
+    min_base_quality: u8,
+
+    downsampling: Option<Downsampling>,
 }
 
 impl BamRepository {
-    async fn new(bam_path: &str, bai_path: &str, min_mapq: u8) -> Result<Self, TGVError> {
+    async fn new(
+        bam_path: &str,
+        bai_path: &str,
+        min_mapq: u8,
+        min_base_quality: u8,
+        downsampling: Option<Downsampling>,
+    ) -> Result<Self, TGVError> {
         use tokio::fs::File;
 
         let mut reader = File::open(bam_path)
@@ -54,6 +67,8 @@ impl BamRepository {
             header,
             reader,
             min_mapq,
+            min_base_quality,
+            downsampling,
         })
     }
 }
@@ -70,10 +85,20 @@ pub struct RemoteBamRepository {
         bam::r#async::io::Reader<noodles::bgzf::r#async::io::Reader<Compat<FuturesAsyncReader>>>,
 
     min_mapq: u8,
+
+    min_base_quality: u8,
+
+    downsampling: Option<Downsampling>,
 }
 
 impl RemoteBamRepository {
-    pub async fn new(s3_bam_path: &str, s3_bai_path: &str, min_mapq: u8) -> Result<Self, TGVError> {
+    pub async fn new(
+        s3_bam_path: &str,
+        s3_bai_path: &str,
+        min_mapq: u8,
+        min_base_quality: u8,
+        downsampling: Option<Downsampling>,
+    ) -> Result<Self, TGVError> {
         let (bucket, name) = s3_bam_path
             .strip_prefix("s3://")
             .unwrap()
@@ -107,6 +132,8 @@ impl RemoteBamRepository {
             header,
             reader,
             min_mapq,
+            min_base_quality,
+            downsampling,
         })
     }
 
@@ -133,6 +160,75 @@ impl RemoteBamRepository {
     }
 }
 
+pub struct CramRepository {
+    index: crai::Index,
+
+    header: Header,
+
+    reader: cram::r#async::io::Reader<File>,
+
+    min_mapq: u8,
+
+    min_base_quality: u8,
+
+    downsampling: Option<Downsampling>,
+}
+
+impl CramRepository {
+    /// reference: whole-contig sequences to decode reads against.
+    /// Without a reference, only CRAM files with embedded references can be decoded.
+    async fn new(
+        cram_path: &str,
+        crai_path: &str,
+        min_mapq: u8,
+        min_base_quality: u8,
+        downsampling: Option<Downsampling>,
+        reference: Option<fasta::Repository>,
+    ) -> Result<Self, TGVError> {
+        if !Path::new(&cram_path).exists() {
+            return Err(TGVError::IOError(format!(
+                "CRAM file {} not found",
+                cram_path
+            )));
+        }
+
+        let mut reader = cram::r#async::io::reader::Builder::default()
+            .set_reference_sequence_repository(reference.unwrap_or_default())
+            .build_from_path(cram_path)
+            .await?;
+        let header = reader.read_header().await?;
+
+        let index = crai::r#async::read(crai_path).await?;
+
+        Ok(Self {
+            index,
+            header,
+            reader,
+            min_mapq,
+            min_base_quality,
+            downsampling,
+        })
+    }
+}
+
+/// Re-encode a decoded CRAM record as a BAM record, so that CRAM reads go through the same
+/// downstream path (rendering, coverage, base modifications) as BAM reads.
+fn encode_as_bam_record(
+    header: &Header,
+    record: &sam::alignment::RecordBuf,
+    buffer: &mut Vec<u8>,
+) -> Result<bam::Record, TGVError> {
+    use noodles::sam::alignment::io::Write;
+
+    buffer.clear();
+    let mut writer = bam::io::Writer::from(buffer);
+    writer.write_alignment_record(header, record)?;
+
+    let mut bam_record = bam::Record::default();
+    bam::io::Reader::from(&writer.get_ref()[..]).read_record(&mut bam_record)?;
+    Ok(bam_record)
+}
+
 fn get_contig_names_and_lengths_from_header(
     header: &Header,
 ) -> Result<Vec<(String, Option<usize>)>, TGVError> {
@@ -146,18 +242,79 @@ fn get_contig_names_and_lengths_from_header(
 pub enum AlignmentRepositoryEnum {
     Bam(BamRepository),
     RemoteBam(RemoteBamRepository),
+    Cram(CramRepository),
 }
 
 impl AlignmentRepositoryEnum {
-    pub async fn new(bam_path: &str, bai_path: &str, min_mapq: u8) -> Result<Self, TGVError> {
-        if is_url(bam_path) {
-            Ok(AlignmentRepositoryEnum::RemoteBam(
-                RemoteBamRepository::new(bam_path, bai_path, min_mapq).await?,
-            ))
-        } else {
-            Ok(AlignmentRepositoryEnum::Bam(
-                BamRepository::new(bam_path, bai_path, min_mapq).await?,
-            ))
+    /// sequence_repository: reference used to decode CRAM files. Not used for BAM files.
+    pub async fn new(
+        bam_path: &str,
+        bai_path: &str,
+        min_mapq: u8,
+        min_base_quality: u8,
+        downsampling: Option<Downsampling>,
+        sequence_repository: Option<&SequenceRepositoryEnum>,
+    ) -> Result<Self, TGVError> {
+        match (is_url(bam_path), is_cram(bam_path)) {
+            (true, true) => Err(TGVError::IOError(format!(
+                "Remote CRAM files are not supported: {}",
+                bam_path
+            ))),
+            (true, false) => Ok(AlignmentRepositoryEnum::RemoteBam(
+                RemoteBamRepository::new(
+                    bam_path,
+                    bai_path,
+                    min_mapq,
+                    min_base_quality,
+                    downsampling,
+                )
+                .await?,
+            )),
+            (false, true) => {
+                let reference = match sequence_repository {
+                    Some(sequence_repository) => sequence_repository.fasta_repository()?,
+                    None => None,
+                };
+                Ok(AlignmentRepositoryEnum::Cram(
+                    CramRepository::new(
+                        bam_path,
+                        bai_path,
+                        min_mapq,
+                        min_base_quality,
+                        downsampling,
+                        reference,
+                    )
+                    .await?,
+                ))
+            }
+            (false, false) => Ok(AlignmentRepositoryEnum::Bam(
+                BamRepository::new(bam_path, bai_path, min_mapq, min_base_quality, downsampling)
+                    .await?,
+            )),
+        }
+    }
+
+    fn header(&self) -> &Header {
+        match self {
+            AlignmentRepositoryEnum::Bam(inner) => &inner.header,
+            AlignmentRepositoryEnum::RemoteBam(inner) => &inner.header,
+            AlignmentRepositoryEnum::Cram(inner) => &inner.header,
+        }
+    }
+
+    fn min_base_quality(&self) -> u8 {
+        match self {
+            AlignmentRepositoryEnum::Bam(inner) => inner.min_base_quality,
+            AlignmentRepositoryEnum::RemoteBam(inner) => inner.min_base_quality,
+            AlignmentRepositoryEnum::Cram(inner) => inner.min_base_quality,
+        }
+    }
+
+    fn downsampling(&self) -> Option<Downsampling> {
+        match self {
+            AlignmentRepositoryEnum::Bam(inner) => inner.downsampling,
+            AlignmentRepositoryEnum::RemoteBam(inner) => inner.downsampling,
+            AlignmentRepositoryEnum::Cram(inner) => inner.downsampling,
         }
     }
 }
@@ -174,7 +331,6 @@ impl AlignmentRepositoryEnum {
         let records = match region.alignment(contig_header)? {
             Some(region) => {
                 let mut records = Vec::new();
-                let mut index = 0;
                 match self {
                     AlignmentRepositoryEnum::Bam(inner) => {
                         let min_mapq = inner.min_mapq;
@@ -186,12 +342,7 @@ impl AlignmentRepositoryEnum {
                                     continue;
                                 }
                             }
-                            records.push(AlignedRead::from_bam_record(
-                                index,
-                                record,
-                                reference_sequence,
-                            )?);
-                            index += 1;
+                            records.push(record);
                         }
                     }
                     AlignmentRepositoryEnum::RemoteBam(inner) => {
@@ -204,12 +355,25 @@ impl AlignmentRepositoryEnum {
                                     continue;
                                 }
                             }
-                            records.push(AlignedRead::from_bam_record(
-                                index,
-                                record,
-                                reference_sequence,
+                            records.push(record);
+                        }
+                    }
+                    AlignmentRepositoryEnum::Cram(inner) => {
+                        let min_mapq = inner.min_mapq;
+                        let mut query = inner.reader.query(&inner.header, &inner.index, &region)?;
+                        let mut buffer = Vec::new();
+
+                        while let Some(record) = query.try_next().await? {
+                            if let Some(mq) = record.mapping_quality()
+                                && u8::from(mq) < min_mapq
+                            {
+                                continue;
+                            }
+                            records.push(encode_as_bam_record(
+                                &inner.header,
+                                &record,
+                                &mut buffer,
                             )?);
-                            index += 1;
                         }
                     }
                 };
@@ -219,28 +383,250 @@ impl AlignmentRepositoryEnum {
             None => Vec::new(),
         };
 
-        Alignment::from_aligned_reads(
-            records,
+        // Coverage still counts the reads dropped by downsampling.
+        let keep = match self.downsampling() {
+            Some(downsampling) => downsampling.sample(records.iter().map(|record| {
+                (
+                    record
+                        .alignment_start()
+                        .and_then(|start| start.ok())
+                        .map_or(0, |start| start.get() as u64),
+                    record.name().map_or(&b""[..], |name| name.as_ref()),
+                )
+            })),
+            None => vec![true; records.len()],
+        };
+        let min_base_quality = self.min_base_quality();
+        let mut reads = Vec::new();
+        let mut downsampled = DownsampledReads::default();
+        for (record, keep) in records.into_iter().zip(keep) {
+            if keep {
+                reads.push(AlignedRead::from_bam_record(
+                    reads.len(),
+                    record,
+                    reference_sequence,
+                )?);
+            } else {
+                downsampled.add(&record, min_base_quality, reference_sequence)?;
+            }
+        }
+
+        let mut alignment = Alignment::from_aligned_reads(
+            reads,
             region.contig_index(),
             (region.start(), region.end()),
+            downsampled,
+            min_base_quality,
             reference_sequence,
-        )
+        )?;
+        alignment.read_group_samples = self.read_group_samples();
+        alignment.reference_sequence_names = self
+            .header()
+            .reference_sequences()
+            .keys()
+            .map(|name| name.to_string())
+            .collect();
+
+        Ok(alignment)
+    }
+
+    /// Read group ID -> sample name (SM) in the header.
+    pub fn read_group_samples(&self) -> HashMap<String, String> {
+        use noodles::sam::header::record::value::map::read_group::tag::SAMPLE;
+
+        self.header()
+            .read_groups()
+            .iter()
+            .filter_map(|(id, read_group)| {
+                read_group
+                    .other_fields()
+                    .get(&SAMPLE)
+                    .map(|sample| (id.to_string(), sample.to_string()))
+            })
+            .collect()
     }
 
     /// Read BAM headers and return contig namesa and lengths.
     /// Note that this function does not interprete the contig name as contg vs chromosome.
     pub fn read_header(&self) -> Result<Vec<(String, Option<usize>)>, TGVError> {
-        let header = match self {
-            AlignmentRepositoryEnum::Bam(inner) => &inner.header,
-            AlignmentRepositoryEnum::RemoteBam(inner) => &inner.header,
-        };
-        get_contig_names_and_lengths_from_header(header)
+        get_contig_names_and_lengths_from_header(self.header())
     }
 }
 
+pub fn is_cram(path: &str) -> bool {
+    path.to_lowercase().ends_with(".cram")
+}
+
 pub fn is_url(path: &str) -> bool {
     path.starts_with("s3://")
         || path.starts_with("http://")
         || path.starts_with("https://")
         || path.starts_with("gs://")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contig_header::ContigSource;
+    use crate::intervals::Focus;
+    use crate::reference::Reference;
+    use crate::sequence::IndexedFastaSequenceRepository;
+    use crate::test_utils::test_data;
+
+    /// covid.sorted.bam without a MAPQ filter.
+    async fn covid_bam(
+        min_base_quality: u8,
+        downsampling: Option<Downsampling>,
+    ) -> AlignmentRepositoryEnum {
+        AlignmentRepositoryEnum::new(
+            &test_data("covid.sorted.bam"),
+            &test_data("covid.sorted.bam.bai"),
+            0,
+            min_base_quality,
+            downsampling,
+            None,
+        )
+        .await
+        .unwrap()
+    }
+
+    /// Contigs of an alignment file header.
+    fn contig_header(repository: &AlignmentRepositoryEnum) -> ContigHeader {
+        let mut contig_header = ContigHeader::new(Reference::NoReference);
+        for (name, length) in repository.read_header().unwrap() {
+            contig_header.update_or_add_contig(
+                name,
+                length.map(|l| l as u64),
+                Vec::new(),
+                ContigSource::Alignment,
+            );
+        }
+        contig_header
+    }
+
+    /// 50-350 on the covid genome.
+    fn region() -> Region {
+        Region {
+            focus: Focus {
+                contig_index: 0,
+                position: 200,
+            },
+            half_width: 150,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cram_matches_bam() {
+        let sequence_repository = SequenceRepositoryEnum::IndexedFasta(
+            IndexedFastaSequenceRepository::new(test_data("covid.fa")).unwrap(),
+        );
+
+        let mut bam = covid_bam(0, None).await;
+        let mut cram = AlignmentRepositoryEnum::new(
+            &test_data("covid.sorted.cram"),
+            &test_data("covid.sorted.cram.crai"),
+            0,
+            0,
+            None,
+            Some(&sequence_repository),
+        )
+        .await
+        .unwrap();
+        assert_eq!(cram.read_header().unwrap(), bam.read_header().unwrap());
+
+        let contig_header = contig_header(&bam);
+        let region = region();
+
+        let bam_alignment = bam
+            .read_alignment(&region, &Sequence::default(), &contig_header)
+            .await
+            .unwrap();
+        let cram_alignment = cram
+            .read_alignment(&region, &Sequence::default(), &contig_header)
+            .await
+            .unwrap();
+
+        assert!(!bam_alignment.reads.is_empty());
+        assert_eq!(bam_alignment.reads.len(), cram_alignment.reads.len());
+        assert_eq!(bam_alignment.ys, cram_alignment.ys);
+        for (bam_read, cram_read) in bam_alignment.reads.iter().zip(cram_alignment.reads.iter()) {
+            assert_eq!(bam_read.read.name(), cram_read.read.name());
+            assert_eq!(bam_read.start, cram_read.start);
+            assert_eq!(bam_read.cigar, cram_read.cigar);
+            assert_eq!(
+                bam_read.read.sequence().iter().collect::<Vec<_>>(),
+                cram_read.read.sequence().iter().collect::<Vec<_>>()
+            );
+        }
+        for position in region.start()..=region.end() {
+            assert_eq!(
+                bam_alignment.coverage_at(position).total,
+                cram_alignment.coverage_at(position).total
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_downsampling_keeps_coverage() {
+        let region = region();
+
+        let mut alignments = Vec::new();
+        for downsampling in [
+            None,
+            Some(Downsampling {
+                max_reads_per_window: 2,
+                window: 50,
+            }),
+        ] {
+            let mut bam = covid_bam(0, downsampling).await;
+            let contig_header = contig_header(&bam);
+            alignments.push(
+                bam.read_alignment(&region, &Sequence::default(), &contig_header)
+                    .await
+                    .unwrap(),
+            );
+        }
+
+        let (all, downsampled) = (&alignments[0], &alignments[1]);
+        assert_eq!(all.downsampled_reads(), 0);
+        assert!(downsampled.downsampled_reads() > 0);
+        assert_eq!(
+            downsampled.reads.len() + downsampled.downsampled_reads(),
+            all.reads.len()
+        );
+        for position in region.start()..=region.end() {
+            assert_eq!(
+                downsampled.coverage_at(position).total,
+                all.coverage_at(position).total
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_min_base_quality() {
+        let region = region();
+
+        let mut alignments = Vec::new();
+        for min_base_quality in [0, 35] {
+            let mut bam = covid_bam(min_base_quality, None).await;
+            let contig_header = contig_header(&bam);
+            alignments.push(
+                bam.read_alignment(&region, &Sequence::default(), &contig_header)
+                    .await
+                    .unwrap(),
+            );
+        }
+
+        let (all, filtered) = (&alignments[0], &alignments[1]);
+        assert_eq!(all.reads.len(), filtered.reads.len());
+        let totals = |alignment: &Alignment| -> usize {
+            (region.start()..=region.end())
+                .map(|position| alignment.coverage_at(position).total)
+                .sum()
+        };
+        assert!(totals(filtered) < totals(all));
+        for position in region.start()..=region.end() {
+            assert!(filtered.coverage_at(position).total <= all.coverage_at(position).total);
+        }
+    }
+}
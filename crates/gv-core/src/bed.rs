@@ -1,52 +1,192 @@
 use crate::{
     contig_header::ContigHeader,
     error::TGVError,
-    intervals::{GenomeInterval, SortedIntervalCollection},
+    feature::Gene,
+    intervals::{GenomeInterval, Region, SortedIntervalCollection},
+    strand::Strand,
+};
+use bigtools::{
+    BigBedRead,
+    utils::{remote_file::RemoteFile, reopen::SeekableRead},
+};
+use noodles::{
+    bgzf,
+    core::Position,
+    csi::{self, BinningIndex},
+    tabix,
+};
+use std::{
+    fs::File,
+    io::{BufRead, BufReader},
+    path::Path,
 };
-use noodles::bed::{self};
 
+/// A BED record with up to 12 columns.
+/// See: https://genome.ucsc.edu/FAQ/FAQformat.html#format1
 #[derive(Debug, Clone)]
 pub struct BEDInterval {
     contig_index: usize,
 
     pub index: usize,
 
+    contig_name: String,
+
+    /// 1-based, inclusive.
     start: u64,
+    /// 1-based, inclusive.
     end: u64,
 
-    record: bed::Record<3>,
+    pub name: Option<String>,
+    pub score: Option<String>,
+    pub strand: Option<Strand>,
+
+    /// Coding region. 1-based, inclusive.
+    pub thick_start: Option<u64>,
+    pub thick_end: Option<u64>,
+
+    /// BED12 blocks (exons). 1-based, inclusive.
+    pub blocks: Vec<(u64, u64)>,
 }
 
 impl BEDInterval {
-    pub fn new(
-        record: bed::Record<3>,
+    /// Parse a tab-separated BED line. Returns None for comment, track and browser lines.
+    pub fn from_line(
+        line: &str,
         index: usize,
         contig_header: &ContigHeader,
-    ) -> Result<Self, TGVError> {
-        let start = record.feature_start()?.get() as u64; // Noodles already converted to 1-based, inclusive
-        Ok(Self {
-            contig_index: contig_header
-                .try_get_index_by_str(&record.reference_sequence_name().to_string())?,
+    ) -> Result<Option<Self>, TGVError> {
+        if line.trim().is_empty()
+            || line.starts_with('#')
+            || line.starts_with("track")
+            || line.starts_with("browser")
+        {
+            return Ok(None);
+        }
+
+        let fields: Vec<&str> = line.trim_end().split('\t').collect();
+        if fields.len() < 3 {
+            return Err(TGVError::ParsingError(format!(
+                "BED line has fewer than 3 columns: {}",
+                line
+            )));
+        }
+
+        // BED is 0-based, half-open. Convert to 1-based, inclusive.
+        let start = fields[1].parse::<u64>()? + 1;
+        let end = fields[2].parse::<u64>()?;
+
+        let optional_field = |i: usize| {
+            fields
+                .get(i)
+                .filter(|field| !field.is_empty() && **field != ".")
+        };
+
+        let strand = match optional_field(5) {
+            Some(strand) => Some(Strand::from_str(strand.to_string())?),
+            None => None,
+        };
+
+        let thick_start = optional_field(6)
+            .map(|field| field.parse::<u64>().map(|v| v + 1))
+            .transpose()?;
+        let thick_end = optional_field(7)
+            .map(|field| field.parse::<u64>())
+            .transpose()?;
+
+        let blocks = match (optional_field(9), optional_field(10), optional_field(11)) {
+            (Some(block_count), Some(block_sizes), Some(block_starts)) => {
+                let block_count = block_count.parse::<usize>()?;
+                let block_sizes = parse_comma_separated_list(block_sizes)?;
+                let block_starts = parse_comma_separated_list(block_starts)?;
+                if block_sizes.len() != block_count || block_starts.len() != block_count {
+                    return Err(TGVError::ParsingError(format!(
+                        "BED blockCount does not match blockSizes / blockStarts: {}",
+                        line
+                    )));
+                }
+                block_starts
+                    .iter()
+                    .zip(block_sizes.iter())
+                    .map(|(block_start, block_size)| {
+                        (start + block_start, start + block_start + block_size - 1)
+                    })
+                    .collect()
+            }
+            _ => Vec::new(),
+        };
+
+        Ok(Some(Self {
+            contig_index: contig_header.try_get_index_by_str(fields[0])?,
             index,
-            start, // BED start is 0-based, inclusive
-            end: match record.feature_end() {
-                Some(end) => end?.get() as u64,
-                None => start, // BED end is 0-based, exclusive
-            },
-            record,
+            contig_name: fields[0].to_string(),
+            start,
+            end,
+            name: optional_field(3).map(|field| field.to_string()),
+            score: optional_field(4).map(|field| field.to_string()),
+            strand,
+            thick_start,
+            thick_end,
+            blocks,
+        }))
+    }
+
+    /// Gene model for stranded records (BED6+). Thick regions are rendered as CDS, blocks as exons.
+    pub fn gene(&self) -> Option<Gene> {
+        let strand = self.strand.clone()?;
+        let name = self.name.clone().unwrap_or_default();
+
+        let (exon_starts, exon_ends): (Vec<u64>, Vec<u64>) = if !self.blocks.is_empty() {
+            self.blocks.iter().cloned().unzip()
+        } else if self.thick_start.is_some() {
+            (vec![self.start], vec![self.end])
+        } else {
+            (vec![], vec![])
+        };
+
+        Some(Gene {
+            id: name.clone(),
+            name,
+            strand,
+            contig_index: self.contig_index,
+            transcription_start: self.start,
+            transcription_end: self.end,
+            cds_start: self.thick_start.unwrap_or(self.start),
+            cds_end: self.thick_end.unwrap_or(self.end),
+            cds_phase: 0,
+            has_exons: !exon_starts.is_empty(),
+            exon_starts,
+            exon_ends,
         })
     }
 
     pub fn describe(&self) -> String {
-        format!(
+        let mut description = format!(
             "BED interval: {}:{}-{}",
-            self.record.reference_sequence_name(),
-            self.start,
-            self.end
-        )
+            self.contig_name, self.start, self.end
+        );
+        if let Some(name) = &self.name {
+            description += &format!(" {}", name);
+        }
+        if let Some(strand) = &self.strand {
+            description += &format!(" ({})", strand);
+        }
+        if let Some(score) = &self.score {
+            description += &format!(" score={}", score);
+        }
+        if !self.blocks.is_empty() {
+            description += &format!(" blocks={}", self.blocks.len());
+        }
+        description
     }
 }
 
+fn parse_comma_separated_list(s: &str) -> Result<Vec<u64>, TGVError> {
+    s.trim_end_matches(',')
+        .split(',')
+        .map(|v| Ok(v.parse::<u64>()?))
+        .collect()
+}
+
 impl GenomeInterval for BEDInterval {
     fn contig_index(&self) -> usize {
         self.contig_index
@@ -60,28 +200,535 @@ impl GenomeInterval for BEDInterval {
         self.end
     }
 }
-#[derive(Debug, Clone)]
+
+/// Windows for finding features next to a position in indexed files. Doubled until enough features are found.
+const INITIAL_SEARCH_WINDOW: u64 = 1_000_000;
+
+/// Largest coordinate a tabix index can address.
+const MAX_INDEXED_POSITION: u64 = (1 << 29) - 1;
+
+/// A bgzipped file of BED-like lines with a .tbi index. Also used for bedGraph and bedMethyl files.
+pub(crate) struct TabixFile {
+    reader: csi::io::IndexedReader<bgzf::io::Reader<File>, tabix::Index>,
+
+    /// Contig names that can be queried.
+    pub(crate) contig_names: Vec<String>,
+}
+
+impl TabixFile {
+    /// None: the file has no .tbi index.
+    pub(crate) fn open(path: &str) -> Result<Option<Self>, TGVError> {
+        if !Path::new(&format!("{}.tbi", path)).exists() {
+            return Ok(None);
+        }
+
+        let reader = tabix::io::indexed_reader::Builder::default().build_from_path(path)?;
+        let contig_names = reader
+            .index()
+            .header()
+            .map(|header| {
+                header
+                    .reference_sequence_names()
+                    .iter()
+                    .map(|name| name.to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+        Ok(Some(Self {
+            reader,
+            contig_names,
+        }))
+    }
+
+    /// Name of a contig in the file.
+    pub(crate) fn contig_name(
+        &self,
+        contig_index: usize,
+        contig_header: &ContigHeader,
+    ) -> Option<&str> {
+        contig_name(&self.contig_names, contig_index, contig_header)
+    }
+
+    /// Lines overlapping [start, end] (1-based, inclusive) on a contig.
+    pub(crate) fn query(
+        &mut self,
+        contig_name: &str,
+        start: u64,
+        end: u64,
+    ) -> Result<Vec<String>, TGVError> {
+        let to_position = |position: u64| {
+            Position::try_from(position.clamp(1, MAX_INDEXED_POSITION) as usize)
+                .map_err(|_| TGVError::ValueError(format!("Invalid position {}", position)))
+        };
+        let region =
+            noodles::core::Region::new(contig_name, to_position(start)?..=to_position(end)?);
+        self.reader
+            .query(&region)?
+            .map(|record| Ok(record?.as_ref().to_string()))
+            .collect()
+    }
+}
+
+/// Name of the contig with an index in the contig header, among the contig names of a file.
+fn contig_name<'a>(
+    contig_names: &'a [String],
+    contig_index: usize,
+    contig_header: &ContigHeader,
+) -> Option<&'a str> {
+    contig_names
+        .iter()
+        .find(|name| {
+            contig_header
+                .try_get_index_by_str(name)
+                .is_ok_and(|index| index == contig_index)
+        })
+        .map(|name| name.as_str())
+}
+
+/// Readers for files that can be queried by region.
+enum BEDReader {
+    /// bgzipped BED file with a .tbi index.
+    Tabix(TabixFile),
+
+    /// bigBed file. Remote files are read with HTTP range requests.
+    BigBed(BigBedRead<Box<dyn SeekableRead + Send>>),
+}
+
 pub struct BEDRepository {
     pub bed_path: String,
+
+    /// None: the whole file is read at once.
+    reader: Option<BEDReader>,
+
+    /// Contig names that can be queried in the indexed file.
+    contig_names: Vec<String>,
 }
 
 impl BEDRepository {
+    pub fn new(bed_path: &str) -> Result<Self, TGVError> {
+        let is_remote = bed_path.starts_with("http://") || bed_path.starts_with("https://");
+        if !is_remote && !Path::new(bed_path).exists() {
+            return Err(TGVError::IOError(format!(
+                "BED file {} not found",
+                bed_path
+            )));
+        }
+
+        let (reader, contig_names) = if is_bigbed(bed_path) {
+            let read: Box<dyn SeekableRead + Send> = if is_remote {
+                Box::new(RemoteFile::new(bed_path))
+            } else {
+                Box::new(File::open(bed_path)?)
+            };
+            let reader = BigBedRead::open(read)?;
+            let contig_names = reader
+                .chroms()
+                .iter()
+                .map(|chrom| chrom.name.clone())
+                .collect();
+            (Some(BEDReader::BigBed(reader)), contig_names)
+        } else if is_remote {
+            return Err(TGVError::IOError(format!(
+                "Remote BED file {} must be a bigBed (.bb) file",
+                bed_path
+            )));
+        } else if let Some(tabix_file) = TabixFile::open(bed_path)? {
+            let contig_names = tabix_file.contig_names.clone();
+            (Some(BEDReader::Tabix(tabix_file)), contig_names)
+        } else {
+            (None, Vec::new())
+        };
+
+        Ok(Self {
+            bed_path: bed_path.to_string(),
+            reader,
+            contig_names,
+        })
+    }
+
+    /// Whether intervals can be queried by region.
+    /// Unindexed files are always read in full.
+    pub fn is_indexed(&self) -> bool {
+        self.reader.is_some()
+    }
+
+    /// Read intervals overlapping region. Unindexed files return all intervals.
     pub fn read_bed(
+        &mut self,
+        region: &Region,
+        contig_header: &ContigHeader,
+    ) -> Result<SortedIntervalCollection<BEDInterval>, TGVError> {
+        if !self.is_indexed() {
+            return self.read_all_intervals(contig_header);
+        }
+
+        SortedIntervalCollection::new(self.read_intervals(
+            region.contig_index(),
+            region.start(),
+            region.end(),
+            contig_header,
+        )?)
+    }
+
+    /// Start of the nth distinct feature start after (or before) a position on the same contig.
+    /// Indexed files only: unindexed files are loaded in full, so search the loaded intervals.
+    pub fn feature_start_around(
+        &mut self,
+        contig_index: usize,
+        position: u64,
+        n: usize,
+        after: bool,
+        contig_header: &ContigHeader,
+    ) -> Result<u64, TGVError> {
+        if n == 0 {
+            return Ok(position);
+        }
+        if !self.is_indexed() {
+            return Err(TGVError::StateError(format!(
+                "{} is not indexed",
+                self.bed_path
+            )));
+        }
+        let limit = contig_header
+            .try_get(contig_index)?
+            .length
+            .unwrap_or(MAX_INDEXED_POSITION);
+
+        let mut window = INITIAL_SEARCH_WINDOW;
+        loop {
+            let (start, end) = if after {
+                (position, u64::min(position.saturating_add(window), limit))
+            } else {
+                (position.saturating_sub(window).max(1), position)
+            };
+
+            let intervals = self.read_intervals(contig_index, start, end, contig_header)?;
+            let mut starts: Vec<u64> = intervals
+                .iter()
+                .filter(|interval| interval.contig_index() == contig_index)
+                .map(|interval| interval.start())
+                .filter(|start| {
+                    if after {
+                        *start > position
+                    } else {
+                        *start < position
+                    }
+                })
+                .collect();
+            starts.sort();
+            starts.dedup();
+            if !after {
+                starts.reverse();
+            }
+
+            if let Some(start) = starts.get(n - 1) {
+                return Ok(*start);
+            }
+
+            let searched_to_contig_end = (after && end >= limit) || (!after && start <= 1);
+            if searched_to_contig_end {
+                return starts
+                    .last()
+                    .copied()
+                    .ok_or(TGVError::StateError("No BED features found".to_string()));
+            }
+            window *= 2;
+        }
+    }
+
+    /// Query intervals overlapping [start, end] (1-based, inclusive) in an indexed file.
+    fn read_intervals(
+        &mut self,
+        contig_index: usize,
+        start: u64,
+        end: u64,
+        contig_header: &ContigHeader,
+    ) -> Result<Vec<BEDInterval>, TGVError> {
+        let Some(name) = contig_name(&self.contig_names, contig_index, contig_header) else {
+            return Ok(Vec::new());
+        };
+
+        let mut intervals = Vec::new();
+        match self.reader.as_mut() {
+            Some(BEDReader::Tabix(tabix_file)) => {
+                for line in tabix_file.query(name, start, end)? {
+                    if let Some(interval) =
+                        BEDInterval::from_line(&line, intervals.len(), contig_header)?
+                    {
+                        intervals.push(interval);
+                    }
+                }
+            }
+            Some(BEDReader::BigBed(reader)) => {
+                let length = reader
+                    .chroms()
+                    .iter()
+                    .find(|chrom| chrom.name == *name)
+                    .map(|chrom| chrom.length)
+                    .unwrap_or(u32::MAX);
+                // Columns beyond the BED standard (bed9+N) are not BED columns.
+                let n_rest_fields =
+                    (reader.info().header.defined_field_count as usize).saturating_sub(3);
+
+                let start = u64::min(start.saturating_sub(1), length as u64) as u32;
+                let end = u64::min(end, length as u64) as u32;
+                for entry in reader.get_interval(name, start, end)? {
+                    let entry = entry?;
+                    let mut line = format!("{}\t{}\t{}", name, entry.start, entry.end);
+                    for field in entry.rest.split('\t').take(n_rest_fields) {
+                        line += "\t";
+                        line += field;
+                    }
+                    if let Some(interval) =
+                        BEDInterval::from_line(&line, intervals.len(), contig_header)?
+                    {
+                        intervals.push(interval);
+                    }
+                }
+            }
+            None => {}
+        }
+
+        Ok(intervals)
+    }
+
+    fn read_all_intervals(
         &self,
         contig_header: &ContigHeader,
     ) -> Result<SortedIntervalCollection<BEDInterval>, TGVError> {
-        let mut reader = bed::io::reader::Builder::<3>.build_from_path(self.bed_path.as_str())?;
-        let mut record = bed::Record::default();
+        let reader: Box<dyn BufRead> = if self.bed_path.ends_with(".gz") {
+            Box::new(bgzf::io::Reader::new(File::open(&self.bed_path)?))
+        } else {
+            Box::new(BufReader::new(File::open(&self.bed_path)?))
+        };
 
-        let mut records = Vec::new();
+        let mut intervals = Vec::new();
+        for line in reader.lines() {
+            if let Some(interval) = BEDInterval::from_line(&line?, intervals.len(), contig_header)?
+            {
+                intervals.push(interval);
+            }
+        }
 
-        let mut index = 0;
+        SortedIntervalCollection::new(intervals)
+    }
+}
+
+/// Whether a path or URL is a bigBed file.
+fn is_bigbed(path: &str) -> bool {
+    let path = path.to_lowercase();
+    path.ends_with(".bb") || path.ends_with(".bigbed")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contig_header::ContigSource;
+    use crate::intervals::Focus;
+    use crate::reference::Reference;
+    use crate::test_utils::{test_data, write_bigbed, write_tabix_copy};
+    use rstest::rstest;
+    use std::io::Write;
 
-        while reader.read_record(&mut record)? != 0 {
-            records.push(BEDInterval::new(record.clone(), index, contig_header)?);
-            index += 1;
+    const BED12_AUTOSQL: &str = r#"table bed12
+"Browser extensible data (12 fields)"
+    (
+    string chrom;       "Reference sequence chromosome or scaffold"
+    uint   chromStart;  "Start position in chromosome"
+    uint   chromEnd;    "End position in chromosome"
+    string name;        "Name of item"
+    uint   score;       "Score from 0-1000"
+    char[1] strand;     "+ or -"
+    uint thickStart;    "Start of where display should be thick (start codon)"
+    uint thickEnd;      "End of where display should be thick (stop codon)"
+    uint reserved;      "Used as itemRgb as of 2004-11-22"
+    int blockCount;     "Number of blocks"
+    int[blockCount] blockSizes; "Comma separated list of block sizes"
+    int[blockCount] chromStarts; "Start positions relative to chromStart"
+    )
+"#;
+
+    fn contig_header() -> ContigHeader {
+        let mut contig_header = ContigHeader::new(Reference::NoReference);
+        for (name, length) in [("chr17", 83257441), ("chr20", 64444167)] {
+            contig_header.update_or_add_contig(
+                name.to_string(),
+                Some(length),
+                Vec::new(),
+                ContigSource::Track,
+            );
         }
+        contig_header
+    }
+
+    fn write_indexed_copy(dir: &Path) -> String {
+        write_tabix_copy(dir, "transcripts.bed", "transcripts.bed.gz")
+    }
+
+    fn write_bigbed_copy(dir: &Path) -> String {
+        write_bigbed(
+            dir,
+            "transcripts.bed",
+            &[("chr17", 83257441), ("chr20", 64444167)],
+            BED12_AUTOSQL,
+        )
+    }
+
+    #[test]
+    fn test_bed12_record() {
+        let line = "chr17\t100\t1000\tTX1\t500\t-\t200\t900\t0\t3\t100,200,300,\t0,400,600,";
+        let interval = BEDInterval::from_line(line, 0, &contig_header())
+            .unwrap()
+            .unwrap();
+
+        assert_eq!((interval.start(), interval.end()), (101, 1000));
+        assert_eq!(interval.name, Some("TX1".to_string()));
+        assert_eq!(interval.score, Some("500".to_string()));
+        assert_eq!(interval.strand, Some(Strand::Reverse));
+        assert_eq!(
+            (interval.thick_start, interval.thick_end),
+            (Some(201), Some(900))
+        );
+        assert_eq!(interval.blocks, vec![(101, 200), (501, 700), (701, 1000)]);
+
+        let gene = interval.gene().unwrap();
+        assert!(gene.has_exons);
+        assert_eq!(gene.exon_starts, vec![101, 501, 701]);
+        assert_eq!(gene.exon_ends, vec![200, 700, 1000]);
+        assert_eq!((gene.cds_start, gene.cds_end), (201, 900));
+    }
 
-        SortedIntervalCollection::new(records)
+    #[rstest]
+    #[case("chr17\t100\t200", None, false)]
+    #[case("chr17\t100\t200\tpeak\t0\t+", Some(Strand::Forward), false)]
+    #[case("chr17\t100\t200\tcds\t0\t+\t120\t180", Some(Strand::Forward), true)]
+    #[case("chr17\t100\t200\tpeak\t0\t.", None, false)]
+    fn test_gene_model(
+        #[case] line: &str,
+        #[case] strand: Option<Strand>,
+        #[case] has_exons: bool,
+    ) {
+        let interval = BEDInterval::from_line(line, 0, &contig_header())
+            .unwrap()
+            .unwrap();
+        assert_eq!(interval.strand, strand);
+        assert_eq!(
+            interval.gene().map(|gene| gene.has_exons),
+            strand.map(|_| has_exons)
+        );
+    }
+
+    #[rstest]
+    #[case("#comment")]
+    #[case("track name=test")]
+    #[case("browser position chr17:1-100")]
+    #[case("")]
+    fn test_skipped_lines(#[case] line: &str) {
+        assert!(
+            BEDInterval::from_line(line, 0, &contig_header())
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_invalid_block_count() {
+        let line = "chr17\t100\t1000\tTX1\t0\t+\t100\t1000\t0\t2\t100,\t0,";
+        assert!(BEDInterval::from_line(line, 0, &contig_header()).is_err());
+    }
+
+    #[rstest]
+    #[case(0, 7668500, 100, vec![7668001, 7668451])]
+    #[case(0, 7700000, 1000, vec![7700001])]
+    #[case(1, 88100, 100, vec![88001, 88101])]
+    #[case(0, 1000, 100, vec![])]
+    fn test_indexed_query_matches_full_load(
+        #[case] contig_index: usize,
+        #[case] position: u64,
+        #[case] half_width: u64,
+        #[case] expected_starts: Vec<u64>,
+    ) {
+        let dir = tempfile::tempdir().unwrap();
+        let indexed_path = write_indexed_copy(dir.path());
+        let bigbed_path = write_bigbed_copy(dir.path());
+        let contig_header = contig_header();
+        let region = Region {
+            focus: Focus {
+                contig_index,
+                position,
+            },
+            half_width,
+        };
+
+        let mut unindexed = BEDRepository::new(&test_data("transcripts.bed")).unwrap();
+        assert!(!unindexed.is_indexed());
+        let all_intervals = unindexed.read_bed(&region, &contig_header).unwrap();
+        assert_eq!(all_intervals.intervals.len(), 6);
+        let full_load_starts = all_intervals
+            .overlapping(contig_index, region.start(), region.end())
+            .unwrap()
+            .iter()
+            .map(|interval| interval.start())
+            .collect::<Vec<u64>>();
+        assert_eq!(full_load_starts, expected_starts);
+
+        let mut indexed = BEDRepository::new(&indexed_path).unwrap();
+        assert!(indexed.is_indexed());
+        let starts = indexed
+            .read_bed(&region, &contig_header)
+            .unwrap()
+            .intervals
+            .iter()
+            .map(|interval| interval.start())
+            .collect::<Vec<u64>>();
+        assert_eq!(starts, expected_starts);
+
+        let mut bigbed = BEDRepository::new(&bigbed_path).unwrap();
+        assert!(bigbed.is_indexed());
+        let intervals = bigbed.read_bed(&region, &contig_header).unwrap().intervals;
+        assert_eq!(
+            intervals
+                .iter()
+                .map(|interval| interval.start())
+                .collect::<Vec<u64>>(),
+            expected_starts
+        );
+        for (interval, expected) in intervals.iter().zip(
+            all_intervals
+                .overlapping(contig_index, region.start(), region.end())
+                .unwrap(),
+        ) {
+            assert_eq!(interval.describe(), expected.describe());
+            assert_eq!(interval.blocks, expected.blocks);
+        }
+    }
+
+    #[rstest]
+    #[case(0, 1, 1, true, Ok(7668001))]
+    #[case(0, 7668001, 2, true, Ok(7700001))]
+    #[case(0, 7668001, 5, true, Ok(7700001))]
+    #[case(0, 7700001, 1, false, Ok(7668451))]
+    #[case(1, 88050, 0, true, Ok(88050))]
+    #[case(1, 88050, 1, false, Ok(88001))]
+    #[case(1, 100000, 1, true, Err(()))]
+    fn test_feature_start_around(
+        #[case] contig_index: usize,
+        #[case] position: u64,
+        #[case] n: usize,
+        #[case] after: bool,
+        #[case] expected: Result<u64, ()>,
+    ) {
+        let dir = tempfile::tempdir().unwrap();
+        let contig_header = contig_header();
+        for path in [
+            write_indexed_copy(dir.path()),
+            write_bigbed_copy(dir.path()),
+        ] {
+            let mut repository = BEDRepository::new(&path).unwrap();
+            let start = repository
+                .feature_start_around(contig_index, position, n, after, &contig_header)
+                .map_err(|_| ());
+            assert_eq!(start, expected, "{}", path);
+        }
     }
 }
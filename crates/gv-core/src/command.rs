@@ -1,16 +1,22 @@
 use crate::{
     error::TGVError,
     message::{
-        AlignmentDisplayOption, AlignmentFilter, AlignmentSort, ExportFormat, Message, Movement,
+        AlignmentColorBy, AlignmentDensity, AlignmentDisplayOption, AlignmentFilter,
+        AlignmentGroupBy, AlignmentSort, ExportFormat, Message, Movement, SignalDisplayOption,
     },
+    modification::ModificationMotif,
+    signal::{SignalScale, SignalStyle},
+    strand::Strand,
 };
 use nom::{
     IResult, Parser,
     branch::alt,
-    bytes::complete::{tag, tag_no_case},
-    character::complete::{char, multispace0, u64},
-    combinator::{opt, value},
-    multi::{many0, separated_list0},
+    bytes::complete::{tag, tag_no_case, take_while_m_n, take_while1},
+    character::complete::{char, multispace0, multispace1, satisfy, u16, u32, u64, usize},
+    combinator::{not, opt, peek, value, verify},
+    error::Error,
+    multi::{many0, many1, separated_list0},
+    number::complete::double,
     sequence::{delimited, preceded, separated_pair, terminated},
 };
 
@@ -19,6 +25,16 @@ use nom::{
 /// :h: Help.
 /// :1234: Go to position 1234 on the same contig.
 /// :12:1234: Go to position 1234 on contig 12.
+/// :samples NA00003,NA00001: Show only these VCF samples, in this order. `:samples` shows all samples.
+/// :mate: Go to the mate of the nearest breakend (BND) variant.
+/// :signal 2 heatmap log: Draw the 2nd signal track as a log-scaled heatmap. Without a number: all signal tracks.
+/// :junctions 5: Show splice junctions supported by at least 5 reads. `:junctions` shows all junctions.
+/// :display squished: Draw two alignment tracks per line. Also: expanded, collapsed.
+/// :color-by tag(HP): Color reads by the HP tag. Can be combined with sort and filter options.
+/// :group-by hp: Stack reads of each haplotype separately. Can be combined with sort and filter options.
+/// :group-by base(1234): Stack reads by their base at 1234 (e.g. a heterozygous SNV). `base`: at the current position.
+/// :mod CG: Color base modifications, only in CG context. `:mod` colors all calls.
+/// :splits: Mark reads whose other alignments (SA tag) are outside the view.
 pub fn parse(input: &str) -> Result<Vec<Message>, TGVError> {
     if input == "q" {
         return Ok(vec![Message::Quit]);
@@ -41,22 +57,99 @@ pub fn parse(input: &str) -> Result<Vec<Message>, TGVError> {
         ])]);
     }
 
-    if let Ok((_, true)) = show_base_modifications(input) {
-        return Ok(vec![Message::SetAlignmentOption(vec![
-            AlignmentDisplayOption::ShowBaseModifications,
-        ])]);
+    if preceded(multispace0, alt((keyword("mod"), keyword("modifications"))))
+        .parse(input)
+        .is_ok()
+    {
+        return match parse_base_modifications(input) {
+            Ok(("", motif)) => Ok(vec![Message::SetAlignmentOption(vec![
+                AlignmentDisplayOption::ShowBaseModifications(
+                    motif.map(ModificationMotif::new).transpose()?,
+                ),
+            ])]),
+            _ => Err(TGVError::RegisterError(
+                "Usage: mod [motif, e.g. CG, CHG, GATC]".to_string(),
+            )),
+        };
     }
 
     if let Some(result) = try_parse_export(input) {
         return result;
     }
 
+    if input.trim().eq_ignore_ascii_case("mate") {
+        return Ok(vec![Message::Move(Movement::BreakendMate)]);
+    }
+
+    if let Ok(("", samples)) = parse_variant_samples(input) {
+        return Ok(vec![Message::SetVariantSamples(samples)]);
+    }
+
+    if preceded(multispace0, keyword("signal"))
+        .parse(input)
+        .is_ok()
+    {
+        return match parse_signal_display(input) {
+            Ok(("", (track, options))) => Ok(vec![Message::SetSignalDisplay(
+                track.map(|track| track - 1),
+                options,
+            )]),
+            _ => Err(TGVError::RegisterError(
+                "Usage: signal [n] bar|heatmap|auto|max <value>|log|linear".to_string(),
+            )),
+        };
+    }
+
+    if preceded(multispace0, keyword("junctions"))
+        .parse(input)
+        .is_ok()
+    {
+        return match parse_junction_min_count(input) {
+            Ok(("", min_count)) => Ok(vec![Message::SetJunctionMinCount(min_count.unwrap_or(1))]),
+            _ => Err(TGVError::RegisterError(
+                "Usage: junctions [minimum read count]".to_string(),
+            )),
+        };
+    }
+
+    if preceded(multispace0, keyword("display"))
+        .parse(input)
+        .is_ok()
+    {
+        return match parse_alignment_density(input) {
+            Ok(("", density)) => Ok(vec![Message::SetAlignmentDensity(density)]),
+            _ => Err(TGVError::RegisterError(
+                "Usage: display expanded|squished|collapsed".to_string(),
+            )),
+        };
+    }
+
     if let Ok((remaining, options)) = parse_display_options(input) {
         if remaining.is_empty() {
             return Ok(vec![Message::SetAlignmentOption(options)]);
         }
     }
 
+    if preceded(multispace0, keyword("color-by"))
+        .parse(input)
+        .is_ok()
+    {
+        return Err(TGVError::RegisterError(
+            "Usage: color-by strand|firstofpair|insertsize|pairorientation|discordant|mapq|tag(XX)"
+                .to_string(),
+        ));
+    }
+
+    if preceded(multispace0, keyword("group-by"))
+        .parse(input)
+        .is_ok()
+    {
+        return Err(TGVError::RegisterError(
+            "Usage: group-by hp|readgroup|sample|strand|matecontig|base[(position)]|tag(XX)"
+                .to_string(),
+        ));
+    }
+
     let split = input.split(":").collect::<Vec<&str>>();
 
     match split.len() {
@@ -83,7 +176,15 @@ pub fn parse(input: &str) -> Result<Vec<Message>, TGVError> {
 
 /// Highest level parser
 fn parse_display_options(input: &str) -> IResult<&str, Vec<AlignmentDisplayOption>> {
-    many0(alt((parse_filter, parse_sort))).parse(input)
+    many0(alt((
+        parse_filter,
+        parse_sort,
+        parse_view_as_pairs,
+        parse_mark_split_reads,
+        parse_color_by,
+        parse_group_by,
+    )))
+    .parse(input)
 }
 
 fn restore_default_options(input: &str) -> IResult<&str, bool> {
@@ -104,15 +205,35 @@ fn view_as_pair(input: &str) -> IResult<&str, bool> {
     Ok((input, (input.is_empty() && !parsed.is_empty())))
 }
 
-fn show_base_modifications(input: &str) -> IResult<&str, bool> {
-    let (input, parsed) = delimited(
-        multispace0,
-        alt((tag_no_case("mod"), tag_no_case("modifications"))),
-        multispace0,
+/// `paired` as a display option, so that it can be combined with sorting and filtering.
+fn parse_view_as_pairs(input: &str) -> IResult<&str, AlignmentDisplayOption> {
+    value(
+        AlignmentDisplayOption::ViewAsPairs,
+        delimited(multispace0, tag_no_case("paired"), multispace0),
     )
-    .parse(input)?;
+    .parse(input)
+}
 
-    Ok((input, (input.is_empty() && !parsed.is_empty())))
+/// `splits`: mark reads with other alignments (SA tag) outside the view.
+fn parse_mark_split_reads(input: &str) -> IResult<&str, AlignmentDisplayOption> {
+    value(
+        AlignmentDisplayOption::MarkSplitReads,
+        delimited(multispace0, keyword("splits"), multispace0),
+    )
+    .parse(input)
+}
+
+/// `mod [motif]`. motif: reference context of the shown calls in IUPAC codes.
+fn parse_base_modifications(input: &str) -> IResult<&str, Option<&str>> {
+    delimited(
+        terminated(
+            preceded(multispace0, alt((keyword("modifications"), keyword("mod")))),
+            multispace0,
+        ),
+        opt(take_while1(|c: char| c.is_ascii_alphabetic())),
+        multispace0,
+    )
+    .parse(input)
 }
 
 fn parse_optional_parenthesis(input: &str) -> IResult<&str, Option<Option<u64>>> {
@@ -157,11 +278,28 @@ fn sort_unit(input: &str) -> IResult<&str, AlignmentSort> {
         value(AlignmentSort::AlignedReadLength, tag_no_case("LENGTH")),
         value(AlignmentSort::InsertSize, tag_no_case("INSERTSIZE")),
         value(AlignmentSort::ChromosomeOfMate, tag_no_case("MATECONTIG")),
-        value(AlignmentSort::Tag, tag_no_case("TAG")),
+        tag_sort_unit,
     ))
     .parse(input)
 }
 
+// Parse TAG with the tag name in parentheses
+fn tag_sort_unit(input: &str) -> IResult<&str, AlignmentSort> {
+    preceded(tag_no_case("TAG"), tag_name)
+        .parse(input)
+        .map(|(input, name)| (input, AlignmentSort::Tag(name.to_string())))
+}
+
+/// Two-character tag name in parentheses, e.g. (HP)
+fn tag_name(input: &str) -> IResult<&str, &str> {
+    delimited(
+        tag("("),
+        take_while_m_n(2, 2, |c: char| c.is_ascii_alphanumeric()),
+        tag(")"),
+    )
+    .parse(input)
+}
+
 // Parse a single sort term (basic sort + optional DESC/DEC)
 fn sort_and_direction(input: &str) -> IResult<&str, AlignmentSort> {
     let (input, basic_sort) = terminated(sort_unit, multispace0).parse(input)?;
@@ -225,6 +363,58 @@ fn parse_sort(input: &str) -> IResult<&str, AlignmentDisplayOption> {
     .map(|(input, filter)| (input, AlignmentDisplayOption::Sort(filter)))
 }
 
+fn parse_color_by(input: &str) -> IResult<&str, AlignmentDisplayOption> {
+    delimited(
+        preceded(multispace0, keyword("COLOR-BY")),
+        preceded(multispace1, color_by_unit),
+        multispace0,
+    )
+    .parse(input)
+    .map(|(input, color_by)| (input, AlignmentDisplayOption::ColorBy(color_by)))
+}
+
+fn color_by_unit(input: &str) -> IResult<&str, AlignmentColorBy> {
+    alt((
+        value(AlignmentColorBy::Strand, keyword("STRAND")),
+        value(AlignmentColorBy::FirstOfPairStrand, keyword("FIRSTOFPAIR")),
+        value(AlignmentColorBy::InsertSize, keyword("INSERTSIZE")),
+        value(
+            AlignmentColorBy::PairOrientation,
+            keyword("PAIRORIENTATION"),
+        ),
+        value(AlignmentColorBy::Discordant, keyword("DISCORDANT")),
+        value(AlignmentColorBy::MappingQuality, keyword("MAPQ")),
+        preceded(tag_no_case("TAG"), tag_name).map(|name| AlignmentColorBy::Tag(name.to_string())),
+    ))
+    .parse(input)
+}
+
+fn parse_group_by(input: &str) -> IResult<&str, AlignmentDisplayOption> {
+    delimited(
+        preceded(multispace0, keyword("GROUP-BY")),
+        preceded(multispace1, group_by_unit),
+        multispace0,
+    )
+    .parse(input)
+    .map(|(input, group_by)| (input, AlignmentDisplayOption::GroupBy(group_by)))
+}
+
+fn group_by_unit(input: &str) -> IResult<&str, AlignmentGroupBy> {
+    alt((
+        value(AlignmentGroupBy::Tag("HP".to_string()), keyword("HP")),
+        value(AlignmentGroupBy::ReadGroup, keyword("READGROUP")),
+        value(AlignmentGroupBy::Sample, keyword("SAMPLE")),
+        value(AlignmentGroupBy::Strand, keyword("STRAND")),
+        value(AlignmentGroupBy::ChromosomeOfMate, keyword("MATECONTIG")),
+        preceded(keyword("BASE"), parse_optional_parenthesis).map(|position| match position {
+            Some(Some(position)) => AlignmentGroupBy::BaseAt(position),
+            _ => AlignmentGroupBy::BaseAtCurrentPosition,
+        }),
+        preceded(tag_no_case("TAG"), tag_name).map(|name| AlignmentGroupBy::Tag(name.to_string())),
+    ))
+    .parse(input)
+}
+
 fn node_base_filter(input: &str) -> IResult<&str, AlignmentFilter> {
     let (input, (position, base)) = preceded(
         tag_no_case("BASE"),
@@ -289,8 +479,271 @@ fn try_parse_export(input: &str) -> Option<Result<Vec<Message>, TGVError>> {
     Some(Ok(vec![Message::Export(format, path.to_string())]))
 }
 
+/// `samples NA00003, NA00001` or `samples NA00003 NA00001`.
+fn parse_variant_samples(input: &str) -> IResult<&str, Vec<String>> {
+    delimited(
+        terminated(preceded(multispace0, keyword("samples")), multispace0),
+        separated_list0(
+            alt((delimited(multispace0, tag(","), multispace0), multispace1)),
+            take_while1(|c: char| c != ',' && !c.is_whitespace()),
+        ),
+        multispace0,
+    )
+    .parse(input)
+    .map(|(input, samples)| (input, samples.into_iter().map(String::from).collect()))
+}
+
+/// `signal [n] <options>`. n: 1-based signal track number.
+/// Options: bar, heatmap, auto, max <value>, log, linear.
+fn parse_signal_display(input: &str) -> IResult<&str, (Option<usize>, Vec<SignalDisplayOption>)> {
+    delimited(
+        terminated(preceded(multispace0, keyword("signal")), multispace0),
+        (
+            opt(terminated(verify(usize, |n| *n > 0), multispace1)),
+            many1(delimited(multispace0, signal_display_option, multispace0)),
+        ),
+        multispace0,
+    )
+    .parse(input)
+}
+
+/// `junctions [n]`. n: minimum number of reads supporting a junction.
+fn parse_junction_min_count(input: &str) -> IResult<&str, Option<usize>> {
+    delimited(
+        terminated(preceded(multispace0, keyword("junctions")), multispace0),
+        opt(verify(usize, |n| *n > 0)),
+        multispace0,
+    )
+    .parse(input)
+}
+
+/// `display expanded|squished|collapsed`.
+fn parse_alignment_density(input: &str) -> IResult<&str, AlignmentDensity> {
+    delimited(
+        terminated(preceded(multispace0, keyword("display")), multispace0),
+        alt((
+            value(AlignmentDensity::Expanded, keyword("expanded")),
+            value(AlignmentDensity::Squished, keyword("squished")),
+            value(AlignmentDensity::Collapsed, keyword("collapsed")),
+        )),
+        multispace0,
+    )
+    .parse(input)
+}
+
+fn signal_display_option(input: &str) -> IResult<&str, SignalDisplayOption> {
+    alt((
+        value(SignalDisplayOption::Style(SignalStyle::Bar), keyword("bar")),
+        value(
+            SignalDisplayOption::Style(SignalStyle::Heatmap),
+            keyword("heatmap"),
+        ),
+        value(
+            SignalDisplayOption::Scale(SignalScale::Auto),
+            keyword("auto"),
+        ),
+        value(SignalDisplayOption::LogScale(true), keyword("log")),
+        value(SignalDisplayOption::LogScale(false), keyword("linear")),
+        preceded(
+            terminated(keyword("max"), multispace1),
+            verify(double, |max: &f64| max.is_finite() && *max > 0.0),
+        )
+        .map(|max| SignalDisplayOption::Scale(SignalScale::Fixed(max))),
+    ))
+    .parse(input)
+}
+
+/// Filter expression. Precedence from low to high: OR, AND, NOT.
+/// Example: MAPQ>=20 AND NOT FLAG&1024 AND (TAG(HP)=1 OR STRAND=+)
 fn node_filter(input: &str) -> IResult<&str, AlignmentFilter> {
-    delimited(multispace0, alt((node_base_filter,)), multispace0).parse(input)
+    delimited(multispace0, node_or_filter, multispace0).parse(input)
+}
+
+/// Case-insensitive keyword not followed by an alphanumeric character (e.g. OR but not ORDER).
+fn keyword<'a>(
+    word: &'static str,
+) -> impl Parser<&'a str, Output = &'a str, Error = Error<&'a str>> {
+    terminated(
+        tag_no_case(word),
+        not(peek(satisfy(|c: char| c.is_ascii_alphanumeric()))),
+    )
+}
+
+fn node_or_filter(input: &str) -> IResult<&str, AlignmentFilter> {
+    let (input, first) = node_and_filter(input)?;
+    let (input, rest) = many0(preceded(
+        delimited(multispace0, keyword("OR"), multispace0),
+        node_and_filter,
+    ))
+    .parse(input)?;
+
+    Ok((
+        input,
+        rest.into_iter().fold(first, |acc, filter| acc.or(filter)),
+    ))
+}
+
+fn node_and_filter(input: &str) -> IResult<&str, AlignmentFilter> {
+    let (input, first) = node_not_filter(input)?;
+    let (input, rest) = many0(preceded(
+        delimited(multispace0, keyword("AND"), multispace0),
+        node_not_filter,
+    ))
+    .parse(input)?;
+
+    Ok((
+        input,
+        rest.into_iter().fold(first, |acc, filter| acc.and(filter)),
+    ))
+}
+
+fn node_not_filter(input: &str) -> IResult<&str, AlignmentFilter> {
+    alt((
+        preceded(terminated(keyword("NOT"), multispace0), node_not_filter).map(|f| f.not()),
+        node_unit_filter,
+    ))
+    .parse(input)
+}
+
+fn node_unit_filter(input: &str) -> IResult<&str, AlignmentFilter> {
+    alt((
+        delimited(
+            terminated(tag("("), multispace0),
+            node_or_filter,
+            preceded(multispace0, tag(")")),
+        ),
+        node_base_filter,
+        node_mapq_filter,
+        node_flag_filter,
+        node_strand_filter,
+        node_tag_filter,
+        node_range_filter,
+    ))
+    .parse(input)
+}
+
+fn equal_sign(input: &str) -> IResult<&str, &str> {
+    delimited(multispace0, alt((tag("=="), tag("="))), multispace0).parse(input)
+}
+
+/// MAPQ>=20, MAPQ<=20, MAPQ>20, MAPQ<20, MAPQ=20
+fn node_mapq_filter(input: &str) -> IResult<&str, AlignmentFilter> {
+    let (input, (operator, mapq)) = preceded(
+        tag_no_case("MAPQ"),
+        (
+            delimited(
+                multispace0,
+                alt((
+                    tag(">="),
+                    tag("<="),
+                    tag(">"),
+                    tag("<"),
+                    tag("=="),
+                    tag("="),
+                )),
+                multispace0,
+            ),
+            u16,
+        ),
+    )
+    .parse(input)?;
+
+    let filter = match operator {
+        ">=" => AlignmentFilter::MappingQualityGE(mapq),
+        "<=" => AlignmentFilter::MappingQualityLE(mapq),
+        ">" => match mapq.checked_add(1) {
+            Some(mapq) => AlignmentFilter::MappingQualityGE(mapq),
+            None => AlignmentFilter::False,
+        },
+        "<" => match mapq.checked_sub(1) {
+            Some(mapq) => AlignmentFilter::MappingQualityLE(mapq),
+            None => AlignmentFilter::False,
+        },
+        _ => AlignmentFilter::MappingQualityGE(mapq).and(AlignmentFilter::MappingQualityLE(mapq)),
+    };
+
+    Ok((input, filter))
+}
+
+/// FLAG&1024 (any bits), FLAG&3=3 (all bits), FLAG&1024=0 (no bits), FLAG=99 (exact)
+fn node_flag_filter(input: &str) -> IResult<&str, AlignmentFilter> {
+    let (input, _) = tag_no_case("FLAG")(input)?;
+
+    if let Ok((input, flags)) = preceded(equal_sign, u32).parse(input) {
+        return Ok((input, AlignmentFilter::FlagsEqual(flags)));
+    }
+
+    let (input, (mask, value)) = (
+        preceded(delimited(multispace0, tag("&"), multispace0), u32),
+        opt(preceded(equal_sign, u32)),
+    )
+        .parse(input)?;
+
+    let filter = match value {
+        None => AlignmentFilter::FlagsAny(mask),
+        Some(value) if value & !mask != 0 => AlignmentFilter::False,
+        Some(value) if value == mask => AlignmentFilter::FlagsAll(mask),
+        Some(0) => AlignmentFilter::FlagsAny(mask).not(),
+        Some(value) => {
+            AlignmentFilter::FlagsAll(value).and(AlignmentFilter::FlagsAny(mask & !value).not())
+        }
+    };
+
+    Ok((input, filter))
+}
+
+/// STRAND=+ / STRAND=-
+fn node_strand_filter(input: &str) -> IResult<&str, AlignmentFilter> {
+    preceded(
+        terminated(tag_no_case("STRAND"), equal_sign),
+        alt((
+            value(Strand::Forward, alt((tag("+"), tag_no_case("F")))),
+            value(Strand::Reverse, alt((tag("-"), tag_no_case("R")))),
+        )),
+    )
+    .parse(input)
+    .map(|(input, strand)| (input, AlignmentFilter::Strand(strand)))
+}
+
+/// TAG(HP)=1
+fn node_tag_filter(input: &str) -> IResult<&str, AlignmentFilter> {
+    separated_pair(
+        preceded(tag_no_case("TAG"), tag_name),
+        equal_sign,
+        take_while1(|c: char| !c.is_whitespace() && c != '(' && c != ')'),
+    )
+    .parse(input)
+    .map(|(input, (name, value))| {
+        (
+            input,
+            AlignmentFilter::Tag(name.to_string(), value.to_string()),
+        )
+    })
+}
+
+/// START(100,200), END(100,200), OVERLAPS(100,200). 1-based, inclusive.
+fn node_range_filter(input: &str) -> IResult<&str, AlignmentFilter> {
+    let (input, (kind, (left, right))) = (
+        alt((
+            tag_no_case("START"),
+            tag_no_case("END"),
+            tag_no_case("OVERLAPS"),
+        )),
+        delimited(
+            terminated(tag("("), multispace0),
+            separated_pair(usize, delimited(multispace0, char(','), multispace0), usize),
+            preceded(multispace0, tag(")")),
+        ),
+    )
+        .parse(input)?;
+
+    let filter = match kind.to_ascii_uppercase().as_str() {
+        "START" => AlignmentFilter::StartsIn(left, right),
+        "END" => AlignmentFilter::EndsIn(left, right),
+        _ => AlignmentFilter::Overlaps(left, right),
+    };
+
+    Ok((input, filter))
 }
 
 #[cfg(test)]
@@ -320,6 +773,12 @@ mod tests {
     #[case("START", AlignmentSort::Start)]
     #[case("MAPQ", AlignmentSort::MappingQuality)]
     #[case("readname", AlignmentSort::ReadName)]
+    #[case("MATECONTIG", AlignmentSort::ChromosomeOfMate)]
+    #[case("TAG(HP)", AlignmentSort::Tag("HP".to_string()))]
+    #[case(
+        "tag(NM) DESC",
+        AlignmentSort::Reverse(Box::new(AlignmentSort::Tag("NM".to_string())))
+    )]
     // Test with DESC/DEC
     #[case(
         "BASE(2) DESC",
@@ -367,6 +826,9 @@ mod tests {
 
     #[rstest]
     #[case("BASE() DEC")]
+    #[case("TAG")]
+    #[case("TAG()")]
+    #[case("TAG(HPX)")]
     fn test_parse_alignment_sort_errors(#[case] input: &str) {
         match parse_sort_expression(input) {
             Ok((input, _sort)) => {
@@ -384,6 +846,65 @@ mod tests {
     #[case("BASE=softclip", AlignmentFilter::BaseAtCurrentPositionSoftClip)]
     #[case("BASE(123)=softclip", AlignmentFilter::BaseSoftclip(123))]
     #[case("BASE(123) = A", AlignmentFilter::Base(123, 'A'))]
+    #[case("MAPQ>=20", AlignmentFilter::MappingQualityGE(20))]
+    #[case("mapq <= 20", AlignmentFilter::MappingQualityLE(20))]
+    #[case("MAPQ>20", AlignmentFilter::MappingQualityGE(21))]
+    #[case("MAPQ<0", AlignmentFilter::False)]
+    #[case(
+        "MAPQ=60",
+        AlignmentFilter::And(
+            Box::new(AlignmentFilter::MappingQualityGE(60)),
+            Box::new(AlignmentFilter::MappingQualityLE(60))
+        )
+    )]
+    #[case("FLAG&1024", AlignmentFilter::FlagsAny(1024))]
+    #[case("FLAG & 3 = 3", AlignmentFilter::FlagsAll(3))]
+    #[case(
+        "FLAG&1024=0",
+        AlignmentFilter::Not(Box::new(AlignmentFilter::FlagsAny(1024)))
+    )]
+    #[case(
+        "FLAG&3=1",
+        AlignmentFilter::And(
+            Box::new(AlignmentFilter::FlagsAll(1)),
+            Box::new(AlignmentFilter::Not(Box::new(AlignmentFilter::FlagsAny(2))))
+        )
+    )]
+    #[case("FLAG&1=2", AlignmentFilter::False)]
+    #[case("FLAG=99", AlignmentFilter::FlagsEqual(99))]
+    #[case("STRAND=+", AlignmentFilter::Strand(Strand::Forward))]
+    #[case("NOT STRAND=+", AlignmentFilter::Strand(Strand::Reverse))]
+    #[case("TAG(HP)=1", AlignmentFilter::Tag("HP".to_string(), "1".to_string()))]
+    #[case("START(100, 200)", AlignmentFilter::StartsIn(100, 200))]
+    #[case("END(100,200)", AlignmentFilter::EndsIn(100, 200))]
+    #[case("overlaps(100,200)", AlignmentFilter::Overlaps(100, 200))]
+    #[case(
+        "MAPQ>=20 AND NOT FLAG&1024 AND TAG(HP)=1",
+        AlignmentFilter::And(
+            Box::new(AlignmentFilter::And(
+                Box::new(AlignmentFilter::MappingQualityGE(20)),
+                Box::new(AlignmentFilter::Not(Box::new(AlignmentFilter::FlagsAny(1024))))
+            )),
+            Box::new(AlignmentFilter::Tag("HP".to_string(), "1".to_string()))
+        )
+    )]
+    #[case(
+        "TAG(HP)=1 OR TAG(HP)=2 AND STRAND=-",
+        AlignmentFilter::Or(
+            Box::new(AlignmentFilter::Tag("HP".to_string(), "1".to_string())),
+            Box::new(AlignmentFilter::And(
+                Box::new(AlignmentFilter::Tag("HP".to_string(), "2".to_string())),
+                Box::new(AlignmentFilter::Strand(Strand::Reverse))
+            ))
+        )
+    )]
+    #[case(
+        "NOT (TAG(HP)=1 OR BASE=softclip)",
+        AlignmentFilter::Not(Box::new(AlignmentFilter::Or(
+            Box::new(AlignmentFilter::Tag("HP".to_string(), "1".to_string())),
+            Box::new(AlignmentFilter::BaseAtCurrentPositionSoftClip)
+        )))
+    )]
     fn test_parse_alignment_filter(#[case] input: &str, #[case] expected: AlignmentFilter) {
         let (remaining, filter) = node_filter(input).unwrap();
 
@@ -430,6 +951,99 @@ mod tests {
         7572659,
     ).into()]))]
     #[case("TP53", Ok(vec![Movement::Gene("TP53".to_string()).into()]))]
+    #[case("sort MAPQ DESC, STRAND", Ok(vec![Message::SetAlignmentOption(vec![
+        AlignmentDisplayOption::Sort(AlignmentSort::Then(
+            Box::new(AlignmentSort::Reverse(Box::new(AlignmentSort::MappingQuality))),
+            Box::new(AlignmentSort::StrandAtCurrentBase),
+        )),
+    ])]))]
+    #[case("paired sort BASE", Ok(vec![Message::SetAlignmentOption(vec![
+        AlignmentDisplayOption::ViewAsPairs,
+        AlignmentDisplayOption::Sort(AlignmentSort::BaseAtCurrentPosition),
+    ])]))]
+    #[case("filter MAPQ>=20 ORDER BY MAPQ", Ok(vec![Message::SetAlignmentOption(vec![
+        AlignmentDisplayOption::Filter(AlignmentFilter::MappingQualityGE(20)),
+        AlignmentDisplayOption::Sort(AlignmentSort::MappingQuality),
+    ])]))]
+    #[case("color-by strand", Ok(vec![Message::SetAlignmentOption(vec![
+        AlignmentDisplayOption::ColorBy(AlignmentColorBy::Strand),
+    ])]))]
+    #[case("paired color-by discordant", Ok(vec![Message::SetAlignmentOption(vec![
+        AlignmentDisplayOption::ViewAsPairs,
+        AlignmentDisplayOption::ColorBy(AlignmentColorBy::Discordant),
+    ])]))]
+    #[case("COLOR-BY tag(HP)", Ok(vec![Message::SetAlignmentOption(vec![
+        AlignmentDisplayOption::ColorBy(AlignmentColorBy::Tag("HP".to_string())),
+    ])]))]
+    #[case("group-by hp sort MAPQ", Ok(vec![Message::SetAlignmentOption(vec![
+        AlignmentDisplayOption::GroupBy(AlignmentGroupBy::Tag("HP".to_string())),
+        AlignmentDisplayOption::Sort(AlignmentSort::MappingQuality),
+    ])]))]
+    #[case("paired group-by matecontig color-by insertsize", Ok(vec![Message::SetAlignmentOption(vec![
+        AlignmentDisplayOption::ViewAsPairs,
+        AlignmentDisplayOption::GroupBy(AlignmentGroupBy::ChromosomeOfMate),
+        AlignmentDisplayOption::ColorBy(AlignmentColorBy::InsertSize),
+    ])]))]
+    #[case("splits", Ok(vec![Message::SetAlignmentOption(vec![
+        AlignmentDisplayOption::MarkSplitReads,
+    ])]))]
+    #[case("paired splits", Ok(vec![Message::SetAlignmentOption(vec![
+        AlignmentDisplayOption::ViewAsPairs,
+        AlignmentDisplayOption::MarkSplitReads,
+    ])]))]
+    #[case("mod", Ok(vec![Message::SetAlignmentOption(vec![
+        AlignmentDisplayOption::ShowBaseModifications(None),
+    ])]))]
+    #[case("modifications chg", Ok(vec![Message::SetAlignmentOption(vec![
+        AlignmentDisplayOption::ShowBaseModifications(Some(ModificationMotif::new("CHG").unwrap())),
+    ])]))]
+    #[case("mod CG GATC", Err(TGVError::RegisterError("Usage: mod [motif, e.g. CG, CHG, GATC]".to_string())))]
+    #[case("mod CXG", Err(TGVError::ValueError("Invalid modification motif: CXG. Use IUPAC codes, e.g. CG, CHG, GATC".to_string())))]
+    #[case("color-by haplotype", Err(TGVError::RegisterError("Usage: color-by strand|firstofpair|insertsize|pairorientation|discordant|mapq|tag(XX)".to_string())))]
+    #[case("group-by base", Ok(vec![Message::SetAlignmentOption(vec![
+        AlignmentDisplayOption::GroupBy(AlignmentGroupBy::BaseAtCurrentPosition),
+    ])]))]
+    #[case("group-by base(1234)", Ok(vec![Message::SetAlignmentOption(vec![
+        AlignmentDisplayOption::GroupBy(AlignmentGroupBy::BaseAt(1234)),
+    ])]))]
+    #[case("group-by", Err(TGVError::RegisterError("Usage: group-by hp|readgroup|sample|strand|matecontig|base[(position)]|tag(XX)".to_string())))]
+    #[case("mate", Ok(vec![Movement::BreakendMate.into()]))]
+    #[case("samples", Ok(vec![Message::SetVariantSamples(vec![])]))]
+    #[case("samples NA00003,NA00001", Ok(vec![Message::SetVariantSamples(vec![
+        "NA00003".to_string(),
+        "NA00001".to_string(),
+    ])]))]
+    #[case("SAMPLES NA00003, NA00001 ", Ok(vec![Message::SetVariantSamples(vec![
+        "NA00003".to_string(),
+        "NA00001".to_string(),
+    ])]))]
+    #[case("samples NA00003 NA00001", Ok(vec![Message::SetVariantSamples(vec![
+        "NA00003".to_string(),
+        "NA00001".to_string(),
+    ])]))]
+    #[case("signal heatmap", Ok(vec![Message::SetSignalDisplay(None, vec![
+        SignalDisplayOption::Style(SignalStyle::Heatmap),
+    ])]))]
+    #[case("signal 2 bar log max 50", Ok(vec![Message::SetSignalDisplay(Some(1), vec![
+        SignalDisplayOption::Style(SignalStyle::Bar),
+        SignalDisplayOption::LogScale(true),
+        SignalDisplayOption::Scale(SignalScale::Fixed(50.0)),
+    ])]))]
+    #[case("SIGNAL 1 linear auto", Ok(vec![Message::SetSignalDisplay(Some(0), vec![
+        SignalDisplayOption::LogScale(false),
+        SignalDisplayOption::Scale(SignalScale::Auto),
+    ])]))]
+    #[case("signal 0 bar", Err(TGVError::RegisterError("Usage: signal [n] bar|heatmap|auto|max <value>|log|linear".to_string())))]
+    #[case("signal max -1", Err(TGVError::RegisterError("Usage: signal [n] bar|heatmap|auto|max <value>|log|linear".to_string())))]
+    #[case("signal max inf", Err(TGVError::RegisterError("Usage: signal [n] bar|heatmap|auto|max <value>|log|linear".to_string())))]
+    #[case("signal max nan", Err(TGVError::RegisterError("Usage: signal [n] bar|heatmap|auto|max <value>|log|linear".to_string())))]
+    #[case("junctions", Ok(vec![Message::SetJunctionMinCount(1)]))]
+    #[case("junctions 5", Ok(vec![Message::SetJunctionMinCount(5)]))]
+    #[case("junctions 0", Err(TGVError::RegisterError("Usage: junctions [minimum read count]".to_string())))]
+    #[case("display squished", Ok(vec![Message::SetAlignmentDensity(AlignmentDensity::Squished)]))]
+    #[case("DISPLAY Collapsed", Ok(vec![Message::SetAlignmentDensity(AlignmentDensity::Collapsed)]))]
+    #[case("display expanded", Ok(vec![Message::SetAlignmentDensity(AlignmentDensity::Expanded)]))]
+    #[case("display dense", Err(TGVError::RegisterError("Usage: display expanded|squished|collapsed".to_string())))]
     #[case("invalid:command:format", Err(TGVError::RegisterError("Invalid command mode input: invalid:command:format".to_string())))]
     #[case("chr1:invalid", Err(TGVError::RegisterError("Invalid command mode input: chr1:invalid".to_string())))]
     fn test_command_parse(#[case] input: &str, #[case] expected: Result<Vec<Message>, TGVError>) {
@@ -84,6 +84,10 @@ pub struct Gene {
 
     pub cds_end: u64,
 
+    /// Bases before the first complete codon at the 5′ end of the CDS (the GFF phase).
+    /// Non-zero for coding sequences that are incomplete at the 5′ end.
+    pub cds_phase: u8,
+
     pub exon_starts: Vec<u64>,
 
     pub exon_ends: Vec<u64>,
@@ -131,6 +135,11 @@ impl Gene {
         self.exon_starts.len()
     }
 
+    /// Whether the gene has a CDS in its exons.
+    pub fn is_coding(&self) -> bool {
+        self.has_exons && self.cds_start <= self.cds_end
+    }
+
     pub fn features(&self) -> Vec<(u64, u64, SubGeneFeatureType, usize)> {
         // TODO: prevent labeling overlap.
         let mut features: Vec<(u64, u64, SubGeneFeatureType)> = Vec::new();
@@ -191,6 +191,42 @@ impl Region {
             .get_sequence_name()
             .map(|name| noodles::core::Region::new(name, start..=end)))
     }
+
+    /// Convert to a noodles region on a contig named as in the queried file.
+    pub fn noodles_region(&self, name: &str) -> Result<noodles::core::Region, TGVError> {
+        let start = noodles::core::Position::try_from(self.start() as usize).map_err(|_| {
+            TGVError::StateError(format!("Failed to convert to noodles region: {:?}", self))
+        })?;
+        let end = noodles::core::Position::try_from(self.end() as usize).map_err(|_| {
+            TGVError::StateError(format!("Failed to convert to noodles region: {:?}", self))
+        })?;
+        Ok(noodles::core::Region::new(name, start..=end))
+    }
+}
+
+/// Part of the genome for which data from a file is held in memory.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum LoadedRegion {
+    /// Nothing is loaded yet.
+    #[default]
+    Nothing,
+
+    /// All records in a region are loaded (indexed files).
+    Region(Region),
+
+    /// The whole file is loaded (small unindexed files).
+    All,
+}
+
+impl LoadedRegion {
+    /// Whether data overlapping region is completely loaded.
+    pub fn has_complete_data(&self, region: &Region) -> bool {
+        match self {
+            LoadedRegion::Nothing => false,
+            LoadedRegion::Region(loaded) => loaded.contains(region),
+            LoadedRegion::All => true,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
@@ -13,8 +13,12 @@ pub mod reference;
 pub mod repository;
 pub mod sequence;
 pub mod settings;
+pub mod signal;
 pub mod state;
 pub mod strand;
+#[cfg(test)]
+mod test_utils;
 pub mod track;
 pub mod tracks;
+pub mod translation;
 pub mod variant;
@@ -1,3 +1,5 @@
+use crate::modification::ModificationMotif;
+use crate::signal::{SignalScale, SignalStyle};
 use crate::strand::Strand;
 
 use strum::Display;
@@ -9,7 +11,7 @@ pub enum ExportFormat {
     Text,
 }
 
-#[derive(Debug, Clone, Eq, PartialEq, Display)]
+#[derive(Debug, Clone, PartialEq, Display)]
 pub enum Message {
     Move(Movement),
 
@@ -24,6 +26,18 @@ pub enum Message {
 
     /// Export current view to a file.
     Export(ExportFormat, String),
+
+    /// Show these VCF samples in the genotype rows, in this order. Empty: all samples.
+    SetVariantSamples(Vec<String>),
+
+    /// Change how the nth signal track is drawn. None: all signal tracks.
+    SetSignalDisplay(Option<usize>, Vec<SignalDisplayOption>),
+
+    /// Only show and navigate splice junctions supported by at least this many reads.
+    SetJunctionMinCount(usize),
+
+    /// How densely alignment tracks are drawn.
+    SetAlignmentDensity(AlignmentDensity),
 }
 
 impl From<Movement> for Message {
@@ -77,12 +91,30 @@ pub enum Movement {
     PreviousGenesStart(usize),
     PreviousGenesEnd(usize),
 
+    /// Start of the nth next / previous feature in the BED track.
+    NextFeature(usize),
+    PreviousFeature(usize),
+
+    /// Start of the nth next / previous splice junction in the loaded reads.
+    NextJunction(usize),
+    PreviousJunction(usize),
+
+    /// nth next / previous position whose alt-allele frequency passes the highlighting threshold.
+    NextAltAllele(usize),
+    PreviousAltAllele(usize),
+
     NextContig(usize),
     PreviousContig(usize),
     ContigIndex(usize),
 
     Gene(String),
 
+    /// Mate of the breakend (BND) variant closest to the focus.
+    BreakendMate,
+
+    /// nth other alignment (SA tag) of the topmost split read at the focus.
+    SplitReadSegment(usize),
+
     Default, // Calculate a default location based on the genome context
 
              // ResizeTrack {
@@ -113,7 +145,110 @@ pub enum AlignmentDisplayOption {
 
     /// Color aligned bases by base-modification probability (MM/ML tags).
     /// Supports 5mC, 5hmC, and 6mA.
-    ShowBaseModifications,
+    /// With a motif, only calls in that reference context (e.g. CG, GATC) are colored.
+    ShowBaseModifications(Option<ModificationMotif>),
+
+    /// Mark reads with other alignments (SA tag) outside the view.
+    MarkSplitReads,
+
+    #[strum(to_string = "Color by: {0}")]
+    ColorBy(AlignmentColorBy),
+
+    #[strum(to_string = "Group by: {0}")]
+    GroupBy(AlignmentGroupBy),
+}
+
+/// Read coloring. Reference: https://igv.org/doc/desktop/#UserGuide/tracks/alignments/color_alignments/
+#[derive(Debug, Clone, Eq, PartialEq, Display)]
+pub enum AlignmentColorBy {
+    /// Forward and reverse strands
+    Strand,
+
+    /// Strand of the first read in the pair
+    #[strum(to_string = "First-of-pair strand")]
+    FirstOfPairStrand,
+
+    /// Insert sizes outside the expected range, and mates on other contigs
+    #[strum(to_string = "Insert size")]
+    InsertSize,
+
+    /// LR, RL, LL, RR pairs
+    #[strum(to_string = "Pair orientation")]
+    PairOrientation,
+
+    /// Discordant pairs: insert size, mate contig and pair orientation
+    #[strum(to_string = "Discordant pairs")]
+    Discordant,
+
+    /// MAPQ, lighter is higher
+    #[strum(to_string = "MAPQ")]
+    MappingQuality,
+
+    /// Value of an auxiliary tag
+    #[strum(to_string = "Tag({0})")]
+    Tag(String),
+}
+
+/// Read grouping. Each group is stacked separately below a label track.
+#[derive(Debug, Clone, Eq, PartialEq, Display)]
+pub enum AlignmentGroupBy {
+    /// Value of an auxiliary tag, e.g. HP for haplotypes
+    #[strum(to_string = "{0}")]
+    Tag(String),
+
+    /// Read group (RG tag)
+    #[strum(to_string = "Read group")]
+    ReadGroup,
+
+    /// Sample name (SM) of the read group
+    Sample,
+
+    Strand,
+
+    /// Contig of the mate
+    #[strum(to_string = "Mate contig")]
+    ChromosomeOfMate,
+
+    /// Base at a position (1-based), e.g. a heterozygous SNV to phase reads. Deletions are DEL.
+    #[strum(to_string = "Base at {0}")]
+    BaseAt(u64),
+
+    #[strum(to_string = "Base")]
+    BaseAtCurrentPosition,
+}
+
+impl AlignmentGroupBy {
+    /// Translate grouping at the current position to grouping at a given position (1-based).
+    pub fn at_position(self, position: u64) -> Self {
+        match self {
+            Self::BaseAtCurrentPosition => Self::BaseAt(position),
+            _ => self,
+        }
+    }
+}
+
+/// How densely alignment tracks are drawn. Reference: https://igv.org/doc/desktop/#UserGuide/tracks/alignments/display_options/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Display)]
+pub enum AlignmentDensity {
+    /// One track per line.
+    #[default]
+    Expanded,
+
+    /// Two tracks per line, drawn with half blocks.
+    Squished,
+
+    /// Consensus on the first line, other alleles below.
+    Collapsed,
+}
+
+#[derive(Debug, Clone, PartialEq, Display)]
+pub enum SignalDisplayOption {
+    Style(SignalStyle),
+
+    Scale(SignalScale),
+
+    /// Plot log10(1 + value).
+    LogScale(bool),
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Display)]
@@ -150,21 +285,27 @@ pub enum AlignmentFilter {
     BaseAtCurrentPositionSoftClip,
 
     /// MAPQ greater or equal than
+    #[strum(to_string = "MAPQ>={0}")]
     MappingQualityGE(u16),
 
     /// MAPQ smaller or equal than
+    #[strum(to_string = "MAPQ<={0}")]
     MappingQualityLE(u16),
 
     /// All bits in the flag are 1 (equivalent to samtools view -f)
+    #[strum(to_string = "FLAG&{0}={0}")]
     FlagsAll(u32),
 
     /// Any bits in the flag are 1 (equivalent to samtools view -rf)
+    #[strum(to_string = "FLAG&{0}")]
     FlagsAny(u32),
 
     /// Exact flag match
+    #[strum(to_string = "FLAG={0}")]
     FlagsEqual(u32),
 
     /// Tag equal to the value
+    #[strum(to_string = "TAG({0})={1}")]
     Tag(String, String),
 
     #[strum(to_string = "NOT({0})")]
@@ -209,6 +350,24 @@ impl AlignmentFilter {
             self_ => Self::Not(Box::new(self_)),
         }
     }
+
+    /// Translate filters at the current position to filters at a given position (1-based).
+    pub fn at_position(self, position: u64) -> Self {
+        match self {
+            Self::BaseAtCurrentPosition(base) => Self::Base(position, base),
+            Self::BaseAtCurrentPositionSoftClip => Self::BaseSoftclip(position),
+            Self::Not(filter) => Self::Not(Box::new(filter.at_position(position))),
+            Self::And(first, second) => Self::And(
+                Box::new(first.at_position(position)),
+                Box::new(second.at_position(position)),
+            ),
+            Self::Or(first, second) => Self::Or(
+                Box::new(first.at_position(position)),
+                Box::new(second.at_position(position)),
+            ),
+            _ => self,
+        }
+    }
 }
 
 /// Sort alignment options
@@ -240,10 +399,10 @@ pub enum AlignmentSort {
     /// MAPQ, reversed order
     MappingQuality,
 
-    ///?
+    /// Sample name (SM) of the read group
     Sample,
 
-    ///?
+    /// Read group (RG tag)
     ReadGroup,
 
     /// First in pair, second in pair, unpaired
@@ -255,14 +414,15 @@ pub enum AlignmentSort {
     /// alignment_end - alignment_start
     AlignedReadLength,
 
-    /// ?
+    /// Absolute insert size, reversed order
     InsertSize,
 
-    /// ?
+    /// Reads with mates on other contigs first, grouped by the mate contig
     ChromosomeOfMate,
 
-    ///?
-    Tag,
+    /// Value of an auxiliary tag
+    #[strum(to_string = "Tag({0})")]
+    Tag(String),
 
     /// Sort by 0 first and then 1
     #[strum(to_string = "{0}, {1}")]
@@ -297,4 +457,29 @@ impl AlignmentSort {
             _ => Self::Reverse(Box::new(self)),
         }
     }
+
+    /// Translate sorts at the current position to sorts at a given position (1-based).
+    pub fn at_position(self, position: u64) -> AlignmentSort {
+        match self {
+            Self::BaseAtCurrentPosition => Self::BaseAt(position),
+            Self::StrandAtCurrentBase => Self::StrandAt(position),
+            Self::Then(first, second) => Self::Then(
+                Box::new(first.at_position(position)),
+                Box::new(second.at_position(position)),
+            ),
+            Self::Reverse(sort) => Self::Reverse(Box::new(sort.at_position(position))),
+            _ => self,
+        }
+    }
+
+    /// The first position (1-based) the sort refers to.
+    /// Rows are sorted by reads covering this position. None if the sort is position-independent.
+    pub fn position(&self) -> Option<u64> {
+        match self {
+            Self::BaseAt(position) | Self::StrandAt(position) => Some(*position),
+            Self::Then(first, second) => first.position().or(second.position()),
+            Self::Reverse(sort) => sort.position(),
+            _ => None,
+        }
+    }
 }
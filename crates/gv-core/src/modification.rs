@@ -3,15 +3,57 @@
 use std::collections::HashMap;
 
 use crate::error::TGVError;
+use crate::sequence::Sequence;
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum ModificationType {
-    /// 5-methylcytosine (5mC), encoded as C+m in the MM tag
+    /// 5-methylcytosine (5mC), encoded as C+m or ChEBI 27551 in the MM tag
     FiveMC,
-    /// 5-hydroxymethylcytosine (5hmC), encoded as C+h in the MM tag
+    /// 5-hydroxymethylcytosine (5hmC), encoded as C+h or ChEBI 76792 in the MM tag
     FiveHMC,
-    /// N6-methyladenine (6mA), encoded as A+a in the MM tag
+    /// 5-formylcytosine (5fC), encoded as C+f or ChEBI 76794 in the MM tag
+    FiveFC,
+    /// 5-carboxylcytosine (5caC), encoded as C+c or ChEBI 76793 in the MM tag
+    FiveCaC,
+    /// N4-methylcytosine (4mC), encoded as ChEBI 21839 in the MM tag
+    FourMC,
+    /// N6-methyladenine (6mA), encoded as A+a or ChEBI 28871 in the MM tag
     SixMA,
+    /// 5-bromodeoxyuridine (BrdU), encoded as ChEBI 472552 in the MM tag
+    BrdU,
+    /// Any other code as written in the MM tag: a single letter (e.g. "o" for 8-oxoG,
+    /// or "C" for any modification of C) or a ChEBI ID.
+    Other(String),
+}
+
+impl ModificationType {
+    /// Modification from a single-letter code or a ChEBI ID.
+    pub fn from_code(code: &str) -> Self {
+        match code {
+            "m" | "27551" => ModificationType::FiveMC,
+            "h" | "76792" => ModificationType::FiveHMC,
+            "f" | "76794" => ModificationType::FiveFC,
+            "c" | "76793" => ModificationType::FiveCaC,
+            "21839" => ModificationType::FourMC,
+            "a" | "28871" => ModificationType::SixMA,
+            "472552" => ModificationType::BrdU,
+            _ => ModificationType::Other(code.to_string()),
+        }
+    }
+
+    /// The unmodified base. None: unknown.
+    pub fn canonical_base(&self) -> Option<u8> {
+        match self {
+            ModificationType::FiveMC
+            | ModificationType::FiveHMC
+            | ModificationType::FiveFC
+            | ModificationType::FiveCaC
+            | ModificationType::FourMC => Some(b'C'),
+            ModificationType::SixMA => Some(b'A'),
+            ModificationType::BrdU => Some(b'T'),
+            ModificationType::Other(_) => None,
+        }
+    }
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -19,37 +61,176 @@ pub struct BaseModification {
     pub modification_type: ModificationType,
     /// Probability from the ML tag: 0 = unmodified, 255 = fully modified.
     pub probability: u8,
+    /// The modification is on the strand opposite to the read (`-` in the MM tag, e.g. duplex calls).
+    pub opposite_strand: bool,
 }
 
 impl BaseModification {
-    /// Probability > 70% (probability >= 179/255).
-    pub fn is_high(&self) -> bool {
-        self.probability >= 179
+    pub fn is_high(&self, thresholds: &ModificationThresholds) -> bool {
+        self.probability >= thresholds.high
+    }
+
+    pub fn is_low(&self, thresholds: &ModificationThresholds) -> bool {
+        self.probability < thresholds.low
+    }
+}
+
+/// ML probabilities (0-255) separating low, ambiguous and high modification calls.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ModificationThresholds {
+    /// Calls below this probability are low (unmodified).
+    pub low: u8,
+
+    /// Calls at or above this probability are high (modified).
+    pub high: u8,
+}
+
+impl ModificationThresholds {
+    /// Thresholds from fractions in [0, 1], e.g. 0.3 and 0.7.
+    pub fn from_fractions(low: f64, high: f64) -> Result<Self, TGVError> {
+        if !(0.0..=1.0).contains(&low) || !(0.0..=1.0).contains(&high) || low > high {
+            return Err(TGVError::ValueError(format!(
+                "Modification thresholds must satisfy 0 <= low <= high <= 1, got {} and {}",
+                low, high
+            )));
+        }
+        Ok(Self {
+            low: (low * 255.0).round() as u8,
+            high: (high * 255.0).round() as u8,
+        })
+    }
+}
+
+impl Default for ModificationThresholds {
+    /// Low: < 30% (77/255). High: > 70% (179/255).
+    fn default() -> Self {
+        Self { low: 77, high: 179 }
+    }
+}
+
+/// Reference context of modification calls in IUPAC codes, e.g. CG, CHG, GATC.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ModificationMotif {
+    /// Uppercase IUPAC codes.
+    motif: Vec<u8>,
+}
+
+impl ModificationMotif {
+    pub fn new(motif: &str) -> Result<Self, TGVError> {
+        let motif = motif.to_ascii_uppercase().into_bytes();
+        if motif.is_empty() || !motif.iter().all(|code| IUPAC_CODES.contains(code)) {
+            return Err(TGVError::ValueError(format!(
+                "Invalid modification motif: {}. Use IUPAC codes, e.g. CG, CHG, GATC",
+                String::from_utf8_lossy(&motif)
+            )));
+        }
+        Ok(Self { motif })
+    }
+
+    /// Whether a call at position (1-based) is in the motif on either strand.
+    /// The modified base is the first occurrence of its unmodified base in the motif
+    /// (e.g. the A of GATC for 6mA), or of its complement in the reverse-complemented motif
+    /// for calls on the reverse strand. Calls of unknown modifications can be any motif base.
+    /// Calls without a loaded reference base (e.g. without a reference) are not filtered out.
+    pub fn matches(
+        &self,
+        position: u64,
+        modification_type: &ModificationType,
+        reference_sequence: &Sequence,
+    ) -> bool {
+        let Some(reference_base) = reference_sequence
+            .base_at(position)
+            .map(|base| base.to_ascii_uppercase())
+        else {
+            return true;
+        };
+
+        let reverse_motif: Vec<u8> = self
+            .motif
+            .iter()
+            .rev()
+            .map(|code| complement(*code))
+            .collect();
+        let canonical_base = modification_type.canonical_base();
+
+        [
+            (&self.motif, canonical_base),
+            (&reverse_motif, canonical_base.map(complement)),
+        ]
+        .into_iter()
+        .any(|(motif, modified_base)| {
+            let modified_base = modified_base.unwrap_or(reference_base);
+            if modified_base != reference_base {
+                return false;
+            }
+            let Some(offset) = motif.iter().position(|code| *code == modified_base) else {
+                return false;
+            };
+            let Some(start) = position.checked_sub(offset as u64) else {
+                return false;
+            };
+            motif.iter().enumerate().all(|(i, code)| {
+                reference_sequence
+                    .base_at(start + i as u64)
+                    .is_some_and(|base| iupac_matches(*code, base.to_ascii_uppercase()))
+            })
+        })
     }
+}
 
-    /// Probability < 30% (probability < 77/255).
-    pub fn is_low(&self) -> bool {
-        self.probability < 77
+impl std::fmt::Display for ModificationMotif {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", String::from_utf8_lossy(&self.motif))
     }
 }
 
+const IUPAC_CODES: &[u8] = b"ACGTRYSWKMBDHVN";
+
+/// Whether an IUPAC code matches an uppercase base.
+fn iupac_matches(code: u8, base: u8) -> bool {
+    let bases: &[u8] = match code {
+        b'R' => b"AG",
+        b'Y' => b"CT",
+        b'S' => b"CG",
+        b'W' => b"AT",
+        b'K' => b"GT",
+        b'M' => b"AC",
+        b'B' => b"CGT",
+        b'D' => b"AGT",
+        b'H' => b"ACT",
+        b'V' => b"ACG",
+        b'N' => b"ACGT",
+        _ => return code == base,
+    };
+    bases.contains(&base)
+}
+
 /// Parse MM and ML auxiliary tags from raw BAM record data bytes.
 ///
 /// Returns a map from 1-based reference positions to a list of base modifications
 /// at that position.
 ///
+/// MM positions count bases in the original read orientation: for reverse-complemented
+/// reads, they count the complement of the listed base from the end of SEQ. Hard clips
+/// are not part of SEQ and don't shift positions.
+///
+/// Skipped bases are unmodified for the `.` flag (also the default) and unknown for `?`.
+/// A malformed MM tag is an error, since the ML probabilities can no longer be matched.
+///
 /// # Arguments
 /// * `mm_str`   – The raw MM tag string value (e.g. `"C+m?,0,3,1;C+h?,2"`)
 /// * `ml_bytes` – Raw ML byte array (probabilities 0-255 in MM order)
 /// * `seq`      – Read sequence bases (uppercase, matching SAM query)
 /// * `cigar_ops` – Pre-parsed CIGAR operations (from noodles)
 /// * `alignment_start` – 1-based alignment start on the reference
+/// * `is_reverse` – Whether the read is reverse-complemented (FLAG 0x10)
 pub fn parse_modification_data(
     mm_str: &str,
     ml_bytes: &[u8],
     seq: &[u8],
     cigar_ops: &[noodles::sam::alignment::record::cigar::Op],
     alignment_start: u64,
+    is_reverse: bool,
 ) -> Result<HashMap<u64, Vec<BaseModification>>, TGVError> {
     use noodles::sam::alignment::record::cigar::op::Kind;
 
@@ -83,15 +264,21 @@ pub fn parse_modification_data(
         }
     }
 
-    // For each base type (A/C/G/T), collect query positions (0-based) in order.
+    // For each base type (A/C/G/T) of the original read, collect query positions (0-based)
+    // in the original read order.
     let mut base_positions: HashMap<u8, Vec<usize>> = HashMap::new();
-    for (i, &base) in seq.iter().enumerate() {
-        let upper = base.to_ascii_uppercase();
-        base_positions.entry(upper).or_default().push(i);
+    for i in 0..seq.len() {
+        let (query_pos, base) = if is_reverse {
+            let query_pos = seq.len() - 1 - i;
+            (query_pos, complement(seq[query_pos].to_ascii_uppercase()))
+        } else {
+            (i, seq[i].to_ascii_uppercase())
+        };
+        base_positions.entry(base).or_default().push(query_pos);
     }
 
     // Parse the MM tag.  Format (per SAM spec):
-    //   MM:Z:{base}{strand}{code}[.?],delta,delta,...[;...]
+    //   MM:Z:{base}{strand}{codes}[.?],delta,delta,...[;...]
     // Multiple modification types are separated by ';'.
     let mut ml_cursor: usize = 0;
 
@@ -108,89 +295,143 @@ pub fn parse_modification_data(
             (section, "")
         };
 
+        let invalid = || TGVError::ValueError(format!("Invalid MM tag section: {}", section));
+
         // Header must be at least "{base}{strand}{code}", e.g. "C+m" (3 bytes).
         let hdr = header.as_bytes();
-        if hdr.len() < 3 {
-            continue;
+        if hdr.len() < 3 || !header.is_ascii() {
+            return Err(invalid());
         }
 
-        let base = hdr[0].to_ascii_uppercase();
-        let strand = hdr[1];
+        // U is stored as T in SEQ.
+        let base = match hdr[0].to_ascii_uppercase() {
+            b'U' => b'T',
+            base @ (b'A' | b'C' | b'G' | b'T' | b'N') => base,
+            _ => return Err(invalid()),
+        };
+        let opposite_strand = match hdr[1] {
+            b'+' => false,
+            b'-' => true,
+            _ => return Err(invalid()),
+        };
 
-        // Only handle forward-strand modifications (+).
-        if strand != b'+' {
-            // Still advance ml_cursor past any probabilities for this section.
-            let n = if deltas_str.is_empty() {
-                0
-            } else {
-                deltas_str.split(',').count()
-            };
-            ml_cursor += n;
-            continue;
-        }
+        // `.`: skipped bases are unmodified (default). `?`: skipped bases are unknown.
+        let (codes, skipped_unmodified) = match header[2..].strip_suffix('?') {
+            Some(codes) => (codes, false),
+            None => (header[2..].strip_suffix('.').unwrap_or(&header[2..]), true),
+        };
 
-        // Single-letter modification code (or ChEBI ID – skip those for now).
-        let mod_code = hdr[2];
-        let mod_type = match mod_code {
-            b'm' => ModificationType::FiveMC,
-            b'h' => ModificationType::FiveHMC,
-            b'a' => ModificationType::SixMA,
-            _ => {
-                let n = if deltas_str.is_empty() {
-                    0
-                } else {
-                    deltas_str.split(',').count()
-                };
-                ml_cursor += n;
-                continue;
-            }
+        // Either one ChEBI ID or one or more single-letter codes, e.g. "mh".
+        let mod_types: Vec<ModificationType> = if codes.is_empty() {
+            return Err(invalid());
+        } else if codes.bytes().all(|b| b.is_ascii_digit()) {
+            vec![ModificationType::from_code(codes)]
+        } else if codes.bytes().all(|b| b.is_ascii_alphabetic()) {
+            codes
+                .chars()
+                .map(|code| ModificationType::from_code(&code.to_string()))
+                .collect()
+        } else {
+            return Err(invalid());
         };
 
-        let positions = match base_positions.get(&base) {
-            Some(p) => p,
-            None => continue,
+        let deltas = if deltas_str.is_empty() {
+            Vec::new()
+        } else {
+            deltas_str
+                .split(',')
+                .map(|delta| delta.trim().parse::<usize>().map_err(|_| invalid()))
+                .collect::<Result<Vec<_>, _>>()?
         };
 
-        if deltas_str.is_empty() {
-            continue;
-        }
+        // N: any base.
+        let all_positions: Vec<usize>;
+        let positions: &[usize] = if base == b'N' {
+            all_positions = (0..seq.len())
+                .map(|i| if is_reverse { seq.len() - 1 - i } else { i })
+                .collect();
+            &all_positions
+        } else {
+            base_positions.get(&base).map_or(&[], |p| p)
+        };
 
-        let mut pos_cursor: usize = 0;
+        let mut add = |query_pos: usize, mod_type: &ModificationType, probability: u8| {
+            if let Some(&ref_pos) = q_to_r.get(&query_pos) {
+                result.entry(ref_pos).or_default().push(BaseModification {
+                    modification_type: mod_type.clone(),
+                    probability,
+                    opposite_strand,
+                });
+            }
+        };
 
-        for delta_str in deltas_str.split(',') {
-            let delta: usize = match delta_str.trim().parse() {
-                Ok(d) => d,
-                Err(_) => break,
-            };
+        // ML holds one probability per listed base and code, codes interleaved.
+        let mut pos_cursor: usize = 0;
+        for (i_delta, delta) in deltas.iter().enumerate() {
+            let end = usize::min(pos_cursor + delta, positions.len());
+            if skipped_unmodified {
+                for &query_pos in &positions[pos_cursor..end] {
+                    for mod_type in &mod_types {
+                        add(query_pos, mod_type, 0);
+                    }
+                }
+            }
 
             pos_cursor += delta;
             if pos_cursor >= positions.len() {
-                ml_cursor += 1;
-                continue;
+                break;
             }
 
             let query_pos = positions[pos_cursor];
             pos_cursor += 1;
 
-            let probability = ml_bytes.get(ml_cursor).copied().unwrap_or(255);
-            ml_cursor += 1;
+            for (i_code, mod_type) in mod_types.iter().enumerate() {
+                let probability = ml_bytes
+                    .get(ml_cursor + i_delta * mod_types.len() + i_code)
+                    .copied()
+                    .unwrap_or(255);
+                add(query_pos, mod_type, probability);
+            }
+        }
 
-            if let Some(&ref_pos) = q_to_r.get(&query_pos) {
-                result.entry(ref_pos).or_default().push(BaseModification {
-                    modification_type: mod_type.clone(),
-                    probability,
-                });
+        if skipped_unmodified && pos_cursor < positions.len() {
+            for &query_pos in &positions[pos_cursor..] {
+                for mod_type in &mod_types {
+                    add(query_pos, mod_type, 0);
+                }
             }
         }
+
+        ml_cursor += deltas.len() * mod_types.len();
     }
 
     Ok(result)
 }
 
+/// Complement of a base or an IUPAC code.
+pub(crate) fn complement(base: u8) -> u8 {
+    match base {
+        b'A' => b'T',
+        b'T' => b'A',
+        b'C' => b'G',
+        b'G' => b'C',
+        b'R' => b'Y',
+        b'Y' => b'R',
+        b'K' => b'M',
+        b'M' => b'K',
+        b'B' => b'V',
+        b'V' => b'B',
+        b'D' => b'H',
+        b'H' => b'D',
+        other => other,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use noodles::sam::alignment::record::cigar::{Op, op::Kind};
+    use rstest::rstest;
 
     fn make_match(len: usize) -> Op {
         Op::new(Kind::Match, len)
@@ -217,7 +458,7 @@ mod tests {
         let mm = "C+m?,0,1";
         let ml = vec![200u8, 50u8];
 
-        let result = parse_modification_data(mm, &ml, seq, &cigars, 1).unwrap();
+        let result = parse_modification_data(mm, &ml, seq, &cigars, 1, false).unwrap();
 
         // ref_pos 2 (query 1, 1st C): probability 200
         assert!(result.contains_key(&2));
@@ -243,7 +484,7 @@ mod tests {
         let mm = "C+m?,0";
         let ml = vec![255u8];
 
-        let result = parse_modification_data(mm, &ml, seq, &cigars, 1).unwrap();
+        let result = parse_modification_data(mm, &ml, seq, &cigars, 1, false).unwrap();
 
         // C positions in query: 3, 5, 7, 9 (0-based, including soft-clip)
         // delta 0 → take C[0] = query 3 → ref_pos = 1 + (3-2) = 2
@@ -260,7 +501,7 @@ mod tests {
         let mm = "C+h?,1";
         let ml = vec![180u8];
 
-        let result = parse_modification_data(mm, &ml, seq, &cigars, 1).unwrap();
+        let result = parse_modification_data(mm, &ml, seq, &cigars, 1, false).unwrap();
 
         // C positions: query 1, 3, 5, 7 → delta 1 skips 1, takes C[1]=query 3 → ref 4
         assert!(result.contains_key(&4));
@@ -269,29 +510,46 @@ mod tests {
         assert_eq!(result.len(), 1);
     }
 
-    /// Test that unknown modification codes are skipped without panic.
+    /// Test that unknown modification codes are kept as written.
     #[test]
-    fn test_unknown_mod_code_is_skipped() {
+    fn test_unknown_mod_code_is_kept() {
         let seq = b"ACGCACGC";
         let cigars = vec![make_match(8)];
         // 'z' is not a recognised mod code
         let mm = "C+z?,0";
         let ml = vec![200u8];
 
-        let result = parse_modification_data(mm, &ml, seq, &cigars, 1).unwrap();
-        assert!(result.is_empty());
+        let result = parse_modification_data(mm, &ml, seq, &cigars, 1, false).unwrap();
+        assert_eq!(
+            result[&2],
+            vec![BaseModification {
+                modification_type: ModificationType::Other("z".to_string()),
+                probability: 200,
+                opposite_strand: false,
+            }]
+        );
+        assert_eq!(result.len(), 1);
     }
 
-    /// Test that reverse-strand sections are skipped.
+    /// Test that opposite-strand (`-`) sections count the listed base on the read.
     #[test]
-    fn test_reverse_strand_skipped() {
+    fn test_opposite_strand() {
+        // Duplex 5mC on the other strand, at the G of each CpG.
         let seq = b"ACGCACGC";
         let cigars = vec![make_match(8)];
-        let mm = "C-m?,0";
+        let mm = "G-m?,1";
         let ml = vec![200u8];
 
-        let result = parse_modification_data(mm, &ml, seq, &cigars, 1).unwrap();
-        assert!(result.is_empty());
+        let result = parse_modification_data(mm, &ml, seq, &cigars, 1, false).unwrap();
+        assert_eq!(
+            result[&7],
+            vec![BaseModification {
+                modification_type: ModificationType::FiveMC,
+                probability: 200,
+                opposite_strand: true,
+            }]
+        );
+        assert_eq!(result.len(), 1);
     }
 
     /// Test empty MM string produces empty result without panic.
@@ -299,7 +557,7 @@ mod tests {
     fn test_empty_mm() {
         let seq = b"ACGT";
         let cigars = vec![make_match(4)];
-        let result = parse_modification_data("", &[], seq, &cigars, 1).unwrap();
+        let result = parse_modification_data("", &[], seq, &cigars, 1, false).unwrap();
         assert!(result.is_empty());
     }
 
@@ -316,7 +574,7 @@ mod tests {
         let mm = "C+m?,0,0";
         let ml = vec![200u8, 100u8];
 
-        let result = parse_modification_data(mm, &ml, seq, &cigars, 1).unwrap();
+        let result = parse_modification_data(mm, &ml, seq, &cigars, 1, false).unwrap();
 
         assert!(result.contains_key(&2), "expected ref pos 2 (1st C)");
         assert!(result.contains_key(&6), "expected ref pos 6 (2nd C, after deletion)");
@@ -324,4 +582,200 @@ mod tests {
         assert_eq!(result[&6][0].probability, 100);
         assert_eq!(result.len(), 2);
     }
+
+    /// Modified positions: (reference position, probability).
+    fn modified(result: &HashMap<u64, Vec<BaseModification>>) -> Vec<(u64, u8)> {
+        let mut modified: Vec<(u64, u8)> = result
+            .iter()
+            .flat_map(|(pos, mods)| mods.iter().map(|m| (*pos, m.probability)))
+            .collect();
+        modified.sort();
+        modified
+    }
+
+    /// hts-specs SAMtags example: the same read stored forward (top-fwd) and
+    /// reverse-complemented (top-rev) shares MM:Z:C+m,1,3,0 and ML:B:C,128,153,179.
+    #[rstest]
+    #[case(b"AGGATCTCTAGCGGATCGGCGGGGGATATGCCATAT", false, vec![(8, 128), (31, 153), (32, 179)])]
+    #[case(b"ATATGGCATATCCCCCGCCGATCCGCTAGAGATCCT", true, vec![(5, 179), (6, 153), (29, 128)])]
+    fn test_spec_top_strand(
+        #[case] seq: &[u8],
+        #[case] is_reverse: bool,
+        #[case] expected: Vec<(u64, u8)>,
+    ) {
+        let cigars = vec![make_match(36)];
+        let result =
+            parse_modification_data("C+m?,1,3,0;", &[128, 153, 179], seq, &cigars, 1, is_reverse)
+                .unwrap();
+        assert_eq!(modified(&result), expected);
+
+        // The modified bases are C on the read, so G on SEQ of the reverse-complemented read.
+        for (pos, _) in expected {
+            let base = seq[pos as usize - 1];
+            assert_eq!(base, if is_reverse { b'G' } else { b'C' });
+        }
+    }
+
+    /// `.` (and no flag): skipped bases are unmodified. `?`: skipped bases are unknown.
+    #[rstest]
+    #[case("C+m.,1", vec![(2, 0), (4, 200), (6, 0), (8, 0)])]
+    #[case("C+m,1", vec![(2, 0), (4, 200), (6, 0), (8, 0)])]
+    #[case("C+m?,1", vec![(4, 200)])]
+    fn test_skip_flags(#[case] mm: &str, #[case] expected: Vec<(u64, u8)>) {
+        let seq = b"ACGCACGC";
+        let cigars = vec![make_match(8)];
+        let result = parse_modification_data(mm, &[200], seq, &cigars, 1, false).unwrap();
+        assert_eq!(modified(&result), expected);
+    }
+
+    /// Combined codes interleave ML probabilities per base, and match separate sections.
+    #[test]
+    fn test_combined_codes() {
+        let seq = b"ACGCACGC";
+        let cigars = vec![make_match(8)];
+        let combined =
+            parse_modification_data("C+mh?,1,1", &[204, 89, 26, 130], seq, &cigars, 1, false)
+                .unwrap();
+        let separate = parse_modification_data(
+            "C+m?,1,1;C+h?,1,1",
+            &[204, 26, 89, 130],
+            seq,
+            &cigars,
+            1,
+            false,
+        )
+        .unwrap();
+        assert_eq!(combined, separate);
+        assert_eq!(
+            combined[&4],
+            vec![
+                BaseModification {
+                    modification_type: ModificationType::FiveMC,
+                    probability: 204,
+                    opposite_strand: false,
+                },
+                BaseModification {
+                    modification_type: ModificationType::FiveHMC,
+                    probability: 89,
+                    opposite_strand: false,
+                },
+            ]
+        );
+    }
+
+    #[rstest]
+    #[case("C+27551?,0", b"ACGT", ModificationType::FiveMC, 2)]
+    #[case("C+76792?,0", b"ACGT", ModificationType::FiveHMC, 2)]
+    #[case("C+f?,0", b"ACGT", ModificationType::FiveFC, 2)]
+    #[case("C+76793?,0", b"ACGT", ModificationType::FiveCaC, 2)]
+    #[case("C+21839?,0", b"ACGT", ModificationType::FourMC, 2)]
+    #[case("A+28871?,0", b"ACGT", ModificationType::SixMA, 1)]
+    #[case("T+472552?,0", b"ACGT", ModificationType::BrdU, 4)]
+    #[case("U+472552?,0", b"ACGT", ModificationType::BrdU, 4)]
+    #[case("N+n?,2", b"ACGT", ModificationType::Other("n".to_string()), 3)]
+    #[case("G+16450?,0", b"ACGT", ModificationType::Other("16450".to_string()), 3)]
+    fn test_modification_codes(
+        #[case] mm: &str,
+        #[case] seq: &[u8],
+        #[case] expected_type: ModificationType,
+        #[case] expected_pos: u64,
+    ) {
+        let cigars = vec![make_match(seq.len())];
+        let result = parse_modification_data(mm, &[255], seq, &cigars, 1, false).unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[&expected_pos][0].modification_type, expected_type);
+    }
+
+    /// Hard-clipped bases are not in SEQ and don't shift MM positions.
+    #[test]
+    fn test_with_hardclip() {
+        let seq = b"ACGCACGC";
+        let cigars = vec![
+            Op::new(Kind::HardClip, 5),
+            make_match(8),
+            Op::new(Kind::HardClip, 3),
+        ];
+        let result = parse_modification_data("C+m?,1", &[200], seq, &cigars, 10, false).unwrap();
+        assert_eq!(modified(&result), vec![(13, 200)]);
+    }
+
+    #[rstest]
+    #[case("C")]
+    #[case("C*m,0")]
+    #[case("X+m,0")]
+    #[case("C+,0")]
+    #[case("C+m?,x")]
+    fn test_invalid_mm(#[case] mm: &str) {
+        let seq = b"ACGCACGC";
+        let cigars = vec![make_match(8)];
+        assert!(parse_modification_data(mm, &[200], seq, &cigars, 1, false).is_err());
+    }
+
+    #[rstest]
+    // CpGs at 2 and 6. The G of a CpG is the C on the reverse strand.
+    #[case("CG", ModificationType::FiveMC, vec![2, 3, 6, 7])]
+    #[case("cg", ModificationType::FiveMC, vec![2, 3, 6, 7])]
+    // CHG: CAG at 9 and 14. Their Gs are the C of CTG on the reverse strand.
+    #[case("CHG", ModificationType::FiveMC, vec![9, 11, 14, 16])]
+    // CHH: CCA at 13, and CAC on the reverse strand (18 to 16).
+    #[case("CHH", ModificationType::FiveMC, vec![13, 18])]
+    // 6mA at the A of GATC (18-21), on both strands.
+    #[case("GATC", ModificationType::SixMA, vec![19, 20])]
+    // Unknown modifications: any base in the motif.
+    #[case("GATC", ModificationType::Other("x".to_string()), vec![18, 19, 20, 21])]
+    fn test_motif_matches(
+        #[case] motif: &str,
+        #[case] modification_type: ModificationType,
+        #[case] expected: Vec<u64>,
+    ) {
+        let reference = Sequence {
+            start: 1,
+            sequence: b"ACGTACGTCAGTCCAGTGATCT".to_vec(),
+            contig_index: 0,
+        };
+        let motif = ModificationMotif::new(motif).unwrap();
+        assert_eq!(
+            (1..=reference.sequence.len() as u64)
+                .filter(|position| motif.matches(*position, &modification_type, &reference))
+                .collect::<Vec<_>>(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_motif_matches_without_reference() {
+        let motif = ModificationMotif::new("CG").unwrap();
+        assert!(motif.matches(2, &ModificationType::FiveMC, &Sequence::default()));
+        assert!(motif.matches(2, &ModificationType::SixMA, &Sequence::default()));
+    }
+
+    #[rstest]
+    #[case("CG", true)]
+    #[case("GATC", true)]
+    #[case("CHG", true)]
+    #[case("", false)]
+    #[case("CXG", false)]
+    fn test_motif_new(#[case] motif: &str, #[case] valid: bool) {
+        assert_eq!(ModificationMotif::new(motif).is_ok(), valid);
+    }
+
+    #[rstest]
+    #[case(0.3, 0.7, Ok(ModificationThresholds { low: 77, high: 179 }))]
+    #[case(0.5, 0.5, Ok(ModificationThresholds { low: 128, high: 128 }))]
+    #[case(0.7, 0.3, Err(()))]
+    #[case(0.3, 1.5, Err(()))]
+    fn test_thresholds_from_fractions(
+        #[case] low: f64,
+        #[case] high: f64,
+        #[case] expected: Result<ModificationThresholds, ()>,
+    ) {
+        assert_eq!(
+            ModificationThresholds::from_fractions(low, high).map_err(|_| ()),
+            expected
+        );
+        assert_eq!(
+            ModificationThresholds::default(),
+            ModificationThresholds::from_fractions(0.3, 0.7).unwrap()
+        );
+    }
 }
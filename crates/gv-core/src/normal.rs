@@ -97,6 +97,15 @@ fn parse_input(input: String) -> Result<Vec<Message>, TGVError> {
             n_movements,
         ))]),
         "E" => Ok(vec![Message::from(Movement::NextGenesEnd(n_movements))]),
+        "]" => Ok(vec![Message::from(Movement::NextFeature(n_movements))]),
+        "J" => Ok(vec![Message::from(Movement::NextJunction(n_movements))]),
+        "gJ" => Ok(vec![Message::from(Movement::PreviousJunction(n_movements))]),
+        "[" => Ok(vec![Message::from(Movement::PreviousFeature(n_movements))]),
+        "S" => Ok(vec![Message::from(Movement::SplitReadSegment(n_movements))]),
+        "a" => Ok(vec![Message::from(Movement::NextAltAllele(n_movements))]),
+        "ga" => Ok(vec![Message::from(Movement::PreviousAltAllele(
+            n_movements,
+        ))]),
         "h" => Ok(vec![Message::from(Movement::Left(
             n_movements as u64 * SMALL_HORIZONTAL_STEP,
         ))]),
@@ -164,6 +173,14 @@ mod tests {
     #[case("g", 'E', Ok(vec![Movement::PreviousGenesEnd(1).into()]))]
     #[case("3", 'w', Ok(vec![Movement::NextExonsStart(3).into()]))]
     #[case("5", 'l', Ok(vec![Movement::Right(5).into()]))]
+    #[case("", ']', Ok(vec![Movement::NextFeature(1).into()]))]
+    #[case("2", '[', Ok(vec![Movement::PreviousFeature(2).into()]))]
+    #[case("", 'J', Ok(vec![Movement::NextJunction(1).into()]))]
+    #[case("3g", 'J', Ok(vec![Movement::PreviousJunction(3).into()]))]
+    #[case("", 'S', Ok(vec![Movement::SplitReadSegment(1).into()]))]
+    #[case("2", 'S', Ok(vec![Movement::SplitReadSegment(2).into()]))]
+    #[case("", 'a', Ok(vec![Movement::NextAltAllele(1).into()]))]
+    #[case("4g", 'a', Ok(vec![Movement::PreviousAltAllele(4).into()]))]
     #[case("10", 'z', Ok(vec![Zoom::In(20).into()]))]
     #[case("", 'x', Err(TGVError::RegisterError("Invalid normal mode input: x".to_string())))]
     #[case("g", 'x', Err(TGVError::RegisterError("Invalid normal mode input: gx".to_string())))]
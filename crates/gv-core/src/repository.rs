@@ -6,6 +6,7 @@ use crate::{
     reference::Reference,
     sequence::SequenceRepositoryEnum,
     settings::Settings,
+    signal::SignalRepository,
     tracks::{TrackService, TrackServiceEnum},
     variant::VariantRepository,
 };
@@ -14,12 +15,16 @@ use itertools::Itertools;
 use std::path::Path;
 
 pub struct Repository {
-    pub alignment_repository: Option<AlignmentRepositoryEnum>,
+    /// One repository per alignment panel, in the order of `Settings::bam_paths`.
+    pub alignment_repositories: Vec<AlignmentRepositoryEnum>,
 
     pub variant_repository: Option<VariantRepository>,
 
     pub bed_repository: Option<BEDRepository>,
 
+    /// One repository per signal track, in the order of `Settings::signal_paths`.
+    pub signal_repositories: Vec<SignalRepository>,
+
     pub track_service: Option<TrackServiceEnum>,
 
     pub sequence_service: Option<SequenceRepositoryEnum>,
@@ -29,11 +34,6 @@ impl Repository {
     pub async fn new(settings: &Settings) -> Result<(Self, ContigHeader), TGVError> {
         let mut track_service = TrackServiceEnum::new(settings).await?;
         let mut sequence_service = SequenceRepositoryEnum::new(settings)?;
-        let alignment_repository = if let Some((bam_path, bai_path)) = settings.bam_path.as_ref() {
-            Some(AlignmentRepositoryEnum::new(bam_path, bai_path, settings.min_mapq).await?)
-        } else {
-            None
-        };
 
         // Contig header collect contigs from multiple sources.
         // - If the reference is a ucsc genome: ucsc database (local, mariadb, or api)
@@ -112,9 +112,25 @@ impl Repository {
             _ => {}
         }
 
+        // Created after the sequence repository is set up, which CRAM files are decoded against.
+        let mut alignment_repositories = Vec::new();
+        for (bam_path, bai_path) in settings.bam_paths.iter() {
+            alignment_repositories.push(
+                AlignmentRepositoryEnum::new(
+                    bam_path,
+                    bai_path,
+                    settings.min_mapq,
+                    settings.min_base_quality,
+                    settings.downsampling,
+                    sequence_service.as_ref(),
+                )
+                .await?,
+            );
+        }
+
         // FIXME
         // Warning when the reference contig is not present in the BAM header.
-        if let Some(bam) = alignment_repository.as_ref() {
+        for bam in alignment_repositories.iter() {
             bam.read_header()?.into_iter().for_each(|(name, length)| {
                 contig_header.update_or_add_contig(
                     name,
@@ -125,20 +141,46 @@ impl Repository {
             })
         }
 
+        // Annotation contigs are matched to the reference and alignment contigs by name and alias.
+        if let Some(TrackServiceEnum::Annotation(annotation_service)) = track_service.as_mut() {
+            annotation_service
+                .get_all_contigs(&settings.reference)
+                .await?
+                .into_iter()
+                .for_each(|contig| {
+                    contig_header.update_or_add_contig(
+                        contig.name,
+                        contig.length,
+                        contig.aliases,
+                        ContigSource::Track,
+                    );
+                });
+        }
+
         let variant_repository = settings
             .vcf_path
             .as_ref()
-            .map(|vcf_path| VariantRepository {
-                vcf_path: vcf_path.clone(),
-            });
+            .map(|vcf_path| VariantRepository::new(vcf_path))
+            .transpose()?;
+
+        let bed_repository = settings
+            .bed_path
+            .as_ref()
+            .map(|bed_path| BEDRepository::new(bed_path))
+            .transpose()?;
+
+        let signal_repositories = settings
+            .signal_paths
+            .iter()
+            .map(|signal_path| SignalRepository::new(signal_path))
+            .collect::<Result<Vec<_>, _>>()?;
 
         Ok((
             Self {
-                alignment_repository,
+                alignment_repositories,
                 variant_repository,
-                bed_repository: settings.bed_path.as_ref().map(|bed_path| BEDRepository {
-                    bed_path: bed_path.clone(),
-                }),
+                bed_repository,
+                signal_repositories,
                 track_service,
                 sequence_service,
             },
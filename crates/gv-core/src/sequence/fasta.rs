@@ -5,6 +5,7 @@ use crate::{
     sequence::Sequence,
 };
 use noodles::fasta::{
+    self,
     fai::Index,
     io::{
         BufReader,
@@ -15,6 +16,8 @@ use noodles::{core::region::Region as noodlesRegion, vcf::header::record::value:
 use std::str::FromStr;
 
 pub struct IndexedFastaSequenceRepository {
+    path: String,
+
     index: Index,
 
     reader: IndexedReader<BufReader<std::fs::File>>,
@@ -22,9 +25,21 @@ pub struct IndexedFastaSequenceRepository {
 
 impl IndexedFastaSequenceRepository {
     pub fn new(path: String) -> Result<Self, TGVError> {
-        let reader = Builder::default().build_from_path(path)?;
+        let reader = Builder::default().build_from_path(&path)?;
         let index = reader.index().clone();
-        Ok(Self { index, reader })
+        Ok(Self {
+            path,
+            index,
+            reader,
+        })
+    }
+
+    /// Reads through a separate handle on the indexed FASTA, so CRAM decoding doesn't move the viewer's reader.
+    pub fn fasta_repository(&self) -> Result<fasta::Repository, TGVError> {
+        let reader = Builder::default().build_from_path(&self.path)?;
+        Ok(fasta::Repository::new(
+            fasta::repository::adapters::IndexedReader::new(reader),
+        ))
     }
 }
 
@@ -144,4 +144,14 @@ impl SequenceRepositoryEnum {
             Self::IndexedFasta(repo) => repo.get_all_contigs().await,
         }
     }
+
+    /// Whole-contig sequence lookup for decoding CRAM files.
+    /// None for the UCSC API, which only serves sequences by region.
+    pub fn fasta_repository(&self) -> Result<Option<noodles::fasta::Repository>, TGVError> {
+        match self {
+            Self::UCSCApi(_) => Ok(None),
+            Self::TwoBit(repo) => repo.fasta_repository().map(Some),
+            Self::IndexedFasta(repo) => repo.fasta_repository().map(Some),
+        }
+    }
 }
@@ -3,7 +3,9 @@ use crate::error::TGVError;
 use crate::intervals::{GenomeInterval, Region};
 use crate::reference::Reference;
 use crate::sequence::Sequence;
+use noodles::fasta;
 use std::collections::HashMap;
+use std::io;
 use twobit::TwoBitFile;
 
 /// Repository for reading sequences from 2bit files
@@ -17,6 +19,9 @@ pub struct TwoBitSequenceRepository {
 
     /// 2bit file buffers.
     buffers: Vec<TwoBitFile<std::io::BufReader<std::fs::File>>>,
+
+    /// 2bit file paths. Same order as buffers.
+    paths: Vec<String>,
 }
 impl TwoBitSequenceRepository {
     pub fn new(reference: &Reference) -> Self {
@@ -24,9 +29,25 @@ impl TwoBitSequenceRepository {
             reference: reference.clone(),
             contig_to_buffer_index: HashMap::new(),
             buffers: Vec::new(),
+            paths: Vec::new(),
         }
     }
 
+    /// Opens all loaded 2bit files again; each contig is looked up in the file that contains it.
+    pub fn fasta_repository(&self) -> Result<fasta::Repository, TGVError> {
+        let buffers = self
+            .paths
+            .iter()
+            .map(|path| {
+                twobit::TwoBitFile::open(path).map_err(|e| {
+                    TGVError::IOError(format!("Failed to open 2bit file {}: {}", path, e))
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(fasta::Repository::new(TwoBitAdapter { buffers }))
+    }
+
     pub fn add_contig_path(
         &mut self,
         path: &str,
@@ -52,6 +73,7 @@ impl TwoBitSequenceRepository {
             });
 
         self.buffers.push(tb);
+        self.paths.push(path.to_string());
 
         Ok(())
     }
@@ -104,3 +126,42 @@ impl TwoBitSequenceRepository {
         todo!()
     }
 }
+
+/// Reads whole contigs from 2bit files. Used as the reference for CRAM decoding.
+struct TwoBitAdapter {
+    buffers: Vec<TwoBitFile<std::io::BufReader<std::fs::File>>>,
+}
+
+impl fasta::repository::Adapter for TwoBitAdapter {
+    fn get(&mut self, name: &[u8]) -> Option<io::Result<fasta::Record>> {
+        let name = String::from_utf8_lossy(name).to_string();
+
+        // Contig names in the alignment file may differ from the 2bit file by the "chr" prefix.
+        let alias = match name.strip_prefix("chr") {
+            Some(stripped) => stripped.to_string(),
+            None => format!("chr{}", name),
+        };
+
+        for contig_name in [&name, &alias] {
+            for buffer in self.buffers.iter_mut() {
+                if !buffer.chrom_names().contains(contig_name) {
+                    continue;
+                }
+
+                return Some(
+                    buffer
+                        .read_sequence(contig_name, ..)
+                        .map(|sequence| {
+                            fasta::Record::new(
+                                fasta::record::Definition::new(name.clone(), None),
+                                fasta::record::Sequence::from(sequence.into_bytes()),
+                            )
+                        })
+                        .map_err(io::Error::other),
+                );
+            }
+        }
+
+        None
+    }
+}
@@ -1,3 +1,4 @@
+use crate::alignment::{BaseCoverage, Downsampling, ModificationThresholds};
 use crate::reference::Reference;
 use crate::tracks::UcscHost;
 use clap::ValueEnum;
@@ -20,12 +21,18 @@ impl Default for BackendType {
     }
 }
 
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Settings {
-    /// bam path, bai path
-    pub bam_path: Option<(String, String)>,
+    /// (bam path, bai path) of each alignment panel, top to bottom.
+    pub bam_paths: Vec<(String, String)>,
     pub vcf_path: Option<String>,
     pub bed_path: Option<String>,
+
+    /// bigWig / bedGraph files. Each file is displayed as a signal track, top to bottom.
+    pub signal_paths: Vec<String>,
+
+    /// GTF / GFF3 gene annotation. Replaces UCSC gene models as the track service.
+    pub annotation_path: Option<String>,
     pub reference: Reference,
     pub backend: BackendType,
 
@@ -35,20 +42,49 @@ pub struct Settings {
 
     /// Minimum mapping quality. Reads with MAPQ below this value are skipped. 0 = no filter.
     pub min_mapq: u8,
+
+    /// Minimum base quality. Bases below this value are not counted in the coverage. 0 = no filter.
+    pub min_base_quality: u8,
+
+    /// Expected insert size range. Pairs outside are discordant.
+    /// None: estimated from the loaded pairs.
+    pub min_insert_size: Option<u64>,
+    pub max_insert_size: Option<u64>,
+
+    /// Keep at most this many reads starting in each window. None: keep all reads.
+    pub downsampling: Option<Downsampling>,
+
+    /// Coverage columns with at least this alt-allele frequency are highlighted.
+    pub allele_frequency_threshold: f64,
+
+    /// Weigh allele frequencies by base qualities.
+    pub quality_weighted_allele_frequency: bool,
+
+    /// ML probabilities separating low, ambiguous and high modification calls.
+    pub modification_thresholds: ModificationThresholds,
     //pub palette: Palette,
 }
 
 impl Default for Settings {
     fn default() -> Settings {
         Settings {
-            bam_path: None,
+            bam_paths: Vec::new(),
             vcf_path: None,
             bed_path: None,
+            signal_paths: Vec::new(),
+            annotation_path: None,
             reference: Reference::default(),
             backend: BackendType::default(), // Default backend
             ucsc_host: UcscHost::default(),
             cache_dir: shellexpand::tilde("~/.tgv").to_string(),
             min_mapq: 0,
+            min_base_quality: 0,
+            min_insert_size: None,
+            max_insert_size: None,
+            downsampling: None,
+            allele_frequency_threshold: BaseCoverage::DEFAULT_ALLELE_FREQUENCY_THRESHOLD,
+            quality_weighted_allele_frequency: false,
+            modification_thresholds: ModificationThresholds::default(),
         }
     }
 }
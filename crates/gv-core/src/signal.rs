@@ -0,0 +1,673 @@
+use crate::{
+    bed::TabixFile,
+    contig_header::ContigHeader,
+    error::TGVError,
+    intervals::{GenomeInterval, LoadedRegion, Region, SortedIntervalCollection},
+    modification::ModificationType,
+};
+use bigtools::{BigWigRead, utils::reopen::ReopenableFile};
+use noodles::bgzf;
+use std::{
+    fs::File,
+    io::{BufRead, BufReader},
+    path::Path,
+};
+use strum::Display;
+
+/// A span of a quantitative signal with a constant value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignalInterval {
+    contig_index: usize,
+
+    /// 1-based, inclusive.
+    start: u64,
+    /// 1-based, inclusive.
+    end: u64,
+
+    /// Signal value. For bigWig zoom levels: mean over the covered bases.
+    pub value: f64,
+}
+
+impl SignalInterval {
+    /// Parse a tab-separated bedGraph line. Returns None for comment, track and browser lines.
+    pub fn from_bedgraph_line(
+        line: &str,
+        contig_header: &ContigHeader,
+    ) -> Result<Option<Self>, TGVError> {
+        if line.trim().is_empty()
+            || line.starts_with('#')
+            || line.starts_with("track")
+            || line.starts_with("browser")
+        {
+            return Ok(None);
+        }
+
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 4 {
+            return Err(TGVError::ParsingError(format!(
+                "bedGraph line has fewer than 4 columns: {}",
+                line
+            )));
+        }
+
+        let value = fields[3]
+            .parse::<f64>()
+            .map_err(|_| TGVError::ParsingError(format!("Invalid bedGraph value: {}", line)))?;
+
+        // bedGraph is 0-based, half-open. Convert to 1-based, inclusive.
+        Ok(Some(Self {
+            contig_index: contig_header.try_get_index_by_str(fields[0])?,
+            start: fields[1].parse::<u64>()? + 1,
+            end: fields[2].parse::<u64>()?,
+            value,
+        }))
+    }
+
+    /// Parse a modkit bedMethyl line. The value is the percent modified (column 11).
+    /// Returns None for comment and track lines, and rows of modifications other than 5mC.
+    /// See: https://nanoporetech.github.io/modkit/intro_bedmethyl.html
+    pub fn from_bedmethyl_line(
+        line: &str,
+        contig_header: &ContigHeader,
+    ) -> Result<Option<Self>, TGVError> {
+        if line.trim().is_empty() || line.starts_with('#') || line.starts_with("track") {
+            return Ok(None);
+        }
+
+        // The first 9 columns are tab-separated, the rest are space-separated in older modkit versions.
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 11 {
+            return Err(TGVError::ParsingError(format!(
+                "bedMethyl line has fewer than 11 columns: {}",
+                line
+            )));
+        }
+
+        if ModificationType::from_code(fields[3]) != ModificationType::FiveMC {
+            return Ok(None);
+        }
+
+        let value = fields[10].parse::<f64>().map_err(|_| {
+            TGVError::ParsingError(format!("Invalid bedMethyl percent modified: {}", line))
+        })?;
+
+        // bedMethyl is 0-based, half-open. Convert to 1-based, inclusive.
+        Ok(Some(Self {
+            contig_index: contig_header.try_get_index_by_str(fields[0])?,
+            start: fields[1].parse::<u64>()? + 1,
+            end: fields[2].parse::<u64>()?,
+            value,
+        }))
+    }
+}
+
+impl GenomeInterval for SignalInterval {
+    fn contig_index(&self) -> usize {
+        self.contig_index
+    }
+
+    fn start(&self) -> u64 {
+        self.start
+    }
+
+    fn end(&self) -> u64 {
+        self.end
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignalFormat {
+    BigWig,
+    BedGraph,
+
+    /// modkit bedMethyl: percent 5mC of each site.
+    BedMethyl,
+}
+
+impl SignalFormat {
+    /// BED files are taken as bedMethyl, the default output of modkit pileup.
+    pub fn from_path(path: &str) -> Result<Self, TGVError> {
+        let lower = path.to_lowercase();
+        // bedGraph and bedMethyl files can be bgzipped.
+        let uncompressed = lower.strip_suffix(".gz").unwrap_or(&lower);
+        if lower.ends_with(".bw") || lower.ends_with(".bigwig") {
+            Ok(SignalFormat::BigWig)
+        } else if uncompressed.ends_with(".bedgraph")
+            || uncompressed.ends_with(".bdg")
+            || uncompressed.ends_with(".bg")
+        {
+            Ok(SignalFormat::BedGraph)
+        } else if uncompressed.ends_with(".bedmethyl") || uncompressed.ends_with(".bed") {
+            Ok(SignalFormat::BedMethyl)
+        } else {
+            Err(TGVError::ValueError(format!(
+                "Cannot infer the signal format of {}. Supported: .bw, .bigwig, .bedgraph, .bdg, .bg, .bedmethyl, .bed",
+                path
+            )))
+        }
+    }
+}
+
+/// How a signal track is drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Display)]
+pub enum SignalStyle {
+    /// Bar chart, like the coverage track.
+    #[default]
+    Bar,
+
+    /// One colored cell per column, from the low to the high end of the palette.
+    Heatmap,
+}
+
+/// Value range of a signal track.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum SignalScale {
+    /// From 0 to the highest value on screen.
+    #[default]
+    Auto,
+
+    /// From 0 to a fixed value. Higher values are clipped.
+    Fixed(f64),
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SignalDisplay {
+    pub style: SignalStyle,
+    pub scale: SignalScale,
+
+    /// Plot log10(1 + value).
+    pub log_scale: bool,
+}
+
+impl SignalDisplay {
+    /// Value as plotted. Negative values are drawn as 0.
+    pub fn transform(&self, value: f64) -> f64 {
+        let value = f64::max(value, 0.0);
+        if self.log_scale {
+            (1.0 + value).log10()
+        } else {
+            value
+        }
+    }
+}
+
+/// Data and display options of a signal track.
+#[derive(Debug, Clone, Default)]
+pub struct SignalTrack {
+    pub intervals: SortedIntervalCollection<SignalInterval>,
+    pub loaded_region: LoadedRegion,
+
+    /// bigWig zoom level of the loaded intervals. None: full resolution.
+    pub reduction_level: Option<u32>,
+
+    pub display: SignalDisplay,
+}
+
+impl SignalTrack {
+    /// Whether data overlapping region is loaded at this zoom level.
+    pub fn has_complete_data(&self, region: &Region, reduction_level: Option<u32>) -> bool {
+        self.loaded_region.has_complete_data(region)
+            && (self.loaded_region == LoadedRegion::All || self.reduction_level == reduction_level)
+    }
+
+    /// Mean value in each bin, weighted by the overlapping bases. None: no data in the bin.
+    /// bins: sorted (left, right), 1-based, inclusive.
+    pub fn binned_values(
+        &self,
+        contig_index: usize,
+        bins: &[(u64, u64)],
+    ) -> Result<Vec<Option<f64>>, TGVError> {
+        let (Some(first_bin), Some(last_bin)) = (bins.first(), bins.last()) else {
+            return Ok(Vec::new());
+        };
+        let intervals = self
+            .intervals
+            .overlapping(contig_index, first_bin.0, last_bin.1)?;
+
+        let mut first = 0;
+        Ok(bins
+            .iter()
+            .map(|(left, right)| {
+                while first < intervals.len() && intervals[first].end() < *left {
+                    first += 1;
+                }
+
+                let mut sum = 0.0;
+                let mut n_bases = 0;
+                for interval in intervals[first..]
+                    .iter()
+                    .take_while(|interval| interval.start() <= *right)
+                {
+                    let overlap = u64::min(interval.end(), *right)
+                        .saturating_sub(u64::max(interval.start(), *left))
+                        + 1;
+                    sum += interval.value * overlap as f64;
+                    n_bases += overlap;
+                }
+
+                if n_bases == 0 {
+                    None
+                } else {
+                    Some(sum / n_bases as f64)
+                }
+            })
+            .collect())
+    }
+}
+
+/// Reads bigWig files by region and zoom level, and bgzipped bedGraph and bedMethyl files with a
+/// .tbi index by region. Other bedGraph and bedMethyl files are read in full.
+pub struct SignalRepository {
+    pub path: String,
+
+    pub format: SignalFormat,
+
+    /// None: bedGraph or bedMethyl.
+    reader: Option<BigWigRead<ReopenableFile>>,
+
+    /// Indexed bedGraph or bedMethyl file.
+    tabix_file: Option<TabixFile>,
+}
+
+impl SignalRepository {
+    pub fn new(path: &str) -> Result<Self, TGVError> {
+        if !Path::new(path).exists() {
+            return Err(TGVError::IOError(format!("Signal file {} not found", path)));
+        }
+
+        let format = SignalFormat::from_path(path)?;
+        let (reader, tabix_file) = match format {
+            SignalFormat::BigWig => (
+                Some(BigWigRead::open_file(path).map_err(|e| {
+                    TGVError::IOError(format!("Failed to open bigWig file {}: {}", path, e))
+                })?),
+                None,
+            ),
+            SignalFormat::BedGraph => (None, TabixFile::open(path)?),
+            SignalFormat::BedMethyl => {
+                check_bedmethyl(path)?;
+                (None, TabixFile::open(path)?)
+            }
+        };
+
+        Ok(Self {
+            path: path.to_string(),
+            format,
+            reader,
+            tabix_file,
+        })
+    }
+
+    /// Whether intervals can be queried by region.
+    pub fn is_indexed(&self) -> bool {
+        self.reader.is_some() || self.tabix_file.is_some()
+    }
+
+    /// Coarsest bigWig zoom level that still has one record per `bases_per_column` bases.
+    /// None: use full-resolution data.
+    pub fn reduction_level(&self, bases_per_column: u64) -> Option<u32> {
+        self.reader.as_ref().and_then(|reader| {
+            reader
+                .info()
+                .zoom_headers
+                .iter()
+                .map(|zoom_header| zoom_header.reduction_level)
+                .filter(|reduction_level| *reduction_level as u64 <= bases_per_column)
+                .max()
+        })
+    }
+
+    /// Read signal overlapping region at a zoom level. Unindexed bedGraph and bedMethyl files return
+    /// all intervals.
+    pub fn read_signal(
+        &mut self,
+        region: &Region,
+        reduction_level: Option<u32>,
+        contig_header: &ContigHeader,
+    ) -> Result<SortedIntervalCollection<SignalInterval>, TGVError> {
+        let parse_line = self.line_parser();
+        if let Some(tabix_file) = self.tabix_file.as_mut() {
+            let Some(contig_name) = tabix_file
+                .contig_name(region.contig_index(), contig_header)
+                .map(|name| name.to_string())
+            else {
+                return SortedIntervalCollection::new(Vec::new());
+            };
+
+            let mut intervals = Vec::new();
+            for line in tabix_file.query(&contig_name, region.start(), region.end())? {
+                if let Some(interval) = parse_line(&line, contig_header)? {
+                    intervals.push(interval);
+                }
+            }
+            return SortedIntervalCollection::new(intervals);
+        }
+
+        let Some(reader) = self.reader.as_mut() else {
+            return self.read_all_intervals(contig_header);
+        };
+
+        let Some(chrom) = reader
+            .chroms()
+            .iter()
+            .find(|chrom| {
+                contig_header
+                    .try_get_index_by_str(&chrom.name)
+                    .is_ok_and(|index| index == region.contig_index())
+            })
+            .cloned()
+        else {
+            return SortedIntervalCollection::new(Vec::new());
+        };
+
+        // bigWig is 0-based, half-open.
+        let start = u32::min(region.start().saturating_sub(1) as u32, chrom.length);
+        let end = u32::min(
+            u32::try_from(region.end()).unwrap_or(u32::MAX),
+            chrom.length,
+        );
+        if start >= end {
+            return SortedIntervalCollection::new(Vec::new());
+        }
+
+        let mut intervals = Vec::new();
+        match reduction_level {
+            Some(reduction_level) => {
+                for record in reader
+                    .get_zoom_interval(&chrom.name, start, end, reduction_level)
+                    .map_err(|e| TGVError::IOError(e.to_string()))?
+                {
+                    let record = record?;
+                    if record.summary.bases_covered == 0 {
+                        continue;
+                    }
+                    intervals.push(SignalInterval {
+                        contig_index: region.contig_index(),
+                        start: record.start as u64 + 1,
+                        end: record.end as u64,
+                        value: record.summary.sum / record.summary.bases_covered as f64,
+                    });
+                }
+            }
+            None => {
+                for value in reader.get_interval(&chrom.name, start, end)? {
+                    let value = value?;
+                    intervals.push(SignalInterval {
+                        contig_index: region.contig_index(),
+                        start: value.start as u64 + 1,
+                        end: value.end as u64,
+                        value: value.value as f64,
+                    });
+                }
+            }
+        }
+
+        SortedIntervalCollection::new(intervals)
+    }
+
+    fn line_parser(&self) -> fn(&str, &ContigHeader) -> Result<Option<SignalInterval>, TGVError> {
+        match self.format {
+            SignalFormat::BedMethyl => SignalInterval::from_bedmethyl_line,
+            _ => SignalInterval::from_bedgraph_line,
+        }
+    }
+
+    fn read_all_intervals(
+        &self,
+        contig_header: &ContigHeader,
+    ) -> Result<SortedIntervalCollection<SignalInterval>, TGVError> {
+        let reader = text_reader(&self.path)?;
+        let parse_line = self.line_parser();
+        let mut intervals = Vec::new();
+        for line in reader.lines() {
+            if let Some(interval) = parse_line(&line?, contig_header)? {
+                intervals.push(interval);
+            }
+        }
+
+        SortedIntervalCollection::new(intervals)
+    }
+}
+
+/// Plain or bgzipped text file.
+fn text_reader(path: &str) -> Result<Box<dyn BufRead>, TGVError> {
+    Ok(if path.ends_with(".gz") {
+        Box::new(bgzf::io::Reader::new(File::open(path)?))
+    } else {
+        Box::new(BufReader::new(File::open(path)?))
+    })
+}
+
+/// Check that the first record looks like modkit output: a modification code in column 4, and
+/// the valid coverage and percent modified in columns 10 and 11. Other BED files would
+/// otherwise show as an empty track.
+fn check_bedmethyl(path: &str) -> Result<(), TGVError> {
+    for line in text_reader(path)?.lines() {
+        let line = line?;
+        if line.trim().is_empty() || line.starts_with('#') || line.starts_with("track") {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        // Single-letter codes (m, h, a) or ChEBI ids (27551).
+        let is_modification_code = |code: &str| {
+            (code.len() == 1 && code.bytes().all(|b| b.is_ascii_alphabetic()))
+                || (!code.is_empty() && code.bytes().all(|b| b.is_ascii_digit()))
+        };
+        return if fields.len() >= 11
+            && is_modification_code(fields[3])
+            && fields[9].parse::<u64>().is_ok()
+            && fields[10].parse::<f64>().is_ok()
+        {
+            Ok(())
+        } else {
+            Err(TGVError::CliError(format!(
+                "{} is not a modkit bedMethyl file. Other BED files can be shown with -b, or as signal tracks after conversion to bedGraph or bigWig.",
+                path
+            )))
+        };
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contig_header::ContigSource;
+    use crate::intervals::Focus;
+    use crate::reference::Reference;
+    use crate::test_utils::{test_data, write_bigwig, write_tabix_copy};
+    use rstest::rstest;
+
+    fn contig_header() -> ContigHeader {
+        let mut contig_header = ContigHeader::new(Reference::NoReference);
+        contig_header.update_or_add_contig(
+            "MN908947.3".to_string(),
+            Some(29903),
+            Vec::new(),
+            ContigSource::Sequence,
+        );
+        contig_header
+    }
+
+    fn region(start: u64, end: u64) -> Region {
+        Region {
+            focus: Focus {
+                contig_index: 0,
+                position: (start + end) / 2,
+            },
+            half_width: (end - start) / 2,
+        }
+    }
+
+    fn write_covid_bigwig(dir: &Path) -> String {
+        write_bigwig(
+            dir,
+            "covid.bedgraph",
+            &[("MN908947.3", 29903)],
+            vec![100, 1000],
+        )
+    }
+
+    #[rstest]
+    #[case("signal.bw", Ok(SignalFormat::BigWig))]
+    #[case("signal.bigWig", Ok(SignalFormat::BigWig))]
+    #[case("signal.bedGraph", Ok(SignalFormat::BedGraph))]
+    #[case("signal.bdg", Ok(SignalFormat::BedGraph))]
+    #[case("signal.bedgraph.gz", Ok(SignalFormat::BedGraph))]
+    #[case("calls.bedMethyl.gz", Ok(SignalFormat::BedMethyl))]
+    #[case("pileup.bed", Ok(SignalFormat::BedMethyl))]
+    #[case("pileup.bed.gz", Ok(SignalFormat::BedMethyl))]
+    #[case("signal.txt", Err(()))]
+    fn test_format_from_path(#[case] path: &str, #[case] expected: Result<SignalFormat, ()>) {
+        assert_eq!(SignalFormat::from_path(path).map_err(|_| ()), expected);
+    }
+
+    #[test]
+    fn test_read_bedgraph() {
+        let mut repository = SignalRepository::new(&test_data("covid.bedgraph")).unwrap();
+        assert!(!repository.is_indexed());
+        assert_eq!(repository.reduction_level(1000), None);
+
+        let signal = repository
+            .read_signal(&region(1, 100), None, &contig_header())
+            .unwrap();
+        assert_eq!(signal.intervals.len(), 5);
+        assert_eq!(signal.intervals[0].start(), 1);
+        assert_eq!(signal.intervals[0].end(), 100);
+        assert_eq!(signal.intervals[2].value, 10.25);
+    }
+
+    #[test]
+    fn test_read_bedmethyl() {
+        let mut repository = SignalRepository::new(&test_data("covid.bedmethyl")).unwrap();
+        assert!(!repository.is_indexed());
+
+        // 5hmC rows are skipped. ChEBI 27551 is 5mC.
+        let signal = repository
+            .read_signal(&region(1, 100), None, &contig_header())
+            .unwrap();
+        assert_eq!(
+            signal
+                .intervals
+                .iter()
+                .map(|interval| (interval.start(), interval.end(), interval.value))
+                .collect::<Vec<_>>(),
+            vec![
+                (44, 44, 80.0),
+                (45, 45, 75.0),
+                (173, 173, 12.5),
+                (336, 336, 0.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reject_plain_bed() {
+        let dir = tempfile::tempdir().unwrap();
+        for path in [
+            test_data("covid.bed"),
+            write_tabix_copy(dir.path(), "covid.bed", "peaks.bed.gz"),
+        ] {
+            assert!(matches!(
+                SignalRepository::new(&path),
+                Err(TGVError::CliError(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_read_indexed_bedmethyl() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_tabix_copy(dir.path(), "covid.bedmethyl", "pileup.bed.gz");
+        let mut repository = SignalRepository::new(&path).unwrap();
+        assert!(repository.is_indexed());
+
+        let signal = repository
+            .read_signal(&region(1, 100), None, &contig_header())
+            .unwrap();
+        assert_eq!(
+            signal
+                .intervals
+                .iter()
+                .map(|interval| (interval.start(), interval.end(), interval.value))
+                .collect::<Vec<_>>(),
+            vec![(44, 44, 80.0), (45, 45, 75.0)]
+        );
+    }
+
+    #[test]
+    fn test_read_bigwig() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut repository = SignalRepository::new(&write_covid_bigwig(dir.path())).unwrap();
+        assert!(repository.is_indexed());
+
+        // Full resolution: the same intervals as the bedGraph, limited to the region.
+        let signal = repository
+            .read_signal(&region(1001, 6001), None, &contig_header())
+            .unwrap();
+        assert_eq!(
+            signal
+                .intervals
+                .iter()
+                .map(|interval| (interval.start(), interval.end(), interval.value))
+                .collect::<Vec<_>>(),
+            vec![(5001, 5500, 2.0)]
+        );
+
+        // Zoom records summarize the covered bases.
+        let reduction_level = repository.reduction_level(500);
+        assert_eq!(reduction_level, Some(100));
+        let signal = repository
+            .read_signal(&region(1, 1001), reduction_level, &contig_header())
+            .unwrap();
+        assert!(signal.intervals.len() > 1);
+        let values = SignalTrack {
+            intervals: signal,
+            ..Default::default()
+        }
+        .binned_values(0, &[(1, 100), (301, 1000)])
+        .unwrap();
+        assert_eq!(values, vec![Some(1.5), Some(10.25)]);
+    }
+
+    #[rstest]
+    #[case(1, None)]
+    #[case(99, None)]
+    #[case(100, Some(100))]
+    #[case(999, Some(100))]
+    #[case(5000, Some(1000))]
+    fn test_reduction_level(#[case] bases_per_column: u64, #[case] expected: Option<u32>) {
+        let dir = tempfile::tempdir().unwrap();
+        let repository = SignalRepository::new(&write_covid_bigwig(dir.path())).unwrap();
+        assert_eq!(repository.reduction_level(bases_per_column), expected);
+    }
+
+    #[rstest]
+    #[case(vec![(1, 100)], vec![Some(1.5)])]
+    #[case(vec![(51, 150)], vec![Some(2.75)])]
+    #[case(vec![(1001, 5000), (5001, 5001)], vec![None, Some(2.0)])]
+    #[case(vec![(1001, 2000), (26001, 27000)], vec![None, None])]
+    fn test_binned_values(#[case] bins: Vec<(u64, u64)>, #[case] expected: Vec<Option<f64>>) {
+        let track = SignalTrack {
+            intervals: SignalRepository::new(&test_data("covid.bedgraph"))
+                .unwrap()
+                .read_signal(&region(1, 29903), None, &contig_header())
+                .unwrap(),
+            ..Default::default()
+        };
+        assert_eq!(track.binned_values(0, &bins).unwrap(), expected);
+    }
+
+    #[rstest]
+    #[case(false, 9.0, 9.0)]
+    #[case(true, 9.0, 1.0)]
+    #[case(true, -5.0, 0.0)]
+    fn test_transform(#[case] log_scale: bool, #[case] value: f64, #[case] expected: f64) {
+        let display = SignalDisplay {
+            log_scale,
+            ..Default::default()
+        };
+        assert_eq!(display.transform(value), expected);
+    }
+}
@@ -2,23 +2,27 @@ use crate::sequence::SequenceRepositoryEnum;
 use crate::tracks::{TrackService, TrackServiceEnum};
 use crate::variant::VariantRepository;
 use crate::{
-    alignment::{Alignment, AlignmentRepositoryEnum},
+    alignment::{
+        Alignment, AlignmentRepositoryEnum, BaseCoverage, ModificationMotif, ModificationThresholds,
+    },
     bed::{BEDInterval, BEDRepository},
     contig_header::ContigHeader,
     cytoband::Cytoband,
     error::TGVError,
     feature::Gene,
-    intervals::{Focus, GenomeInterval, Region, SortedIntervalCollection},
-    message::{AlignmentDisplayOption, AlignmentFilter, Movement},
+    intervals::{Focus, GenomeInterval, LoadedRegion, Region, SortedIntervalCollection},
+    message::{AlignmentDisplayOption, Movement, SignalDisplayOption},
     reference::Reference,
     //register::Registers,
     //rendering::{MainLayout, layout::resize_node},
     repository::Repository,
     sequence::Sequence,
+    signal::{SignalRepository, SignalTrack},
     track::Track,
     variant::Variant,
 };
 use itertools::Itertools;
+use noodles::vcf;
 
 /// Holds states of the application.
 pub struct State {
@@ -26,14 +30,38 @@ pub struct State {
 
     pub contig_header: ContigHeader,
     pub reference: Reference,
-    pub alignment: Alignment,
+    /// One alignment per alignment panel. Display options are shared by all panels.
+    pub alignments: Vec<Alignment>,
     pub alignment_options: Vec<AlignmentDisplayOption>,
 
     pub variants: SortedIntervalCollection<Variant>,
-    pub variant_loaded: bool, // Temporary hack before proper implemetation for the indexed VCF IO
+    /// Region covered by the loaded variants.
+    pub variant_loaded_region: LoadedRegion,
+    /// Header of the variant file. Needed to describe INFO and sample fields.
+    pub variant_header: Option<vcf::Header>,
+    /// Sample indexes in the variant file, in the displayed order.
+    pub variant_samples: Vec<usize>,
 
     pub bed_intervals: SortedIntervalCollection<BEDInterval>,
-    pub bed_loaded: bool, // Temporary hack before proper implemetation for large bed file io
+    pub bed_loaded_region: LoadedRegion,
+
+    /// One track per signal file.
+    pub signals: Vec<SignalTrack>,
+
+    /// Splice junctions with fewer supporting reads are hidden.
+    pub junction_min_count: usize,
+
+    /// Expected insert size range from the settings. None: estimated from the loaded pairs.
+    pub min_insert_size: Option<u64>,
+    pub max_insert_size: Option<u64>,
+
+    /// Coverage columns with at least this alt-allele frequency are highlighted.
+    pub allele_frequency_threshold: f64,
+    /// Weigh allele frequencies by base qualities.
+    pub quality_weighted_allele_frequency: bool,
+
+    /// ML probabilities separating low, ambiguous and high modification calls.
+    pub modification_thresholds: ModificationThresholds,
 
     pub track: Track<Gene>,
 
@@ -41,21 +69,35 @@ pub struct State {
 }
 
 impl State {
-    pub fn new(reference: Reference, contigs: ContigHeader) -> Result<Self, TGVError> {
+    pub fn new(
+        reference: Reference,
+        contigs: ContigHeader,
+        n_alignments: usize,
+        n_signals: usize,
+    ) -> Result<Self, TGVError> {
         Ok(Self {
             reference,
 
             // /settings: settings.clone(),
             messages: Vec::new(),
 
-            alignment: Alignment::default(),
+            alignments: (0..n_alignments).map(|_| Alignment::default()).collect(),
             alignment_options: Vec::new(),
             track: Track::<Gene>::default(),
             sequence: Sequence::default(),
             variants: SortedIntervalCollection::<Variant>::default(),
-            variant_loaded: false,
+            variant_loaded_region: LoadedRegion::default(),
+            variant_header: None,
+            variant_samples: Vec::new(),
             bed_intervals: SortedIntervalCollection::<BEDInterval>::default(),
-            bed_loaded: false,
+            bed_loaded_region: LoadedRegion::default(),
+            signals: (0..n_signals).map(|_| SignalTrack::default()).collect(),
+            junction_min_count: 1,
+            min_insert_size: None,
+            max_insert_size: None,
+            allele_frequency_threshold: BaseCoverage::DEFAULT_ALLELE_FREQUENCY_THRESHOLD,
+            quality_weighted_allele_frequency: false,
+            modification_thresholds: ModificationThresholds::default(),
             contig_header: contigs,
         })
     }
@@ -72,6 +114,34 @@ impl State {
             .map(|contig| contig.cytoband.as_ref())
     }
 
+    /// Reference context that base modification calls are filtered by, if any.
+    pub fn modification_motif(&self) -> Option<&ModificationMotif> {
+        self.alignment_options
+            .iter()
+            .rev()
+            .find_map(|option| match option {
+                AlignmentDisplayOption::ShowBaseModifications(motif) => Some(motif.as_ref()),
+                _ => None,
+            })
+            .flatten()
+    }
+
+    /// Expected insert size range of an alignment panel.
+    /// Bounds from the settings take precedence over the bounds estimated from the loaded pairs.
+    pub fn insert_size_bounds(&self, panel: usize) -> Option<(u64, u64)> {
+        let estimated = self
+            .alignments
+            .get(panel)
+            .and_then(|alignment| alignment.insert_size_bounds());
+        match (self.min_insert_size, self.max_insert_size) {
+            (None, None) => estimated,
+            (min, max) => {
+                let (lower, upper) = estimated.unwrap_or((0, u64::MAX));
+                Some((min.unwrap_or(lower), max.unwrap_or(upper)))
+            }
+        }
+    }
+
     /// Maximum length of the contig.
     pub fn contig_length(&self, focus: &Focus) -> Result<Option<u64>, TGVError> {
         self.contig_header
@@ -109,6 +179,12 @@ impl State {
                 self.previous_genes_start(focus, repository, n).await
             }
             Movement::PreviousGenesEnd(n) => self.previous_genes_end(focus, repository, n).await,
+            Movement::NextFeature(n) => self.feature_start(focus, repository, n, true),
+            Movement::PreviousFeature(n) => self.feature_start(focus, repository, n, false),
+            Movement::NextJunction(n) => self.junction_start(focus, n, true),
+            Movement::PreviousJunction(n) => self.junction_start(focus, n, false),
+            Movement::NextAltAllele(n) => self.alt_allele(focus, n, true),
+            Movement::PreviousAltAllele(n) => self.alt_allele(focus, n, false),
 
             Movement::NextContig(n) => Ok(self.next_contig(focus, n)),
             Movement::PreviousContig(n) => Ok(self.previous_contig(focus, n)),
@@ -119,28 +195,174 @@ impl State {
 
             Movement::Gene(name) => self.gene(repository, name.as_ref()).await,
 
+            Movement::BreakendMate => self.breakend_mate(focus),
+            Movement::SplitReadSegment(n) => self.split_read_segment(focus, n),
+
             Movement::Default => self.default_focus(repository).await,
         }
     }
 
+    /// Start of the nth other alignment (SA tag) of the topmost read at the focus that has one.
+    fn split_read_segment(&self, focus: Focus, n: usize) -> Result<Focus, TGVError> {
+        if n == 0 {
+            return Ok(focus);
+        }
+
+        let segment = self
+            .alignments
+            .iter()
+            .filter(|alignment| alignment.contig_index == focus.contig_index)
+            .flat_map(|alignment| {
+                alignment
+                    .ys_index
+                    .iter()
+                    .flatten()
+                    .map(|i_read| &alignment.reads[*i_read])
+            })
+            .filter(|read| read.full_read_covers(focus.position))
+            .find_map(|read| read.supplementary_alignments().into_iter().nth(n - 1))
+            .ok_or(TGVError::StateError(
+                "No split read (SA tag) at the current position".to_string(),
+            ))?;
+
+        Ok(Focus {
+            contig_index: self
+                .contig_header
+                .try_get_index_by_str(&segment.contig_name)?,
+            position: segment.start,
+        })
+    }
+
+    fn breakend_mate(&self, focus: Focus) -> Result<Focus, TGVError> {
+        let mate = self
+            .variants
+            .intervals
+            .iter()
+            .filter(|variant| variant.contig_index == focus.contig_index)
+            .filter_map(|variant| {
+                variant
+                    .mate
+                    .as_ref()
+                    .map(|mate| (variant.start().abs_diff(focus.position), mate))
+            })
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, mate)| mate)
+            .ok_or(TGVError::StateError(
+                "No breakend (BND) variant nearby".to_string(),
+            ))?;
+
+        Ok(Focus {
+            contig_index: self.contig_header.try_get_index_by_str(&mate.contig)?,
+            position: mate.position,
+        })
+    }
+
+    fn feature_start(
+        &self,
+        focus: Focus,
+        repository: &mut Repository,
+        n: usize,
+        after: bool,
+    ) -> Result<Focus, TGVError> {
+        if n == 0 {
+            return Ok(focus);
+        }
+
+        // Unindexed files are loaded in full.
+        if self.bed_loaded_region == LoadedRegion::All {
+            let starts = self
+                .bed_intervals
+                .intervals
+                .iter()
+                .filter(|interval| interval.contig_index() == focus.contig_index)
+                .map(|interval| interval.start());
+            let position = nth_position(&focus, starts, n, after)
+                .ok_or(TGVError::StateError("No BED features found".to_string()))?;
+            return Ok(focus.move_to(position));
+        }
+
+        let bed_repository = repository
+            .bed_repository
+            .as_mut()
+            .ok_or(TGVError::StateError("No BED file loaded".to_string()))?;
+
+        Ok(Focus {
+            contig_index: focus.contig_index,
+            position: bed_repository.feature_start_around(
+                focus.contig_index,
+                focus.position,
+                n,
+                after,
+                &self.contig_header,
+            )?,
+        })
+    }
+
+    /// Start of the nth distinct junction start after (or before) the focus, in all alignment panels.
+    fn junction_start(&self, focus: Focus, n: usize, after: bool) -> Result<Focus, TGVError> {
+        if n == 0 {
+            return Ok(focus);
+        }
+
+        let starts = self
+            .alignments
+            .iter()
+            .filter(|alignment| alignment.contig_index == focus.contig_index)
+            .flat_map(|alignment| alignment.junctions(self.junction_min_count))
+            .map(|junction| junction.start);
+        let position = nth_position(&focus, starts, n, after).ok_or(TGVError::StateError(
+            "No splice junction in the loaded reads".to_string(),
+        ))?;
+        Ok(focus.move_to(position))
+    }
+
+    /// nth position after / before the focus whose alt-allele frequency passes the threshold.
+    fn alt_allele(&self, focus: Focus, n: usize, after: bool) -> Result<Focus, TGVError> {
+        if n == 0 {
+            return Ok(focus);
+        }
+
+        let positions = self
+            .alignments
+            .iter()
+            .filter(|alignment| alignment.contig_index == focus.contig_index)
+            .flat_map(|alignment| {
+                alignment.alt_allele_positions(
+                    self.allele_frequency_threshold,
+                    self.quality_weighted_allele_frequency,
+                )
+            });
+        let position =
+            nth_position(&focus, positions, n, after).ok_or(TGVError::StateError(format!(
+                "No alt allele above {} frequency in the loaded reads",
+                self.allele_frequency_threshold
+            )))?;
+        Ok(focus.move_to(position))
+    }
+
     pub fn add_message(&mut self, message: String) {
         self.messages.push(message);
     }
 
+    /// Load the alignment of the `index`th panel.
     pub async fn load_alignment_data(
         &mut self,
         region: &Region,
+        index: usize,
         alignment_repository: &mut AlignmentRepositoryEnum,
     ) -> Result<&mut Self, TGVError> {
         // if !self.alignment.has_complete_data(&region) {
         //     Ok(false)
         // } else {
-        self.alignment = alignment_repository
+        let mut alignment = alignment_repository
             .read_alignment(&region, &self.sequence, &self.contig_header)
             .await?;
 
-        self.alignment
-            .apply_options(&self.alignment_options, &self.sequence)?;
+        alignment.apply_options(&self.alignment_options, &self.sequence)?;
+
+        *self.alignments.get_mut(index).ok_or_else(|| {
+            TGVError::StateError(format!("Alignment panel {} does not exist", index))
+        })? = alignment;
 
         Ok(self)
     }
@@ -174,8 +396,16 @@ impl State {
         region: &Region,
         variant_repository: &mut VariantRepository,
     ) -> Result<&mut Self, TGVError> {
-        self.variants = variant_repository.read_variants(&self.contig_header)?;
-        self.variant_loaded = true;
+        self.variants = variant_repository.read_variants(region, &self.contig_header)?;
+        if self.variant_header.is_none() {
+            self.variant_samples = (0..variant_repository.header.sample_names().len()).collect();
+            self.variant_header = Some(variant_repository.header.clone());
+        }
+        self.variant_loaded_region = if variant_repository.is_indexed() {
+            LoadedRegion::Region(region.clone())
+        } else {
+            LoadedRegion::All
+        };
         Ok(self)
     }
 
@@ -184,8 +414,36 @@ impl State {
         region: &Region,
         bed_repository: &mut BEDRepository,
     ) -> Result<&mut Self, TGVError> {
-        self.bed_intervals = bed_repository.read_bed(&self.contig_header)?;
-        self.bed_loaded = true;
+        self.bed_intervals = bed_repository.read_bed(region, &self.contig_header)?;
+        self.bed_loaded_region = if bed_repository.is_indexed() {
+            LoadedRegion::Region(region.clone())
+        } else {
+            LoadedRegion::All
+        };
+        Ok(self)
+    }
+
+    /// Load the signal of the `index`th track at a bigWig zoom level.
+    pub async fn load_signal_data(
+        &mut self,
+        region: &Region,
+        index: usize,
+        reduction_level: Option<u32>,
+        signal_repository: &mut SignalRepository,
+    ) -> Result<&mut Self, TGVError> {
+        let intervals =
+            signal_repository.read_signal(region, reduction_level, &self.contig_header)?;
+        let signal = self.signals.get_mut(index).ok_or_else(|| {
+            TGVError::StateError(format!("Signal track {} does not exist", index))
+        })?;
+
+        signal.intervals = intervals;
+        signal.reduction_level = reduction_level;
+        signal.loaded_region = if signal_repository.is_indexed() {
+            LoadedRegion::Region(region.clone())
+        } else {
+            LoadedRegion::All
+        };
         Ok(self)
     }
 
@@ -212,6 +470,98 @@ impl State {
     }
 }
 
+/// nth distinct position after (or before) the focus, or the farthest one if there are fewer.
+/// Positions are on the focus contig.
+fn nth_position(
+    focus: &Focus,
+    positions: impl Iterator<Item = u64>,
+    n: usize,
+    after: bool,
+) -> Option<u64> {
+    let mut positions: Vec<u64> = positions
+        .filter(|position| {
+            if after {
+                *position > focus.position
+            } else {
+                *position < focus.position
+            }
+        })
+        .collect();
+    positions.sort();
+    positions.dedup();
+    if !after {
+        positions.reverse();
+    }
+
+    positions.get(n - 1).or(positions.last()).copied()
+}
+
+impl State {
+    /// Show these samples in the genotype rows, in this order. Empty: all samples in the file order.
+    pub fn set_variant_samples(&mut self, samples: Vec<String>) -> Result<(), TGVError> {
+        let header = self
+            .variant_header
+            .as_ref()
+            .ok_or(TGVError::StateError("No VCF file is loaded".to_string()))?;
+        let sample_names = header.sample_names();
+
+        self.variant_samples = if samples.is_empty() {
+            (0..sample_names.len()).collect()
+        } else {
+            samples
+                .iter()
+                .map(|sample| {
+                    sample_names
+                        .get_index_of(sample)
+                        .ok_or(TGVError::ValueError(format!(
+                            "Sample {} not found in the VCF file",
+                            sample
+                        )))
+                })
+                .collect::<Result<Vec<usize>, _>>()?
+        };
+
+        Ok(())
+    }
+}
+
+impl State {
+    /// Change how signal tracks are drawn. index: None for all tracks.
+    pub fn set_signal_display(
+        &mut self,
+        index: Option<usize>,
+        options: Vec<SignalDisplayOption>,
+    ) -> Result<(), TGVError> {
+        let signals = match index {
+            Some(index) => {
+                let n_signals = self.signals.len();
+                std::slice::from_mut(self.signals.get_mut(index).ok_or_else(|| {
+                    TGVError::ValueError(format!(
+                        "Signal track {} does not exist ({} signal tracks are loaded)",
+                        index + 1,
+                        n_signals
+                    ))
+                })?)
+            }
+            None => self.signals.as_mut_slice(),
+        };
+
+        for signal in signals.iter_mut() {
+            for option in options.iter() {
+                match option {
+                    SignalDisplayOption::Style(style) => signal.display.style = *style,
+                    SignalDisplayOption::Scale(scale) => signal.display.scale = *scale,
+                    SignalDisplayOption::LogScale(log_scale) => {
+                        signal.display.log_scale = *log_scale
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
 impl State {
     /// Main function to route state message handling.
     pub fn set_alignment_change(
@@ -219,25 +569,32 @@ impl State {
         focus: &Focus,
         options: Vec<AlignmentDisplayOption>,
     ) -> Result<(), TGVError> {
-        self.alignment.reset(&self.sequence)?;
+        for alignment in self.alignments.iter_mut() {
+            alignment.reset(&self.sequence)?;
+        }
 
         let options = options
             .into_iter()
             .map(|option| match option {
-                AlignmentDisplayOption::Filter(AlignmentFilter::BaseAtCurrentPosition(base)) => {
-                    AlignmentDisplayOption::Filter(AlignmentFilter::Base(focus.position, base))
+                AlignmentDisplayOption::Filter(filter) => {
+                    AlignmentDisplayOption::Filter(filter.at_position(focus.position))
                 }
 
-                AlignmentDisplayOption::Filter(AlignmentFilter::BaseAtCurrentPositionSoftClip) => {
-                    AlignmentDisplayOption::Filter(AlignmentFilter::BaseSoftclip(focus.position))
+                AlignmentDisplayOption::Sort(sort) => {
+                    AlignmentDisplayOption::Sort(sort.at_position(focus.position))
+                }
+
+                AlignmentDisplayOption::GroupBy(group_by) => {
+                    AlignmentDisplayOption::GroupBy(group_by.at_position(focus.position))
                 }
 
                 _ => option,
             })
             .collect_vec();
         self.alignment_options = options;
-        self.alignment
-            .apply_options(&self.alignment_options, &self.sequence)?;
+        for alignment in self.alignments.iter_mut() {
+            alignment.apply_options(&self.alignment_options, &self.sequence)?;
+        }
 
         Ok(())
     }
@@ -605,3 +962,77 @@ impl State {
             })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contig_header::ContigSource;
+    use crate::test_utils::test_data;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(0, 1, 1, true, Ok(7668001))]
+    #[case(0, 7668001, 2, true, Ok(7700001))]
+    #[case(0, 7668001, 5, true, Ok(7700001))]
+    #[case(0, 7700001, 1, false, Ok(7668451))]
+    #[case(1, 88050, 0, true, Ok(88050))]
+    #[case(1, 88050, 1, false, Ok(88001))]
+    #[case(1, 100000, 1, true, Err(()))]
+    #[tokio::test]
+    async fn test_feature_movement_in_unindexed_bed(
+        #[case] contig_index: usize,
+        #[case] position: u64,
+        #[case] n: usize,
+        #[case] after: bool,
+        #[case] expected: Result<u64, ()>,
+    ) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("transcripts.bed");
+        std::fs::copy(test_data("transcripts.bed"), &path).unwrap();
+
+        let mut contig_header = ContigHeader::new(Reference::NoReference);
+        for (name, length) in [("chr17", 83257441), ("chr20", 64444167)] {
+            contig_header.update_or_add_contig(
+                name.to_string(),
+                Some(length),
+                Vec::new(),
+                ContigSource::Track,
+            );
+        }
+        let mut repository = Repository {
+            alignment_repositories: Vec::new(),
+            variant_repository: None,
+            bed_repository: Some(BEDRepository::new(path.to_str().unwrap()).unwrap()),
+            signal_repositories: Vec::new(),
+            track_service: None,
+            sequence_service: None,
+        };
+        let mut state = State::new(Reference::NoReference, contig_header, 0, 0).unwrap();
+        let focus = Focus {
+            contig_index,
+            position,
+        };
+        let region = Region {
+            focus: focus.clone(),
+            half_width: 100,
+        };
+        state
+            .load_bed_data(&region, repository.bed_repository.as_mut().unwrap())
+            .await
+            .unwrap();
+
+        // Motions search the loaded intervals instead of reading the file again.
+        std::fs::remove_file(&path).unwrap();
+        let movement = if after {
+            Movement::NextFeature(n)
+        } else {
+            Movement::PreviousFeature(n)
+        };
+        let position = state
+            .movement(focus, &mut repository, movement)
+            .await
+            .map(|focus| focus.position)
+            .map_err(|_| ());
+        assert_eq!(position, expected);
+    }
+}
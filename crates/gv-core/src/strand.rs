@@ -2,7 +2,7 @@ use strum::Display;
 
 use crate::error::TGVError;
 
-#[derive(Debug, Clone, PartialEq, Eq, Display)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Display)]
 pub enum Strand {
     #[strum[to_string = "+"]]
     Forward,
@@ -0,0 +1,154 @@
+//! Fixtures shared by unit tests.
+
+use crate::{alignment::AlignedRead, sequence::Sequence};
+use bigtools::{BigBedWrite, BigWigWrite, beddata::BedParserStreamingIterator};
+use noodles::sam::{
+    self,
+    alignment::{
+        RecordBuf,
+        io::Write as _,
+        record::data::field::Tag,
+        record_buf::{
+            Data,
+            data::field::{Value, value::Array},
+        },
+    },
+};
+use noodles::{bam, bgzf, tabix};
+use std::{collections::HashMap, fs::File, io::Write, path::Path};
+
+/// Path to a file in the test data directory of the tgv crate.
+pub(crate) fn test_data(name: &str) -> String {
+    format!("{}/../tgv/tests/data/{}", env!("CARGO_MANIFEST_DIR"), name)
+}
+
+/// Lines of a test data file, without its track line.
+pub(crate) fn data_lines(name: &str) -> Vec<String> {
+    std::fs::read_to_string(test_data(name))
+        .unwrap()
+        .lines()
+        .filter(|line| !line.starts_with("track"))
+        .map(|line| line.to_string())
+        .collect()
+}
+
+/// Copy a test data file into `dir` without its track line.
+fn copy_without_track_line(dir: &Path, name: &str) -> File {
+    let path = dir.join(name);
+    let contents: String = data_lines(name)
+        .into_iter()
+        .map(|line| format!("{}\n", line))
+        .collect();
+    std::fs::write(&path, contents).unwrap();
+    File::open(&path).unwrap()
+}
+
+/// Write a BED-like test data file (without its track line) to `dir` as a bgzipped file with a
+/// tabix index.
+pub(crate) fn write_tabix_copy(dir: &Path, name: &str, file_name: &str) -> String {
+    let path = dir.join(file_name);
+    let mut writer = bgzf::io::Writer::new(File::create(&path).unwrap());
+    for line in data_lines(name) {
+        writeln!(writer, "{}", line).unwrap();
+    }
+    writer.finish().unwrap();
+    let index = noodles::bed::fs::index(&path).unwrap();
+    tabix::fs::write(dir.join(format!("{}.tbi", file_name)), &index).unwrap();
+    path.to_str().unwrap().to_string()
+}
+
+fn chrom_map(chrom_sizes: &[(&str, u32)]) -> HashMap<String, u32> {
+    chrom_sizes
+        .iter()
+        .map(|(name, length)| (name.to_string(), *length))
+        .collect()
+}
+
+fn runtime() -> tokio::runtime::Runtime {
+    tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap()
+}
+
+/// Convert a bedGraph test file to a bigWig in `dir` with the given zoom levels.
+pub(crate) fn write_bigwig(
+    dir: &Path,
+    bedgraph: &str,
+    chrom_sizes: &[(&str, u32)],
+    zoom_sizes: Vec<u32>,
+) -> String {
+    let bedgraph = copy_without_track_line(dir, bedgraph);
+    let bigwig_path = dir.join("signal.bw");
+    let mut writer = BigWigWrite::create_file(&bigwig_path, chrom_map(chrom_sizes)).unwrap();
+    writer.options.manual_zoom_sizes = Some(zoom_sizes);
+    writer
+        .write(
+            BedParserStreamingIterator::from_bedgraph_file(bedgraph, false),
+            runtime(),
+        )
+        .unwrap();
+    bigwig_path.to_str().unwrap().to_string()
+}
+
+/// Convert a BED test file to a bigBed in `dir` described by `autosql`.
+pub(crate) fn write_bigbed(
+    dir: &Path,
+    bed: &str,
+    chrom_sizes: &[(&str, u32)],
+    autosql: &str,
+) -> String {
+    let bed = copy_without_track_line(dir, bed);
+    let bigbed_path = dir.join("features.bb");
+    let mut writer = BigBedWrite::create_file(&bigbed_path, chrom_map(chrom_sizes)).unwrap();
+    writer.autosql = Some(autosql.to_string());
+    writer
+        .write(
+            BedParserStreamingIterator::from_bed_file(bed, false),
+            runtime(),
+        )
+        .unwrap();
+    bigbed_path.to_str().unwrap().to_string()
+}
+
+/// Reference sequence starting at position 1 of the first contig.
+pub(crate) fn sequence(bases: &[u8]) -> Sequence {
+    Sequence {
+        start: 1,
+        sequence: bases.to_vec(),
+        contig_index: 0,
+    }
+}
+
+/// Encode a record as BAM and read it back, the way records come out of an alignment file.
+pub(crate) fn bam_record(header: &sam::Header, record_buf: &RecordBuf) -> bam::Record {
+    let mut writer = bam::io::Writer::from(Vec::new());
+    writer.write_alignment_record(header, record_buf).unwrap();
+    let src = writer.into_inner();
+    let mut reader = bam::io::Reader::from(&src[..]);
+    let mut record = bam::Record::default();
+    reader.read_record(&mut record).unwrap();
+    record
+}
+
+/// Read of a record, compared against `reference`.
+pub(crate) fn aligned_read(
+    index: usize,
+    record_buf: &RecordBuf,
+    reference: &Sequence,
+) -> AlignedRead {
+    let record = bam_record(&sam::Header::default(), record_buf);
+    AlignedRead::from_bam_record(index, record, reference).unwrap()
+}
+
+/// MM and ML tags of base modification calls.
+pub(crate) fn modification_data(mm: &str, ml: Vec<u8>) -> Data {
+    [
+        (Tag::BASE_MODIFICATIONS, Value::from(mm)),
+        (
+            Tag::BASE_MODIFICATION_PROBABILITIES,
+            Value::Array(Array::UInt8(ml)),
+        ),
+    ]
+    .into_iter()
+    .collect()
+}
@@ -452,6 +452,7 @@ mod tests {
                 transcription_end: 10,
                 cds_start: 2,
                 cds_end: 10,
+                cds_phase: 0,
                 exon_starts: vec![2, 8],
                 exon_ends: vec![5, 10],
                 has_exons: true,
@@ -465,6 +466,7 @@ mod tests {
                 transcription_end: 30,
                 cds_start: 25,
                 cds_end: 25,
+                cds_phase: 0,
                 exon_starts: vec![],
                 exon_ends: vec![],
                 has_exons: false,
@@ -478,6 +480,7 @@ mod tests {
                 transcription_end: 50,
                 cds_start: 45,
                 cds_end: 50,
+                cds_phase: 0,
                 exon_starts: vec![41],
                 exon_ends: vec![50],
                 has_exons: true,
@@ -0,0 +1,1022 @@
+use crate::tracks::TrackService;
+use crate::{
+    contig_header::{Contig, ContigHeader},
+    cytoband::Cytoband,
+    error::TGVError,
+    feature::{Gene, SubGeneFeature},
+    intervals::{GenomeInterval, Region},
+    reference::Reference,
+    strand::Strand,
+    track::Track,
+};
+use async_trait::async_trait;
+use noodles::{
+    bgzf,
+    core::Position,
+    csi::{self, BinningIndex},
+    tabix,
+};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+/// Transcripts tagged with these are preferred when a gene has multiple transcripts.
+const CANONICAL_TAGS: [&str; 3] = ["Ensembl_canonical", "MANE_Select", "RefSeq Select"];
+
+/// Windows for finding genes next to a position in indexed files. Doubled until enough genes are found.
+const INITIAL_SEARCH_WINDOW: u64 = 1_000_000;
+
+/// Largest coordinate a tabix index can address.
+const MAX_INDEXED_POSITION: u64 = (1 << 29) - 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnnotationFormat {
+    Gtf,
+    Gff3,
+}
+
+impl AnnotationFormat {
+    pub fn from_path(path: &str) -> Result<Self, TGVError> {
+        let path = path.to_lowercase();
+        let path = path.strip_suffix(".gz").unwrap_or(&path);
+        if path.ends_with(".gtf") {
+            Ok(Self::Gtf)
+        } else if path.ends_with(".gff") || path.ends_with(".gff3") {
+            Ok(Self::Gff3)
+        } else {
+            Err(TGVError::ValueError(format!(
+                "Annotation file {} must end with .gtf, .gff or .gff3 (optionally .gz)",
+                path
+            )))
+        }
+    }
+}
+
+/// A GTF / GFF3 line.
+/// Coordinates are 1-based, inclusive, same as in the file.
+#[derive(Debug, Clone)]
+struct AnnotationRecord {
+    contig: String,
+    feature_type: String,
+    start: u64,
+    end: u64,
+    strand: Option<Strand>,
+
+    /// Bases to skip from the 5′ end of a CDS record to reach the next codon. None if `.`.
+    phase: Option<u8>,
+
+    /// Attributes in file order. Keys can repeat (e.g. GTF tag).
+    attributes: Vec<(String, String)>,
+}
+
+impl AnnotationRecord {
+    fn parse(line: &str, format: AnnotationFormat) -> Result<Option<Self>, TGVError> {
+        if line.trim().is_empty() || line.starts_with('#') {
+            return Ok(None);
+        }
+
+        let fields: Vec<&str> = line.trim_end().split('\t').collect();
+        if fields.len() < 9 {
+            return Err(TGVError::ParsingError(format!(
+                "Annotation line has fewer than 9 columns: {}",
+                line
+            )));
+        }
+
+        let strand = match fields[6] {
+            "+" | "-" => Some(Strand::from_str(fields[6].to_string())?),
+            _ => None,
+        };
+
+        let attributes = fields[8]
+            .split(';')
+            .map(|attribute| attribute.trim())
+            .filter(|attribute| !attribute.is_empty())
+            .filter_map(|attribute| match format {
+                // gene_id "ENSG00000141510"
+                AnnotationFormat::Gtf => attribute.split_once(' ').map(|(key, value)| {
+                    (key.to_string(), value.trim().trim_matches('"').to_string())
+                }),
+                // ID=gene-TP53;Note=a%3Bb
+                AnnotationFormat::Gff3 => attribute
+                    .split_once('=')
+                    .map(|(key, value)| (key.to_string(), percent_decode(value))),
+            })
+            .collect();
+
+        Ok(Some(Self {
+            contig: fields[0].to_string(),
+            feature_type: fields[2].to_string(),
+            start: fields[3].parse::<u64>()?,
+            end: fields[4].parse::<u64>()?,
+            strand,
+            phase: fields[7].parse::<u8>().ok(),
+            attributes,
+        }))
+    }
+
+    fn attribute(&self, key: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn is_exon(&self) -> bool {
+        self.feature_type == "exon"
+    }
+
+    fn is_cds(&self) -> bool {
+        self.feature_type == "CDS"
+    }
+
+    /// Whether the record spans a whole gene or transcript (i.e. not a part of a transcript).
+    fn is_container(&self) -> bool {
+        !(self.is_exon()
+            || self.is_cds()
+            || self.feature_type.contains("UTR")
+            || self.feature_type.contains("codon"))
+    }
+
+    fn is_canonical(&self) -> bool {
+        self.attributes.iter().any(|(key, value)| {
+            key == "tag"
+                && value
+                    .split(',')
+                    .any(|tag| CANONICAL_TAGS.contains(&tag.trim()))
+        })
+    }
+}
+
+/// Decode GFF3 escapes (e.g. %3B for ';'). Malformed escapes are kept as-is.
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%'
+            && let Some(byte) = value
+                .get(i + 1..i + 3)
+                .filter(|hex| hex.bytes().all(|byte| byte.is_ascii_hexdigit()))
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        {
+            decoded.push(byte);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// A transcript assembled from exon and CDS records.
+/// Coordinates are 1-based, inclusive.
+#[derive(Debug, Clone)]
+struct Transcript {
+    id: String,
+    gene_id: String,
+    gene_name: String,
+    contig: String,
+    strand: Option<Strand>,
+    start: u64,
+    end: u64,
+    exons: Vec<(u64, u64)>,
+    cds: Option<(u64, u64)>,
+
+    /// Phase of the 5′-most CDS record.
+    cds_phase: u8,
+    canonical: bool,
+}
+
+impl Transcript {
+    fn exon_length(&self) -> u64 {
+        self.exons.iter().map(|(start, end)| end + 1 - start).sum()
+    }
+
+    fn overlaps(&self, start: u64, end: u64) -> bool {
+        self.start <= end && self.end >= start
+    }
+
+    fn to_gene(&self, contig_index: usize) -> Gene {
+        // Non-coding transcripts have an empty CDS after the transcript end, same as UCSC tables.
+        let (cds_start, cds_end) = self.cds.unwrap_or((self.end + 1, self.end));
+
+        Gene {
+            id: self.id.clone(),
+            name: self.gene_name.clone(),
+            strand: self.strand.clone().unwrap_or(Strand::Forward),
+            contig_index,
+            transcription_start: self.start,
+            transcription_end: self.end,
+            cds_start,
+            cds_end,
+            cds_phase: self.cds_phase,
+            exon_starts: self.exons.iter().map(|(start, _)| *start).collect(),
+            exon_ends: self.exons.iter().map(|(_, end)| *end).collect(),
+            has_exons: true,
+        }
+    }
+}
+
+/// Group exon and CDS records into transcripts, and keep one transcript per gene:
+/// the one tagged canonical, or the one with the longest exons.
+/// Output is sorted by contig and start.
+fn assemble_transcripts(records: &[AnnotationRecord], format: AnnotationFormat) -> Vec<Transcript> {
+    // GFF3: ID -> record, for resolving parents. GTF: transcript_id -> transcript line.
+    let records_by_id: HashMap<&str, &AnnotationRecord> = records
+        .iter()
+        .filter_map(|record| match format {
+            AnnotationFormat::Gtf if record.feature_type == "transcript" => {
+                record.attribute("transcript_id").map(|id| (id, record))
+            }
+            AnnotationFormat::Gtf => None,
+            AnnotationFormat::Gff3 => record.attribute("ID").map(|id| (id, record)),
+        })
+        .collect();
+
+    let mut transcripts: HashMap<String, Transcript> = HashMap::new();
+    for record in records.iter().filter(|r| r.is_exon() || r.is_cds()) {
+        let transcript_ids: Vec<&str> = match format {
+            AnnotationFormat::Gtf => record.attribute("transcript_id").into_iter().collect(),
+            AnnotationFormat::Gff3 => record
+                .attribute("Parent")
+                .map(|parents| parents.split(',').collect())
+                .unwrap_or_default(),
+        };
+
+        for transcript_id in transcript_ids {
+            let transcript = transcripts
+                .entry(transcript_id.to_string())
+                .or_insert_with(|| new_transcript(transcript_id, record, &records_by_id, format));
+
+            transcript.start = u64::min(transcript.start, record.start);
+            transcript.end = u64::max(transcript.end, record.end);
+            if record.is_exon() {
+                transcript.exons.push((record.start, record.end));
+            } else {
+                let five_prime = match (transcript.cds, &transcript.strand) {
+                    (None, _) => true,
+                    (Some((_, end)), Some(Strand::Reverse)) => record.end > end,
+                    (Some((start, _)), _) => record.start < start,
+                };
+                if five_prime {
+                    transcript.cds_phase = record.phase.unwrap_or(0);
+                }
+                transcript.cds = Some(match transcript.cds {
+                    Some((start, end)) => {
+                        (u64::min(start, record.start), u64::max(end, record.end))
+                    }
+                    None => (record.start, record.end),
+                });
+            }
+        }
+    }
+
+    // GTF transcript lines and GFF3 parents carry the full transcript span.
+    for transcript in transcripts.values_mut() {
+        if let Some(record) = records_by_id.get(transcript.id.as_str()) {
+            transcript.start = u64::min(transcript.start, record.start);
+            transcript.end = u64::max(transcript.end, record.end);
+            transcript.canonical |= record.is_canonical();
+        }
+
+        if transcript.exons.is_empty() {
+            // CDS-only annotations (e.g. prokaryotes): the CDS is the exon.
+            transcript.exons.extend(transcript.cds);
+        }
+        transcript.exons.sort();
+    }
+
+    let mut genes: HashMap<String, Transcript> = HashMap::new();
+    for transcript in transcripts.into_values() {
+        match genes.get(&transcript.gene_id) {
+            Some(selected)
+                if (selected.canonical, selected.exon_length(), &selected.id)
+                    >= (
+                        transcript.canonical,
+                        transcript.exon_length(),
+                        &transcript.id,
+                    ) => {}
+            _ => {
+                genes.insert(transcript.gene_id.clone(), transcript);
+            }
+        }
+    }
+
+    let mut transcripts: Vec<Transcript> = genes.into_values().collect();
+    transcripts.sort_by(|a, b| (&a.contig, a.start, a.end).cmp(&(&b.contig, b.start, b.end)));
+    transcripts
+}
+
+fn new_transcript(
+    transcript_id: &str,
+    record: &AnnotationRecord,
+    records_by_id: &HashMap<&str, &AnnotationRecord>,
+    format: AnnotationFormat,
+) -> Transcript {
+    let (gene_id, gene_name) = match format {
+        AnnotationFormat::Gtf => {
+            let gene_id = record.attribute("gene_id").unwrap_or(transcript_id);
+            (gene_id, record.attribute("gene_name").unwrap_or(gene_id))
+        }
+        AnnotationFormat::Gff3 => {
+            // exon -> transcript -> gene. The parent may also be the gene itself.
+            let transcript = records_by_id.get(transcript_id).copied();
+            let gene_id = transcript
+                .and_then(|transcript| transcript.attribute("Parent"))
+                .unwrap_or(transcript_id);
+            let gene_name = [records_by_id.get(gene_id).copied(), transcript]
+                .into_iter()
+                .flatten()
+                .find_map(|record| {
+                    record
+                        .attribute("Name")
+                        .or(record.attribute("gene_name"))
+                        .or(record.attribute("gene"))
+                })
+                .unwrap_or(gene_id);
+            (gene_id, gene_name)
+        }
+    };
+
+    Transcript {
+        id: transcript_id.to_string(),
+        gene_id: gene_id.to_string(),
+        gene_name: gene_name.to_string(),
+        contig: record.contig.clone(),
+        strand: record.strand.clone(),
+        start: record.start,
+        end: record.end,
+        exons: Vec::new(),
+        cds: None,
+        cds_phase: 0,
+        canonical: record.is_canonical(),
+    }
+}
+
+fn open_lines(path: &str) -> Result<Box<dyn BufRead>, TGVError> {
+    Ok(if path.ends_with(".gz") {
+        Box::new(bgzf::io::Reader::new(File::open(path)?))
+    } else {
+        Box::new(BufReader::new(File::open(path)?))
+    })
+}
+
+/// Gene models from a local GTF or GFF3 file.
+/// bgzipped files with a .tbi index are queried by region. Other files are read in full on start.
+pub struct AnnotationTrackService {
+    pub path: String,
+
+    format: AnnotationFormat,
+
+    /// Tabix reader. None: the file is read in full.
+    reader: Option<csi::io::IndexedReader<bgzf::io::Reader<File>, tabix::Index>>,
+
+    /// Unindexed files: contig name -> transcripts, sorted by start.
+    transcripts: HashMap<String, Vec<Transcript>>,
+
+    /// Indexed files: gene name -> (contig name, start, end).
+    /// Built by scanning the file on the first gene name query.
+    gene_locations: Option<HashMap<String, (String, u64, u64)>>,
+}
+
+impl fmt::Debug for AnnotationTrackService {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AnnotationTrackService")
+            .field("path", &self.path)
+            .field("format", &self.format)
+            .field("indexed", &self.reader.is_some())
+            .finish()
+    }
+}
+
+impl AnnotationTrackService {
+    pub fn new(path: &str) -> Result<Self, TGVError> {
+        if !Path::new(path).exists() {
+            return Err(TGVError::IOError(format!(
+                "Annotation file {} not found",
+                path
+            )));
+        }
+        let format = AnnotationFormat::from_path(path)?;
+
+        let mut service = Self {
+            path: path.to_string(),
+            format,
+            reader: None,
+            transcripts: HashMap::new(),
+            gene_locations: None,
+        };
+
+        if Path::new(&format!("{}.tbi", path)).exists() {
+            service.reader =
+                Some(tabix::io::indexed_reader::Builder::default().build_from_path(path)?);
+        } else {
+            for transcript in assemble_transcripts(&service.read_all_records()?, format) {
+                service
+                    .transcripts
+                    .entry(transcript.contig.clone())
+                    .or_default()
+                    .push(transcript);
+            }
+        }
+
+        Ok(service)
+    }
+
+    /// Whether genes can be queried by region.
+    pub fn is_indexed(&self) -> bool {
+        self.reader.is_some()
+    }
+
+    fn read_all_records(&self) -> Result<Vec<AnnotationRecord>, TGVError> {
+        let mut records = Vec::new();
+        for line in open_lines(&self.path)?.lines() {
+            let line = line?;
+            if line.starts_with("##FASTA") {
+                break; // GFF3 can end with sequences.
+            }
+            records.extend(AnnotationRecord::parse(&line, self.format)?);
+        }
+        Ok(records)
+    }
+
+    /// Records overlapping [start, end] in an indexed file.
+    fn query_records(
+        &mut self,
+        contig: &str,
+        start: u64,
+        end: u64,
+    ) -> Result<Vec<AnnotationRecord>, TGVError> {
+        let Some(reader) = self.reader.as_mut() else {
+            return Ok(Vec::new());
+        };
+        if !reader.index().header().is_some_and(|header| {
+            header
+                .reference_sequence_names()
+                .contains(contig.as_bytes())
+        }) {
+            return Ok(Vec::new());
+        }
+
+        let to_position = |position: u64| {
+            Position::try_from(position.clamp(1, MAX_INDEXED_POSITION) as usize)
+                .map_err(|_| TGVError::ValueError(format!("Invalid position {}", position)))
+        };
+        let region = noodles::core::Region::new(contig, to_position(start)?..=to_position(end)?);
+
+        let mut records = Vec::new();
+        for record in reader.query(&region)? {
+            records.extend(AnnotationRecord::parse(record?.as_ref(), self.format)?);
+        }
+        Ok(records)
+    }
+
+    /// Representative transcripts overlapping [start, end] on a contig (named as in the file).
+    fn transcripts_overlapping(
+        &mut self,
+        contig: &str,
+        start: u64,
+        end: u64,
+    ) -> Result<Vec<Transcript>, TGVError> {
+        if !self.is_indexed() {
+            return Ok(self
+                .transcripts
+                .get(contig)
+                .map(|transcripts| {
+                    transcripts
+                        .iter()
+                        .filter(|transcript| transcript.overlaps(start, end))
+                        .cloned()
+                        .collect()
+                })
+                .unwrap_or_default());
+        }
+
+        let mut records = self.query_records(contig, start, end)?;
+
+        // Genes crossing the region boundary: query again over the whole gene span.
+        let (span_start, span_end) = records
+            .iter()
+            .filter(|record| record.is_container())
+            .fold((start, end), |(s, e), record| {
+                (u64::min(s, record.start), u64::max(e, record.end))
+            });
+        if (span_start, span_end) != (start, end) {
+            records = self.query_records(contig, span_start, span_end)?;
+        }
+
+        Ok(assemble_transcripts(&records, self.format)
+            .into_iter()
+            .filter(|transcript| transcript.overlaps(start, end))
+            .collect())
+    }
+
+    /// Gene track around a position, with at least k genes on the searched side when available.
+    fn track_around(
+        &mut self,
+        contig_index: usize,
+        coord: u64,
+        k: usize,
+        after: bool,
+        contig_header: &ContigHeader,
+    ) -> Result<Track<Gene>, TGVError> {
+        if k == 0 {
+            return Err(TGVError::ValueError("k cannot be 0".to_string()));
+        }
+        let contig_name = track_name(contig_index, contig_header)?.to_string();
+        let limit = contig_header
+            .try_get(contig_index)?
+            .length
+            .unwrap_or(MAX_INDEXED_POSITION);
+
+        let mut window = INITIAL_SEARCH_WINDOW;
+        loop {
+            let (start, end) = if !self.is_indexed() {
+                (1, u64::MAX)
+            } else if after {
+                (coord, u64::min(coord.saturating_add(window), limit))
+            } else {
+                (coord.saturating_sub(window).max(1), coord)
+            };
+
+            let genes = self
+                .transcripts_overlapping(&contig_name, start, end)?
+                .iter()
+                .map(|transcript| transcript.to_gene(contig_index))
+                .collect::<Vec<Gene>>();
+            // Same as Track::get_k_genes_after / get_k_genes_before.
+            let n_genes = genes
+                .iter()
+                .filter(|gene| {
+                    if after {
+                        gene.transcription_start > coord
+                    } else {
+                        gene.transcription_end < coord
+                    }
+                })
+                .count();
+
+            let searched_to_contig_end =
+                !self.is_indexed() || (after && end >= limit) || (!after && start <= 1);
+            if n_genes >= k || searched_to_contig_end {
+                if genes.is_empty() {
+                    return Err(TGVError::IOError("No genes found".to_string()));
+                }
+                return Track::from_genes(genes, contig_index);
+            }
+            window *= 2;
+        }
+    }
+
+    /// Location of a gene by name: (contig name, start, end).
+    fn locate_gene(&mut self, gene_name: &str) -> Result<Option<(String, u64, u64)>, TGVError> {
+        if !self.is_indexed() {
+            return Ok(self
+                .transcripts
+                .values()
+                .flatten()
+                .find(|transcript| transcript.gene_name == gene_name)
+                .map(|transcript| (transcript.contig.clone(), transcript.start, transcript.end)));
+        }
+
+        if self.gene_locations.is_none() {
+            let locations = assemble_transcripts(&self.read_all_records()?, self.format)
+                .into_iter()
+                .map(|transcript| {
+                    (
+                        transcript.gene_name,
+                        (transcript.contig, transcript.start, transcript.end),
+                    )
+                })
+                .collect();
+            self.gene_locations = Some(locations);
+        }
+
+        Ok(self
+            .gene_locations
+            .as_ref()
+            .and_then(|locations| locations.get(gene_name))
+            .cloned())
+    }
+}
+
+fn track_name(contig_index: usize, contig_header: &ContigHeader) -> Result<&str, TGVError> {
+    contig_header
+        .try_get(contig_index)?
+        .get_track_name()
+        .ok_or(TGVError::StateError(format!(
+            "Contig {} (index = {}, aliases = {}) does not have track data.",
+            contig_header.contigs[contig_index].name,
+            contig_index,
+            contig_header.contigs[contig_index].aliases.join(",")
+        )))
+}
+
+#[async_trait]
+impl TrackService for AnnotationTrackService {
+    async fn close(&mut self) -> Result<(), TGVError> {
+        Ok(())
+    }
+
+    /// Contigs named in the annotation file. Lengths are unknown.
+    async fn get_all_contigs(&mut self, _reference: &Reference) -> Result<Vec<Contig>, TGVError> {
+        let names: Vec<String> = match self.reader.as_ref() {
+            Some(reader) => reader
+                .index()
+                .header()
+                .map(|header| {
+                    header
+                        .reference_sequence_names()
+                        .iter()
+                        .map(|name| name.to_string())
+                        .collect()
+                })
+                .unwrap_or_default(),
+            None => self.transcripts.keys().cloned().collect(),
+        };
+
+        Ok(Contig::contigs_sort(
+            names.iter().map(|name| Contig::new(name, None)).collect(),
+        ))
+    }
+
+    async fn get_cytoband(
+        &mut self,
+        _reference: &Reference,
+        _contig_index: usize,
+        _contig_header: &ContigHeader,
+    ) -> Result<Option<Cytoband>, TGVError> {
+        Ok(None)
+    }
+
+    async fn get_preferred_track_name(
+        &mut self,
+        _reference: &Reference,
+    ) -> Result<Option<String>, TGVError> {
+        Ok(Some(self.path.clone()))
+    }
+
+    async fn query_genes_overlapping(
+        &mut self,
+        _reference: &Reference,
+        region: &Region,
+        contig_header: &ContigHeader,
+    ) -> Result<Vec<Gene>, TGVError> {
+        let contig_name = match contig_header
+            .try_get(region.contig_index())?
+            .get_track_name()
+        {
+            Some(contig_name) => contig_name,
+            None => return Ok(Vec::new()), // Contig doesn't have track data
+        };
+
+        Ok(self
+            .transcripts_overlapping(contig_name, region.start(), region.end())?
+            .iter()
+            .map(|transcript| transcript.to_gene(region.contig_index()))
+            .collect())
+    }
+
+    async fn query_gene_covering(
+        &mut self,
+        _reference: &Reference,
+        contig_index: usize,
+        coord: u64,
+        contig_header: &ContigHeader,
+    ) -> Result<Option<Gene>, TGVError> {
+        let contig_name = track_name(contig_index, contig_header)?;
+        Ok(self
+            .transcripts_overlapping(contig_name, coord, coord)?
+            .first()
+            .map(|transcript| transcript.to_gene(contig_index)))
+    }
+
+    async fn query_gene_name(
+        &mut self,
+        _reference: &Reference,
+        gene_name: &str,
+        contig_header: &ContigHeader,
+    ) -> Result<Gene, TGVError> {
+        let (contig_name, start, end) =
+            self.locate_gene(gene_name)?
+                .ok_or(TGVError::IOError(format!(
+                    "Failed to query gene: {}",
+                    gene_name
+                )))?;
+        let contig_index = contig_header.try_get_index_by_str(&contig_name)?;
+
+        self.transcripts_overlapping(&contig_name, start, end)?
+            .iter()
+            .find(|transcript| transcript.gene_name == gene_name)
+            .map(|transcript| transcript.to_gene(contig_index))
+            .ok_or(TGVError::IOError(format!(
+                "Failed to query gene: {}",
+                gene_name
+            )))
+    }
+
+    async fn query_k_genes_after(
+        &mut self,
+        _reference: &Reference,
+        contig_index: usize,
+        coord: u64,
+        k: usize,
+        contig_header: &ContigHeader,
+    ) -> Result<Gene, TGVError> {
+        self.track_around(contig_index, coord, k, true, contig_header)?
+            .get_saturating_k_genes_after(coord, k)
+            .cloned()
+            .ok_or(TGVError::IOError("No genes found".to_string()))
+    }
+
+    async fn query_k_genes_before(
+        &mut self,
+        _reference: &Reference,
+        contig_index: usize,
+        coord: u64,
+        k: usize,
+        contig_header: &ContigHeader,
+    ) -> Result<Gene, TGVError> {
+        self.track_around(contig_index, coord, k, false, contig_header)?
+            .get_saturating_k_genes_before(coord, k)
+            .cloned()
+            .ok_or(TGVError::IOError("No genes found".to_string()))
+    }
+
+    async fn query_k_exons_after(
+        &mut self,
+        _reference: &Reference,
+        contig_index: usize,
+        coord: u64,
+        k: usize,
+        contig_header: &ContigHeader,
+    ) -> Result<SubGeneFeature, TGVError> {
+        self.track_around(contig_index, coord, k, true, contig_header)?
+            .get_saturating_k_exons_after(coord, k)
+            .ok_or(TGVError::IOError("No exons found".to_string()))
+    }
+
+    async fn query_k_exons_before(
+        &mut self,
+        _reference: &Reference,
+        contig_index: usize,
+        coord: u64,
+        k: usize,
+        contig_header: &ContigHeader,
+    ) -> Result<SubGeneFeature, TGVError> {
+        self.track_around(contig_index, coord, k, false, contig_header)?
+            .get_saturating_k_exons_before(coord, k)
+            .ok_or(TGVError::IOError("No exons found".to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contig_header::ContigSource;
+    use crate::intervals::Focus;
+    use crate::test_utils::test_data;
+    use noodles::csi::binning_index::index::reference_sequence::bin::Chunk;
+    use rstest::rstest;
+    use std::io::Write;
+
+    fn contig_header() -> ContigHeader {
+        let mut contig_header = ContigHeader::new(Reference::NoReference);
+        contig_header.update_or_add_contig(
+            "MN908947.3".to_string(),
+            Some(29903),
+            Vec::new(),
+            ContigSource::Track,
+        );
+        contig_header
+    }
+
+    /// Write a test annotation file as a bgzipped file with a tabix index.
+    fn write_indexed_copy(dir: &Path, name: &str) -> String {
+        let path = dir.join(format!("{}.gz", name));
+        let mut writer = bgzf::io::Writer::new(File::create(&path).unwrap());
+        let mut indexer = tabix::index::Indexer::default();
+        indexer.set_header(csi::binning_index::index::header::Builder::gff().build());
+
+        for line in std::fs::read_to_string(test_data(name)).unwrap().lines() {
+            let start_position = writer.virtual_position();
+            writeln!(writer, "{}", line).unwrap();
+            if line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<&str> = line.split('\t').collect();
+            indexer
+                .add_record(
+                    fields[0],
+                    fields[3].parse().unwrap(),
+                    fields[4].parse().unwrap(),
+                    Chunk::new(start_position, writer.virtual_position()),
+                )
+                .unwrap();
+        }
+        writer.finish().unwrap();
+        tabix::fs::write(dir.join(format!("{}.gz.tbi", name)), &indexer.build()).unwrap();
+
+        path.to_str().unwrap().to_string()
+    }
+
+    /// The same annotation as GTF, GFF3, and their indexed copies.
+    fn services(dir: &Path) -> Vec<AnnotationTrackService> {
+        ["covid.gtf", "covid.gff3"]
+            .into_iter()
+            .flat_map(|name| [test_data(name), write_indexed_copy(dir, name)])
+            .map(|path| AnnotationTrackService::new(&path).unwrap())
+            .collect()
+    }
+
+    #[rstest]
+    #[case(
+        r#"gene_id "g1"; tag "basic"; tag "Ensembl_canonical";"#,
+        AnnotationFormat::Gtf,
+        vec![("gene_id", "g1"), ("tag", "basic"), ("tag", "Ensembl_canonical")]
+    )]
+    #[case("ID=gene-1;Name=ORF1ab", AnnotationFormat::Gff3, vec![("ID", "gene-1"), ("Name", "ORF1ab")])]
+    #[case(
+        "ID=gene-1;Note=a%3Bb%2Cc%3Dd;Alias=50%25%",
+        AnnotationFormat::Gff3,
+        vec![("ID", "gene-1"), ("Note", "a;b,c=d"), ("Alias", "50%%")]
+    )]
+    fn test_parse_attributes(
+        #[case] attributes: &str,
+        #[case] format: AnnotationFormat,
+        #[case] expected: Vec<(&str, &str)>,
+    ) {
+        let line = format!("chr1\tsource\tgene\t1\t10\t.\t+\t.\t{}", attributes);
+        let record = AnnotationRecord::parse(&line, format).unwrap().unwrap();
+        assert_eq!(
+            record.attributes,
+            expected
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[rstest]
+    // The phase of the 5′-most CDS record, regardless of record order.
+    #[case("+", 2)]
+    #[case("-", 1)]
+    fn test_cds_phase(#[case] strand: &str, #[case] expected: u8) {
+        let records = [(30, 40, 0), (10, 20, 2), (50, 60, 1)]
+            .map(|(start, end, phase)| {
+                let line = format!(
+                    "chr1\tsource\tCDS\t{}\t{}\t.\t{}\t{}\tParent=t1",
+                    start, end, strand, phase
+                );
+                AnnotationRecord::parse(&line, AnnotationFormat::Gff3)
+                    .unwrap()
+                    .unwrap()
+            })
+            .to_vec();
+        let transcripts = assemble_transcripts(&records, AnnotationFormat::Gff3);
+        assert_eq!(transcripts[0].to_gene(0).cds_phase, expected);
+    }
+
+    #[rstest]
+    #[case("genes.gtf", Ok(AnnotationFormat::Gtf))]
+    #[case("genes.GTF.gz", Ok(AnnotationFormat::Gtf))]
+    #[case("genes.gff3.gz", Ok(AnnotationFormat::Gff3))]
+    #[case("genes.gff", Ok(AnnotationFormat::Gff3))]
+    #[case("genes.bed", Err(()))]
+    fn test_format_from_path(#[case] path: &str, #[case] expected: Result<AnnotationFormat, ()>) {
+        assert_eq!(AnnotationFormat::from_path(path).map_err(|_| ()), expected);
+    }
+
+    #[rstest]
+    #[case(1, 30000, vec![("ORF1ab", 266, 21555), ("S", 21563, 25384), ("ORF10-AS", 29558, 29674)])]
+    #[case(21000, 21530, vec![("ORF1ab", 266, 21555)])]
+    #[case(25000, 25100, vec![("S", 21563, 25384)])]
+    #[case(26000, 27000, vec![])]
+    #[tokio::test]
+    async fn test_query_genes_overlapping(
+        #[case] start: u64,
+        #[case] end: u64,
+        #[case] expected: Vec<(&str, u64, u64)>,
+    ) {
+        let dir = tempfile::tempdir().unwrap();
+        let region = Region {
+            focus: Focus {
+                contig_index: 0,
+                position: (start + end) / 2,
+            },
+            half_width: (end - start) / 2,
+        };
+
+        for mut service in services(dir.path()) {
+            let genes = service
+                .query_genes_overlapping(&Reference::NoReference, &region, &contig_header())
+                .await
+                .unwrap();
+            let genes = genes
+                .iter()
+                .map(|gene| (gene.name.as_str(), gene.start(), gene.end()))
+                .collect::<Vec<_>>();
+            assert_eq!(genes, expected, "{}", service.path);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_gene_model() {
+        let dir = tempfile::tempdir().unwrap();
+
+        for mut service in services(dir.path()) {
+            let orf1ab = service
+                .query_gene_name(&Reference::NoReference, "ORF1ab", &contig_header())
+                .await
+                .unwrap();
+            assert_eq!(orf1ab.strand, Strand::Forward);
+            assert_eq!(orf1ab.exon_starts, vec![266, 13469]);
+            assert_eq!(orf1ab.exon_ends, vec![13468, 21555]);
+            assert_eq!((orf1ab.cds_start, orf1ab.cds_end), (266, 21552));
+
+            // The canonical transcript, not the longest one.
+            let s = service
+                .query_gene_name(&Reference::NoReference, "S", &contig_header())
+                .await
+                .unwrap();
+            assert_eq!(s.exon_starts, vec![21563], "{}", service.path);
+
+            // Non-coding: all exons are outside of the CDS.
+            let orf10_as = service
+                .query_gene_name(&Reference::NoReference, "ORF10-AS", &contig_header())
+                .await
+                .unwrap();
+            assert_eq!(orf10_as.strand, Strand::Reverse);
+            assert!(orf10_as.cds_start > orf10_as.cds_end);
+
+            assert!(
+                service
+                    .query_gene_name(&Reference::NoReference, "TP53", &contig_header())
+                    .await
+                    .is_err()
+            );
+        }
+    }
+
+    #[rstest]
+    #[case(100, 1, true, "ORF1ab")]
+    #[case(100, 2, true, "S")]
+    #[case(100, 5, true, "ORF10-AS")]
+    #[case(29000, 1, false, "S")]
+    #[case(29000, 2, false, "ORF1ab")]
+    #[tokio::test]
+    async fn test_gene_navigation(
+        #[case] coord: u64,
+        #[case] k: usize,
+        #[case] after: bool,
+        #[case] expected: &str,
+    ) {
+        let dir = tempfile::tempdir().unwrap();
+
+        for mut service in services(dir.path()) {
+            let gene = if after {
+                service
+                    .query_k_genes_after(&Reference::NoReference, 0, coord, k, &contig_header())
+                    .await
+            } else {
+                service
+                    .query_k_genes_before(&Reference::NoReference, 0, coord, k, &contig_header())
+                    .await
+            }
+            .unwrap();
+            assert_eq!(gene.name, expected, "{}", service.path);
+        }
+    }
+
+    #[rstest]
+    #[case(300, 1, true, (13469, 21555))]
+    #[case(300, 2, true, (21563, 25384))]
+    #[case(25000, 1, false, (13469, 21555))]
+    #[tokio::test]
+    async fn test_exon_navigation(
+        #[case] coord: u64,
+        #[case] k: usize,
+        #[case] after: bool,
+        #[case] expected: (u64, u64),
+    ) {
+        let dir = tempfile::tempdir().unwrap();
+
+        for mut service in services(dir.path()) {
+            let exon = if after {
+                service
+                    .query_k_exons_after(&Reference::NoReference, 0, coord, k, &contig_header())
+                    .await
+            } else {
+                service
+                    .query_k_exons_before(&Reference::NoReference, 0, coord, k, &contig_header())
+                    .await
+            }
+            .unwrap();
+            assert_eq!((exon.start, exon.end), expected, "{}", service.path);
+        }
+    }
+}
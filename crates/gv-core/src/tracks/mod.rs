@@ -1,3 +1,4 @@
+mod annotation;
 mod downloader;
 mod local_db;
 pub mod schema;
@@ -18,6 +19,7 @@ use async_trait::async_trait;
 use chrono::Local;
 use std::collections::{HashMap, HashSet};
 
+pub use annotation::{AnnotationFormat, AnnotationTrackService};
 pub use downloader::UCSCDownloader;
 pub use local_db::LocalDbTrackService;
 pub use ucsc_api::UcscApiTrackService;
@@ -203,10 +205,17 @@ pub enum TrackServiceEnum {
     Api(UcscApiTrackService),
     Db(UcscDbTrackService),
     LocalDb(LocalDbTrackService),
+    Annotation(AnnotationTrackService),
 }
 
 impl TrackServiceEnum {
     pub async fn new(settings: &Settings) -> Result<Option<Self>, TGVError> {
+        if let Some(annotation_path) = &settings.annotation_path {
+            return Ok(Some(Self::Annotation(AnnotationTrackService::new(
+                annotation_path,
+            )?)));
+        }
+
         match (&settings.backend, &settings.reference) {
             (_, Reference::NoReference)
             | (_, Reference::BYOIndexedFasta(_))
@@ -266,6 +275,10 @@ impl TrackServiceEnum {
                     .get_contig_2bit_file_lookup(reference, contig_header)
                     .await
             }
+            TrackServiceEnum::Annotation(_) => Err(TGVError::IOError(
+                "get_contig_2bit_file_lookup is not supported for AnnotationTrackService"
+                    .to_string(),
+            )),
         }
     }
 }
@@ -278,6 +291,7 @@ impl TrackService for TrackServiceEnum {
             TrackServiceEnum::Api(service) => service.close().await,
             TrackServiceEnum::Db(service) => service.close().await,
             TrackServiceEnum::LocalDb(service) => service.close().await,
+            TrackServiceEnum::Annotation(service) => service.close().await,
         }
     }
 
@@ -286,6 +300,7 @@ impl TrackService for TrackServiceEnum {
             TrackServiceEnum::Api(service) => service.get_all_contigs(reference).await,
             TrackServiceEnum::Db(service) => service.get_all_contigs(reference).await,
             TrackServiceEnum::LocalDb(service) => service.get_all_contigs(reference).await,
+            TrackServiceEnum::Annotation(service) => service.get_all_contigs(reference).await,
         }
     }
 
@@ -311,6 +326,11 @@ impl TrackService for TrackServiceEnum {
                     .get_cytoband(reference, contig_index, contig_header)
                     .await
             }
+            TrackServiceEnum::Annotation(service) => {
+                service
+                    .get_cytoband(reference, contig_index, contig_header)
+                    .await
+            }
         }
     }
 
@@ -322,6 +342,9 @@ impl TrackService for TrackServiceEnum {
             TrackServiceEnum::Api(service) => service.get_preferred_track_name(reference).await,
             TrackServiceEnum::Db(service) => service.get_preferred_track_name(reference).await,
             TrackServiceEnum::LocalDb(service) => service.get_preferred_track_name(reference).await,
+            TrackServiceEnum::Annotation(service) => {
+                service.get_preferred_track_name(reference).await
+            }
         }
     }
 
@@ -347,6 +370,11 @@ impl TrackService for TrackServiceEnum {
                     .query_genes_overlapping(reference, region, contig_header)
                     .await
             }
+            TrackServiceEnum::Annotation(service) => {
+                service
+                    .query_genes_overlapping(reference, region, contig_header)
+                    .await
+            }
         }
     }
 
@@ -373,6 +401,11 @@ impl TrackService for TrackServiceEnum {
                     .query_gene_covering(reference, contig_index, coord, contig_header)
                     .await
             }
+            TrackServiceEnum::Annotation(service) => {
+                service
+                    .query_gene_covering(reference, contig_index, coord, contig_header)
+                    .await
+            }
         }
     }
 
@@ -398,6 +431,11 @@ impl TrackService for TrackServiceEnum {
                     .query_gene_name(reference, gene_name, contig_header)
                     .await
             }
+            TrackServiceEnum::Annotation(service) => {
+                service
+                    .query_gene_name(reference, gene_name, contig_header)
+                    .await
+            }
         }
     }
 
@@ -425,6 +463,11 @@ impl TrackService for TrackServiceEnum {
                     .query_k_genes_after(reference, contig_index, coord, k, contig_header)
                     .await
             }
+            TrackServiceEnum::Annotation(service) => {
+                service
+                    .query_k_genes_after(reference, contig_index, coord, k, contig_header)
+                    .await
+            }
         }
     }
 
@@ -452,6 +495,11 @@ impl TrackService for TrackServiceEnum {
                     .query_k_genes_before(reference, contig_index, coord, k, contig_header)
                     .await
             }
+            TrackServiceEnum::Annotation(service) => {
+                service
+                    .query_k_genes_before(reference, contig_index, coord, k, contig_header)
+                    .await
+            }
         }
     }
 
@@ -479,6 +527,11 @@ impl TrackService for TrackServiceEnum {
                     .query_k_exons_after(reference, contig_index, coord, k, contig_header)
                     .await
             }
+            TrackServiceEnum::Annotation(service) => {
+                service
+                    .query_k_exons_after(reference, contig_index, coord, k, contig_header)
+                    .await
+            }
         }
     }
 
@@ -506,6 +559,11 @@ impl TrackService for TrackServiceEnum {
                     .query_k_exons_before(reference, contig_index, coord, k, contig_header)
                     .await
             }
+            TrackServiceEnum::Annotation(service) => {
+                service
+                    .query_k_exons_before(reference, contig_index, coord, k, contig_header)
+                    .await
+            }
         }
     }
     // Default helper methods delegate
@@ -531,6 +589,11 @@ impl TrackService for TrackServiceEnum {
                     .query_gene_track(reference, region, contig_header)
                     .await
             }
+            TrackServiceEnum::Annotation(service) => {
+                service
+                    .query_gene_track(reference, region, contig_header)
+                    .await
+            }
         }
     }
 }
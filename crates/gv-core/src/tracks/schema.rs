@@ -8,7 +8,7 @@ use crate::{
     track::Track,
 };
 use serde::Deserialize;
-use sqlx::{FromRow, Row, mysql::MySqlRow, sqlite::SqliteRow};
+use sqlx::{ColumnIndex, Decode, FromRow, Row, Type, mysql::MySqlRow, sqlite::SqliteRow};
 use std::collections::HashMap;
 
 /// Deserialization target for a row in the gene table.
@@ -26,6 +26,21 @@ pub struct UcscGeneRow {
     pub name2: Option<String>,
     pub exonStarts: Vec<u8>,
     pub exonEnds: Vec<u8>,
+    /// Not in every gene table.
+    pub exonFrames: Option<Vec<u8>>,
+}
+
+/// A column that not every table has. None if the column is missing or NULL.
+fn optional_column<'r, R, T, I>(row: &'r R, index: I) -> sqlx::Result<Option<T>>
+where
+    R: Row,
+    I: ColumnIndex<R>,
+    T: Decode<'r, R::Database> + Type<R::Database>,
+{
+    match row.try_get::<Option<T>, I>(index) {
+        Err(sqlx::Error::ColumnNotFound(_)) => Ok(None),
+        result => result,
+    }
 }
 
 #[allow(non_snake_case)]
@@ -47,6 +62,7 @@ impl FromRow<'_, SqliteRow> for UcscGeneRow {
             name2: row.try_get("name2")?,
             exonStarts: row.try_get("exonStarts")?,
             exonEnds: row.try_get("exonEnds")?,
+            exonFrames: optional_column(row, "exonFrames")?,
         })
     }
 }
@@ -64,6 +80,7 @@ impl FromRow<'_, MySqlRow> for UcscGeneRow {
             name2: row.try_get("name2")?,
             exonStarts: row.try_get("exonStarts")?,
             exonEnds: row.try_get("exonEnds")?,
+            exonFrames: optional_column(row, "exonFrames")?,
         })
     }
 }
@@ -83,10 +100,13 @@ impl UcscGeneRow {
     pub fn to_gene(self, contig_header: &ContigHeader) -> Result<Gene, TGVError> {
         // USCS coordinates are 0-based, half-open
         // https://genome-blog.gi.ucsc.edu/blog/2016/12/12/the-ucsc-genome-browser-coordinate-counting-systems/
+        let strand = Strand::from_str(self.strand)?;
+        let exon_frames = String::from_utf8_lossy(self.exonFrames.as_deref().unwrap_or_default());
         Ok(Gene {
             id: self.name.clone(),
             name: self.name2.unwrap_or(self.name.clone()),
-            strand: Strand::from_str(self.strand)?,
+            cds_phase: cds_phase(&exon_frames, &strand),
+            strand,
             contig_index: contig_header.try_get_index_by_str(&self.chrom)?,
             transcription_start: self.txStart + 1,
             transcription_end: self.txEnd,
@@ -102,6 +122,19 @@ impl UcscGeneRow {
     }
 }
 
+/// GFF phase of a gene from UCSC exonFrames: the codon position (0, 1 or 2) of the first coding
+/// base of each exon in genome order, -1 for non-coding exons. 0 if unknown.
+fn cds_phase(exon_frames: &str, strand: &Strand) -> u8 {
+    let mut frames = exon_frames
+        .split(',')
+        .filter_map(|frame| frame.trim().parse::<u8>().ok());
+    let frame = match strand {
+        Strand::Forward => frames.next(),
+        Strand::Reverse => frames.next_back(),
+    };
+    frame.map_or(0, |frame| (3 - frame % 3) % 3)
+}
+
 impl Track<Gene> {
     pub fn from_gene_rows(
         gene_rows: Vec<UcscGeneRow>,
@@ -223,6 +256,8 @@ pub enum UcscGeneResponse {
         cdsEnd: u64,
         exonStarts: String,
         exonEnds: String,
+        #[serde(default)]
+        exonFrames: Option<String>,
     },
 
     GeneResponse2 {
@@ -280,19 +315,28 @@ impl UcscGeneResponse {
                 cdsEnd,
                 exonStarts,
                 exonEnds,
-            } => Ok(Gene {
-                id: name.clone(),
-                name: name2.unwrap_or(name.clone()),
-                strand: Strand::from_str(strand)?,
-                contig_index,
-                transcription_start: txStart,
-                transcription_end: txEnd,
-                cds_start: cdsStart,
-                cds_end: cdsEnd,
-                exon_starts: Self::parse_comma_separated_list(&exonStarts)?,
-                exon_ends: Self::parse_comma_separated_list(&exonEnds)?,
-                has_exons: true,
-            }),
+                exonFrames,
+            } => {
+                // Same 0-based, half-open coordinates as the gene tables.
+                let strand = Strand::from_str(strand)?;
+                Ok(Gene {
+                    id: name.clone(),
+                    name: name2.unwrap_or(name.clone()),
+                    cds_phase: cds_phase(exonFrames.as_deref().unwrap_or_default(), &strand),
+                    strand,
+                    contig_index,
+                    transcription_start: txStart + 1,
+                    transcription_end: txEnd,
+                    cds_start: cdsStart + 1,
+                    cds_end: cdsEnd,
+                    exon_starts: Self::parse_comma_separated_list(&exonStarts)?
+                        .iter()
+                        .map(|v| v + 1)
+                        .collect(),
+                    exon_ends: Self::parse_comma_separated_list(&exonEnds)?,
+                    has_exons: true,
+                })
+            }
 
             UcscGeneResponse::GeneResponse2 {
                 chromStart,
@@ -306,10 +350,11 @@ impl UcscGeneResponse {
                 name,
                 strand: Strand::from_str(strand)?,
                 contig_index,
-                transcription_start: chromStart,
+                transcription_start: chromStart + 1,
                 transcription_end: chromEnd,
-                cds_start: thickStart,
+                cds_start: thickStart + 1,
                 cds_end: thickEnd,
+                cds_phase: 0,
                 exon_starts: vec![],
                 exon_ends: vec![],
                 has_exons: false,
@@ -423,3 +468,121 @@ impl UcscApiCytobandResponse {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contig_header::ContigSource;
+    use rstest::rstest;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    #[rstest]
+    #[case("0,2,1,", Strand::Forward, 0)]
+    #[case("1,2,0,", Strand::Forward, 2)]
+    #[case("2,0,", Strand::Forward, 1)]
+    // Minus-strand frames start from the last exon in genome order.
+    #[case("0,2,1,", Strand::Reverse, 2)]
+    #[case("1,0,", Strand::Reverse, 0)]
+    // Non-coding exons have no frame.
+    #[case("-1,2,0,-1,", Strand::Forward, 1)]
+    #[case("-1,2,1,-1,", Strand::Reverse, 2)]
+    #[case("-1,-1,", Strand::Forward, 0)]
+    #[case("", Strand::Forward, 0)]
+    #[case("", Strand::Reverse, 0)]
+    fn test_cds_phase(#[case] exon_frames: &str, #[case] strand: Strand, #[case] expected: u8) {
+        assert_eq!(cds_phase(exon_frames, &strand), expected);
+    }
+
+    #[rstest]
+    // Coordinates are 0-based, half-open in the response.
+    #[case(
+        r#"{"name": "NM_1", "name2": "G", "strand": "-", "txStart": 100, "txEnd": 500,
+            "cdsStart": 150, "cdsEnd": 450, "exonStarts": "100,300,", "exonEnds": "200,500,"}"#,
+        ("NM_1", "G", Strand::Reverse),
+        (101, 500, 151, 450, 0),
+        (vec![101, 301], vec![200, 500], true)
+    )]
+    #[case(
+        r#"{"name": "NM_1", "name2": "G", "strand": "+", "txStart": 100, "txEnd": 500,
+            "cdsStart": 150, "cdsEnd": 450, "exonStarts": "100,300,", "exonEnds": "200,500,",
+            "exonFrames": "2,1,"}"#,
+        ("NM_1", "G", Strand::Forward),
+        (101, 500, 151, 450, 1),
+        (vec![101, 301], vec![200, 500], true)
+    )]
+    #[case(
+        r#"{"chrom": "NC_072398.2", "chromStart": 100, "chromEnd": 500, "name": "NM_1",
+            "score": 0, "strand": "+", "thickStart": 150, "thickEnd": 450}"#,
+        ("NM_1", "NM_1", Strand::Forward),
+        (101, 500, 151, 450, 0),
+        (vec![], vec![], false)
+    )]
+    fn test_api_gene(
+        #[case] response: &str,
+        #[case] names: (&str, &str, Strand),
+        #[case] coordinates: (u64, u64, u64, u64, u8),
+        #[case] exons: (Vec<u64>, Vec<u64>, bool),
+    ) {
+        let gene = serde_json::from_str::<UcscGeneResponse>(response)
+            .unwrap()
+            .to_gene(3)
+            .unwrap();
+        assert_eq!(
+            (gene.id.as_str(), gene.name.as_str(), gene.strand.clone()),
+            names
+        );
+        assert_eq!(gene.contig_index, 3);
+        assert_eq!(
+            (
+                gene.transcription_start,
+                gene.transcription_end,
+                gene.cds_start,
+                gene.cds_end,
+                gene.cds_phase
+            ),
+            coordinates
+        );
+        assert_eq!((gene.exon_starts, gene.exon_ends, gene.has_exons), exons);
+    }
+
+    #[rstest]
+    // Older gene tables have no exonFrames column.
+    #[case(None, 0)]
+    #[case(Some("2,1,"), 1)]
+    #[tokio::test]
+    async fn test_gene_row(#[case] exon_frames: Option<&str>, #[case] expected_phase: u8) {
+        let pool = SqlitePoolOptions::new()
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let query = format!(
+            "SELECT 'NM_1' AS name, 'chr1' AS chrom, '+' AS strand, 100 AS txStart, 500 AS txEnd, \
+             150 AS cdsStart, 450 AS cdsEnd, 'G' AS name2, \
+             CAST('100,300,' AS BLOB) AS exonStarts, CAST('200,500,' AS BLOB) AS exonEnds{}",
+            match exon_frames {
+                Some(_) => ", CAST(? AS BLOB) AS exonFrames",
+                None => "",
+            }
+        );
+        let mut query = sqlx::query_as::<_, UcscGeneRow>(&query);
+        if let Some(exon_frames) = exon_frames {
+            query = query.bind(exon_frames);
+        }
+        let row = query.fetch_one(&pool).await.unwrap();
+        assert_eq!(row.exonFrames.is_some(), exon_frames.is_some());
+
+        let mut contig_header = ContigHeader::new(Reference::NoReference);
+        contig_header.update_or_add_contig(
+            "chr1".to_string(),
+            Some(1000),
+            Vec::new(),
+            ContigSource::Track,
+        );
+        let gene = row.to_gene(&contig_header).unwrap();
+        assert_eq!(
+            (gene.transcription_start, gene.cds_start, gene.cds_phase),
+            (101, 151, expected_phase)
+        );
+        assert_eq!(gene.exon_starts, vec![101, 301]);
+    }
+}
@@ -0,0 +1,348 @@
+use crate::{feature::Gene, modification::complement, sequence::Sequence, strand::Strand};
+use std::collections::BTreeSet;
+
+/// Amino acids of the standard genetic code, indexed by codon with T=0, C=1, A=2, G=3.
+const GENETIC_CODE: &[u8; 64] = b"FFLLSSSSYY**CC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG";
+
+/// A translated codon.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Codon {
+    /// Genome positions of the three bases in reading order. 1-based.
+    /// Minus-strand codons run right to left, and spliced codons span an intron.
+    pub positions: [u64; 3],
+
+    /// Bases in reading order, complemented on the minus strand.
+    pub bases: [u8; 3],
+
+    /// One-letter amino acid. `*`: stop. `X`: ambiguous bases.
+    pub amino_acid: u8,
+
+    /// Codon number in the reading frame, 1-based. Amino acid number for genes.
+    pub number: u64,
+}
+
+impl Codon {
+    pub fn is_start(&self) -> bool {
+        self.amino_acid == b'M'
+    }
+
+    pub fn is_stop(&self) -> bool {
+        self.amino_acid == b'*'
+    }
+}
+
+/// Amino acid of a codon in the standard genetic code. U reads as T.
+/// `X` if a base is not A, C, G or T.
+pub fn translate_codon(codon: [u8; 3]) -> u8 {
+    let index = codon.iter().try_fold(0, |index, base| {
+        let base_index = match base.to_ascii_uppercase() {
+            b'T' | b'U' => 0,
+            b'C' => 1,
+            b'A' => 2,
+            b'G' => 3,
+            _ => return None,
+        };
+        Some(index * 4 + base_index)
+    });
+    index.map_or(b'X', |index| GENETIC_CODE[index])
+}
+
+/// Codon at positions in reading order. None if the sequence is not loaded at a position.
+fn codon_at(
+    positions: [u64; 3],
+    strand: &Strand,
+    number: u64,
+    reference_sequence: &Sequence,
+) -> Option<Codon> {
+    let mut bases = [0; 3];
+    for (base, position) in bases.iter_mut().zip(positions) {
+        let reference_base = reference_sequence.base_at(position)?.to_ascii_uppercase();
+        *base = match strand {
+            Strand::Forward => reference_base,
+            Strand::Reverse => complement(reference_base),
+        };
+    }
+
+    Some(Codon {
+        positions,
+        bases,
+        amino_acid: translate_codon(bases),
+        number,
+    })
+}
+
+/// Codons of a genome-wide reading frame overlapping [left, right].
+/// frame: 0, 1 or 2. Forward codons of frame f start at positions p with (p - 1) % 3 == f,
+/// reverse codons are the reverse complement of the same triplets.
+/// 1-based, inclusive.
+pub fn frame_codons(
+    reference_sequence: &Sequence,
+    left: u64,
+    right: u64,
+    frame: u64,
+    strand: &Strand,
+) -> Vec<Codon> {
+    // The first codon ending at or after left.
+    let lowest_start = u64::max(left.saturating_sub(2), 1);
+    let first = lowest_start + (frame % 3 + 3 - (lowest_start - 1) % 3) % 3;
+    (first..=right)
+        .step_by(3)
+        .filter_map(|start| {
+            let positions = match strand {
+                Strand::Forward => [start, start + 1, start + 2],
+                Strand::Reverse => [start + 2, start + 1, start],
+            };
+            codon_at(positions, strand, (start - 1) / 3 + 1, reference_sequence)
+        })
+        .collect()
+}
+
+/// Coding segments of a gene in reading order: (start, end), 1-based, inclusive.
+/// Empty for non-coding genes.
+fn cds_segments(gene: &Gene) -> Vec<(u64, u64)> {
+    let mut segments: Vec<(u64, u64)> = gene
+        .exon_starts
+        .iter()
+        .zip(gene.exon_ends.iter())
+        .map(|(start, end)| {
+            (
+                u64::max(*start, gene.cds_start),
+                u64::min(*end, gene.cds_end),
+            )
+        })
+        .filter(|(start, end)| start <= end)
+        .collect();
+    segments.sort();
+    if gene.strand == Strand::Reverse {
+        segments.reverse();
+    }
+    segments
+}
+
+/// Codons of a gene's coding sequence overlapping [left, right], in the transcript's reading frame.
+/// The frame of each exon follows from the coding bases before it, so codons continue across
+/// intron gaps. Minus-strand genes are read on the reverse complement. The first codon starts
+/// after the CDS phase, and incomplete codons at either end of the CDS are skipped.
+/// 1-based, inclusive.
+pub fn gene_codons(
+    gene: &Gene,
+    reference_sequence: &Sequence,
+    left: u64,
+    right: u64,
+) -> Vec<Codon> {
+    let segments = cds_segments(gene);
+    let phase = u64::from(gene.cds_phase);
+
+    // Coding bases before each segment.
+    let mut offsets = Vec::with_capacity(segments.len());
+    let mut cds_length = 0;
+    for (start, end) in segments.iter() {
+        offsets.push(cds_length);
+        cds_length += end - start + 1;
+    }
+
+    // Position of the nth coding base (0-based).
+    let position_of = |offset: u64| {
+        let i = offsets.partition_point(|segment_offset| *segment_offset <= offset) - 1;
+        let (start, end) = segments[i];
+        match gene.strand {
+            Strand::Forward => start + offset - offsets[i],
+            Strand::Reverse => end - (offset - offsets[i]),
+        }
+    };
+
+    let mut codon_indexes = BTreeSet::new();
+    for ((start, end), offset) in segments.iter().zip(offsets.iter()) {
+        let (overlap_start, overlap_end) = (u64::max(*start, left), u64::min(*end, right));
+        if overlap_start > overlap_end {
+            continue;
+        }
+        let (first, last) = match gene.strand {
+            Strand::Forward => (offset + overlap_start - start, offset + overlap_end - start),
+            Strand::Reverse => (offset + end - overlap_end, offset + end - overlap_start),
+        };
+        if last < phase {
+            continue;
+        }
+        codon_indexes.extend(first.saturating_sub(phase) / 3..=(last - phase) / 3);
+    }
+
+    codon_indexes
+        .into_iter()
+        .filter(|codon_index| phase + codon_index * 3 + 2 < cds_length)
+        .filter_map(|codon_index| {
+            let positions = [0, 1, 2].map(|i| position_of(phase + codon_index * 3 + i));
+            codon_at(positions, &gene.strand, codon_index + 1, reference_sequence)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::sequence;
+    use crate::tracks::schema::UcscGeneResponse;
+    use rstest::rstest;
+
+    /// ATG AAA TGG TAA, with an intron (ccc) in the second codon at 6-8.
+    ///                            1234567890123456
+    const REFERENCE: &[u8] = b"ATGAAcccATGGTAAg";
+
+    fn reference() -> Sequence {
+        sequence(REFERENCE)
+    }
+
+    fn gene(strand: Strand) -> Gene {
+        Gene {
+            id: "t".to_string(),
+            name: "g".to_string(),
+            strand,
+            contig_index: 0,
+            transcription_start: 1,
+            transcription_end: 16,
+            cds_start: 1,
+            cds_end: 15,
+            cds_phase: 0,
+            exon_starts: vec![1, 9],
+            exon_ends: vec![5, 16],
+            has_exons: true,
+        }
+    }
+
+    #[rstest]
+    #[case(b"ATG", b'M')]
+    #[case(b"tgg", b'W')]
+    #[case(b"TAA", b'*')]
+    #[case(b"UGA", b'*')]
+    #[case(b"GCN", b'X')]
+    fn test_translate_codon(#[case] codon: &[u8; 3], #[case] expected: u8) {
+        assert_eq!(translate_codon(*codon), expected);
+    }
+
+    #[rstest]
+    // ATG AAc ccA TGG TAA
+    #[case(0, Strand::Forward, 1, 16, b"MNPW*".to_vec(), 1)]
+    // (A) TGA Acc cAT GGT AAg
+    #[case(1, Strand::Forward, 1, 16, b"*THGK".to_vec(), 2)]
+    // Codons overlapping the view, including partially.
+    #[case(0, Strand::Forward, 5, 7, b"NP".to_vec(), 4)]
+    // Reverse complement of each triplet: CAT GTT TGG CCA TTA.
+    #[case(0, Strand::Reverse, 1, 16, b"HVWPL".to_vec(), 1)]
+    fn test_frame_codons(
+        #[case] frame: u64,
+        #[case] strand: Strand,
+        #[case] left: u64,
+        #[case] right: u64,
+        #[case] expected: Vec<u8>,
+        #[case] first_start: u64,
+    ) {
+        let codons = frame_codons(&reference(), left, right, frame, &strand);
+        assert_eq!(
+            codons
+                .iter()
+                .map(|codon| codon.amino_acid)
+                .collect::<Vec<_>>(),
+            expected
+        );
+        assert_eq!(codons[0].positions.iter().min().copied(), Some(first_start));
+    }
+
+    #[test]
+    fn test_gene_codons() {
+        // ATGAA + ATGGTAA: ATG AAA TGG TAA, the second codon spans the intron.
+        let codons = gene_codons(&gene(Strand::Forward), &reference(), 1, 16);
+        assert_eq!(
+            codons,
+            vec![
+                Codon {
+                    positions: [1, 2, 3],
+                    bases: *b"ATG",
+                    amino_acid: b'M',
+                    number: 1,
+                },
+                Codon {
+                    positions: [4, 5, 9],
+                    bases: *b"AAA",
+                    amino_acid: b'K',
+                    number: 2,
+                },
+                Codon {
+                    positions: [10, 11, 12],
+                    bases: *b"TGG",
+                    amino_acid: b'W',
+                    number: 3,
+                },
+                Codon {
+                    positions: [13, 14, 15],
+                    bases: *b"TAA",
+                    amino_acid: b'*',
+                    number: 4,
+                },
+            ]
+        );
+
+        // Only codons overlapping the view. The intron has no codons.
+        let codons = gene_codons(&gene(Strand::Forward), &reference(), 6, 9);
+        assert_eq!(
+            codons.iter().map(|codon| codon.number).collect::<Vec<_>>(),
+            vec![2]
+        );
+
+        // Minus strand: TTACCAT + TTCAT, read from position 15 down.
+        // TTA CCA TTT CAT: L P F H, and the codon over the intron is the third.
+        let codons = gene_codons(&gene(Strand::Reverse), &reference(), 1, 16);
+        assert_eq!(
+            codons
+                .iter()
+                .map(|codon| (codon.amino_acid, codon.positions))
+                .collect::<Vec<_>>(),
+            vec![
+                (b'L', [15, 14, 13]),
+                (b'P', [12, 11, 10]),
+                (b'F', [9, 5, 4]),
+                (b'H', [3, 2, 1]),
+            ]
+        );
+    }
+
+    #[rstest]
+    // 0-based, half-open coordinates of the test gene.
+    #[case(
+        r#"{"name": "t", "name2": "g", "strand": "+", "txStart": 0, "txEnd": 16,
+            "cdsStart": 0, "cdsEnd": 15, "exonStarts": "0,8,", "exonEnds": "5,16,"}"#,
+        b"MKW*".to_vec()
+    )]
+    // Incomplete 5' end: TG AAA TGG TAA.
+    #[case(
+        r#"{"name": "t", "name2": "g", "strand": "+", "txStart": 0, "txEnd": 16,
+            "cdsStart": 1, "cdsEnd": 15, "exonStarts": "0,8,", "exonEnds": "5,16,",
+            "exonFrames": "1,2,"}"#,
+        b"KW*".to_vec()
+    )]
+    // Minus strand, incomplete 5' end from position 14: T ACC ATT TCA T.
+    #[case(
+        r#"{"name": "t", "name2": "g", "strand": "-", "txStart": 0, "txEnd": 16,
+            "cdsStart": 0, "cdsEnd": 14, "exonStarts": "0,8,", "exonEnds": "5,16,",
+            "exonFrames": "2,2,"}"#,
+        b"TIS".to_vec()
+    )]
+    fn test_api_gene_codons(#[case] response: &str, #[case] expected: Vec<u8>) {
+        let gene = serde_json::from_str::<UcscGeneResponse>(response)
+            .unwrap()
+            .to_gene(0)
+            .unwrap();
+        assert_eq!(
+            (gene.exon_starts.clone(), gene.exon_ends.clone()),
+            (vec![1, 9], vec![5, 16])
+        );
+
+        let codons = gene_codons(&gene, &reference(), 1, 16);
+        assert_eq!(
+            codons
+                .iter()
+                .map(|codon| codon.amino_acid)
+                .collect::<Vec<_>>(),
+            expected
+        );
+    }
+}
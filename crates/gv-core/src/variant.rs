@@ -2,11 +2,82 @@ use crate::contig_header::ContigHeader;
 use crate::error::TGVError;
 use crate::intervals::{GenomeInterval, Region, SortedIntervalCollection};
 use itertools::Itertools;
-use noodles::vcf::{
-    self,
-    variant::record::{AlternateBases, Filters},
+use noodles::{
+    bcf, bgzf,
+    vcf::{
+        self,
+        variant::{
+            RecordBuf,
+            record::samples::series::value::genotype::Phasing,
+            record_buf::{
+                info,
+                samples::sample::{self, value::Genotype},
+            },
+        },
+    },
 };
-use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::path::Path;
+use strum::Display;
+
+/// Structural variant type, from INFO/SVTYPE or the symbolic / breakend ALT allele.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
+pub enum StructuralVariantType {
+    #[strum(to_string = "DEL")]
+    Deletion,
+    #[strum(to_string = "DUP")]
+    Duplication,
+    #[strum(to_string = "INV")]
+    Inversion,
+    #[strum(to_string = "INS")]
+    Insertion,
+    #[strum(to_string = "CNV")]
+    CopyNumber,
+    #[strum(to_string = "BND")]
+    Breakend,
+}
+
+impl StructuralVariantType {
+    /// DEL, DUP:TANDEM, <INV>, ... Subtypes after ':' are ignored.
+    fn parse(s: &str) -> Option<Self> {
+        let s = s.trim_start_matches('<').trim_end_matches('>');
+        match s.split(':').next()?.to_ascii_uppercase().as_str() {
+            "DEL" => Some(Self::Deletion),
+            "DUP" => Some(Self::Duplication),
+            "INV" => Some(Self::Inversion),
+            "INS" => Some(Self::Insertion),
+            "CNV" => Some(Self::CopyNumber),
+            "BND" | "TRA" => Some(Self::Breakend),
+            _ => None,
+        }
+    }
+}
+
+/// Position of the mate breakend of a BND record. e.g. G]17:198982] -> 17:198982
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BreakendMate {
+    /// Contig name as in the VCF file.
+    pub contig: String,
+
+    /// 1-based.
+    pub position: u64,
+}
+
+impl BreakendMate {
+    fn from_allele(allele: &str) -> Option<Self> {
+        let bracket = allele.find(['[', ']'])?;
+        let rest = &allele[bracket + 1..];
+        let mate = &rest[..rest.find(['[', ']'])?];
+        // Contig names can contain ':' (e.g. HLA contigs), so split at the last one.
+        let (contig, position) = mate.rsplit_once(':')?;
+
+        Some(Self {
+            contig: contig.to_string(),
+            position: position.parse().ok()?,
+        })
+    }
+}
+
 pub struct Variant {
     /// Contig id name. This is not stored in the record.
     pub contig_index: usize,
@@ -14,16 +85,25 @@ pub struct Variant {
     /// Variant start. 1-based, inclusive.
     start: u64,
 
-    /// Index in the VCF file
+    /// Variant end. 1-based, inclusive. INFO/END or SVLEN for structural variants.
+    end: u64,
+
+    pub sv_type: Option<StructuralVariantType>,
+
+    /// Mate breakend of BND records.
+    pub mate: Option<BreakendMate>,
+
+    /// Index of the record among the variants of one load: the whole file for unindexed files,
+    /// the queried region for indexed files. Not stable across loads.
     pub index: usize,
 
     /// VCF record
-    pub record: vcf::Record,
+    pub record: RecordBuf,
 }
 
 impl Variant {
     pub fn new(
-        record: vcf::Record,
+        record: RecordBuf,
         index: usize,
         contig_header: &ContigHeader,
     ) -> Result<Self, TGVError> {
@@ -32,45 +112,273 @@ impl Variant {
 
         let start = record
             .variant_start()
-            .ok_or(TGVError::ValueError("VCF record parsing error".to_string()))??
+            .ok_or(TGVError::ValueError("VCF record parsing error".to_string()))?
             .get() as u64;
 
+        let alternate_bases = record.alternate_bases().as_ref();
+        let sv_type = match record.info().get("SVTYPE") {
+            Some(Some(info::field::Value::String(sv_type))) => {
+                StructuralVariantType::parse(sv_type)
+            }
+            _ => alternate_bases.iter().find_map(|allele| {
+                if allele.starts_with('<') {
+                    StructuralVariantType::parse(allele)
+                } else if allele.contains(['[', ']']) {
+                    Some(StructuralVariantType::Breakend)
+                } else {
+                    None
+                }
+            }),
+        };
+
+        let mate = match sv_type {
+            Some(StructuralVariantType::Breakend) => alternate_bases
+                .iter()
+                .find_map(|allele| BreakendMate::from_allele(allele)),
+            _ => None,
+        };
+
+        let info_integer = |key: &str| match record.info().get(key) {
+            Some(Some(info::field::Value::Integer(n))) => Some(*n as i64),
+            Some(Some(info::field::Value::Array(info::field::value::Array::Integer(values)))) => {
+                values.iter().flatten().next().map(|n| *n as i64)
+            }
+            _ => None,
+        };
+
+        let end = match (info_integer("END"), info_integer("SVLEN"), sv_type) {
+            (Some(end), _, _) if end >= start as i64 => end as u64,
+            (
+                None,
+                Some(sv_length),
+                Some(
+                    StructuralVariantType::Deletion
+                    | StructuralVariantType::Duplication
+                    | StructuralVariantType::Inversion
+                    | StructuralVariantType::CopyNumber,
+                ),
+            ) => start + sv_length.unsigned_abs(),
+            _ => start + (record.reference_bases().len() as u64).max(1) - 1,
+        };
+
         Ok(Self {
             contig_index,
             start,
+            end,
+            sv_type,
+            mate,
             index,
             record,
         })
     }
 }
 
+/// Genotype of a sample at a variant, classified for display.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Zygosity {
+    HomRef,
+    Het,
+    HomAlt,
+    /// Any allele is missing (e.g. ./., ./1).
+    NoCall,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SampleGenotype {
+    pub zygosity: Zygosity,
+
+    /// All alleles are phased (e.g. 0|1).
+    pub phased: bool,
+}
+
+impl Variant {
+    /// Genotype (GT) of the ith sample in the file. None if GT is absent.
+    pub fn sample_genotype(&self, sample_index: usize) -> Option<SampleGenotype> {
+        let Some(Some(sample::Value::Genotype(genotype))) =
+            self.record.samples().get_index(sample_index)?.get("GT")
+        else {
+            return None;
+        };
+
+        let alleles = genotype.as_ref();
+        let positions = alleles
+            .iter()
+            .map(|allele| allele.position())
+            .collect::<Option<Vec<usize>>>();
+
+        let zygosity = match positions {
+            None => Zygosity::NoCall,
+            Some(positions) if positions.is_empty() => Zygosity::NoCall,
+            Some(positions) if positions.iter().all(|position| *position == 0) => Zygosity::HomRef,
+            Some(positions) if positions.iter().all_equal() => Zygosity::HomAlt,
+            Some(_) => Zygosity::Het,
+        };
+
+        Some(SampleGenotype {
+            zygosity,
+            phased: alleles.len() > 1
+                && alleles
+                    .iter()
+                    .skip(1)
+                    .all(|allele| allele.phasing() == Phasing::Phased),
+        })
+    }
+}
+
+/// FORMAT fields shown for each sample.
+const DESCRIBED_FORMAT_KEYS: [&str; 4] = ["GT", "AD", "DP", "GQ"];
+
 impl Variant {
-    pub fn describe(&self) -> String {
-        // FIXME: display more fields.
-        // Note that other fields (filter, info, sample) requires the VCF header.
-        format!(
-            "Variant: {}:{} {}>{} QUAL={}",
+    /// One line for the variant, one line per INFO field and one line per sample.
+    /// header: VCF header. Used for INFO descriptions and sample names.
+    pub fn describe(&self, header: &vcf::Header) -> Vec<String> {
+        let ids = self.record.ids().as_ref().iter().join(";");
+        let filters = self.record.filters().as_ref().iter().join(";");
+
+        let mut first_line = format!(
+            "Variant: {}:{} {}>{} QUAL={} FILTER={}{}",
             self.record.reference_sequence_name(),
             self.start,
             self.record.reference_bases(),
-            self.record
-                .alternate_bases()
-                .iter()
-                .collect::<Result<Vec<&str>, _>>()
-                .unwrap_or(vec!["?"; 1])
-                .iter()
-                .join(","),
+            self.record.alternate_bases().as_ref().iter().join(","),
             self.record
                 .quality_score()
-                .map(|score_result| match score_result {
-                    Ok(score) => format!("{}", score),
-                    _ => "?".to_string(),
-                })
+                .map(|score| format!("{}", score))
                 .unwrap_or("?".to_string()),
-        )
+            if filters.is_empty() { "." } else { &filters },
+            if ids.is_empty() {
+                "".to_string()
+            } else {
+                format!(" ID={}", ids)
+            },
+        );
+        if let Some(sv_type) = self.sv_type {
+            first_line += &format!(" {}", sv_type);
+            if sv_type != StructuralVariantType::Breakend {
+                first_line += &format!(" {}-{} ({}bp)", self.start, self.end, self.length());
+            }
+        }
+        if let Some(mate) = &self.mate {
+            first_line += &format!(" mate={}:{}", mate.contig, mate.position);
+        }
+        let mut lines = vec![first_line];
+
+        for (key, value) in self.record.info().as_ref().iter() {
+            let field = match value {
+                Some(info::field::Value::Flag) => key.to_string(),
+                value => format!("{}={}", key, format_info_value(value.as_ref())),
+            };
+            match header.infos().get(key) {
+                Some(info) => lines.push(format!("  {}: {}", field, info.description())),
+                None => lines.push(format!("  {}", field)),
+            }
+        }
+
+        for sample_index in 0..header.sample_names().len() {
+            if let Some(sample) = self.describe_sample(header, sample_index) {
+                lines.push(format!("  {}", sample));
+            }
+        }
+
+        lines
+    }
+
+    /// GT/AD/DP/GQ of the ith sample in the file. e.g. NA00001: GT=0|1 DP=8 GQ=48
+    pub fn describe_sample(&self, header: &vcf::Header, sample_index: usize) -> Option<String> {
+        let sample_name = header.sample_names().get_index(sample_index)?;
+        let samples = self.record.samples();
+        let sample = samples.get_index(sample_index)?;
+
+        let fields = DESCRIBED_FORMAT_KEYS
+            .iter()
+            .filter(|key| samples.keys().as_ref().contains(**key))
+            .map(|key| {
+                format!(
+                    "{}={}",
+                    key,
+                    format_sample_value(sample.get(*key).flatten())
+                )
+            })
+            .join(" ");
+
+        Some(format!("{}: {}", sample_name, fields))
     }
 }
 
+fn format_option<T: ToString>(value: &Option<T>) -> String {
+    value
+        .as_ref()
+        .map(|value| value.to_string())
+        .unwrap_or(".".to_string())
+}
+
+fn format_info_value(value: Option<&info::field::Value>) -> String {
+    use info::field::value::Array;
+
+    match value {
+        None => ".".to_string(),
+        Some(info::field::Value::Integer(n)) => n.to_string(),
+        Some(info::field::Value::Float(n)) => n.to_string(),
+        Some(info::field::Value::Flag) => "".to_string(),
+        Some(info::field::Value::Character(c)) => c.to_string(),
+        Some(info::field::Value::String(s)) => s.clone(),
+        Some(info::field::Value::Array(Array::Integer(values))) => {
+            values.iter().map(format_option).join(",")
+        }
+        Some(info::field::Value::Array(Array::Float(values))) => {
+            values.iter().map(format_option).join(",")
+        }
+        Some(info::field::Value::Array(Array::Character(values))) => {
+            values.iter().map(format_option).join(",")
+        }
+        Some(info::field::Value::Array(Array::String(values))) => {
+            values.iter().map(format_option).join(",")
+        }
+    }
+}
+
+fn format_sample_value(value: Option<&sample::Value>) -> String {
+    use sample::value::Array;
+
+    match value {
+        None => ".".to_string(),
+        Some(sample::Value::Integer(n)) => n.to_string(),
+        Some(sample::Value::Float(n)) => n.to_string(),
+        Some(sample::Value::Character(c)) => c.to_string(),
+        Some(sample::Value::String(s)) => s.clone(),
+        Some(sample::Value::Genotype(genotype)) => format_genotype(genotype),
+        Some(sample::Value::Array(Array::Integer(values))) => {
+            values.iter().map(format_option).join(",")
+        }
+        Some(sample::Value::Array(Array::Float(values))) => {
+            values.iter().map(format_option).join(",")
+        }
+        Some(sample::Value::Array(Array::Character(values))) => {
+            values.iter().map(format_option).join(",")
+        }
+        Some(sample::Value::Array(Array::String(values))) => {
+            values.iter().map(format_option).join(",")
+        }
+    }
+}
+
+/// VCF genotype string. e.g. 0|1, 1/1, ./.
+fn format_genotype(genotype: &Genotype) -> String {
+    genotype
+        .as_ref()
+        .iter()
+        .enumerate()
+        .map(|(i, allele)| {
+            let separator = match (i, allele.phasing()) {
+                (0, _) => "",
+                (_, Phasing::Phased) => "|",
+                (_, Phasing::Unphased) => "/",
+            };
+            format!("{}{}", separator, format_option(&allele.position()))
+        })
+        .join("")
+}
+
 impl GenomeInterval for Variant {
     fn contig_index(&self) -> usize {
         self.contig_index
@@ -81,45 +389,446 @@ impl GenomeInterval for Variant {
     }
 
     fn end(&self) -> u64 {
-        self.start + self.record.reference_bases().len() as u64 - 1
+        self.end
     }
 }
+
+/// Variant files larger than this (in bytes) must be indexed.
+const MAX_UNINDEXED_FILE_SIZE: u64 = 10 * 1024 * 1024;
+
+enum VariantReader {
+    /// Small unindexed VCF or BCF. The whole file is read at once.
+    Unindexed,
+
+    /// bgzipped VCF with a .tbi or .csi index.
+    IndexedVcf(vcf::io::IndexedReader<bgzf::io::Reader<File>>),
+
+    /// BCF with a .csi index.
+    IndexedBcf(bcf::io::IndexedReader<bgzf::io::Reader<File>>),
+}
+
 pub struct VariantRepository {
     pub vcf_path: String,
-    // FIXME: save VCF header. This is needed for retrieving VCF FILTER, INFO, SAMPLE fields.
+
+    pub header: vcf::Header,
+
+    reader: VariantReader,
+
+    /// Contig names that can be queried in the indexed file.
+    contig_names: Vec<String>,
 }
 
 impl VariantRepository {
+    pub fn new(vcf_path: &str) -> Result<Self, TGVError> {
+        if !Path::new(vcf_path).exists() {
+            return Err(TGVError::IOError(format!(
+                "Variant file {} not found",
+                vcf_path
+            )));
+        }
+
+        let (header, reader, contig_names) = if is_bcf(vcf_path) {
+            if index_exists(vcf_path, &["csi"]) {
+                let mut reader =
+                    bcf::io::indexed_reader::Builder::default().build_from_path(vcf_path)?;
+                let header = reader.read_header()?;
+                let contig_names = header.contigs().keys().cloned().collect();
+                (header, VariantReader::IndexedBcf(reader), contig_names)
+            } else {
+                check_unindexed_file_size(vcf_path)?;
+                let header = bcf::io::reader::Builder::default()
+                    .build_from_path(vcf_path)?
+                    .read_header()?;
+                (header, VariantReader::Unindexed, Vec::new())
+            }
+        } else if index_exists(vcf_path, &["tbi", "csi"]) {
+            let mut reader =
+                vcf::io::indexed_reader::Builder::default().build_from_path(vcf_path)?;
+            let header = reader.read_header()?;
+            let contig_names = match reader.index().header() {
+                Some(index_header) => index_header
+                    .reference_sequence_names()
+                    .iter()
+                    .map(|name| name.to_string())
+                    .collect(),
+                None => header.contigs().keys().cloned().collect(),
+            };
+            (header, VariantReader::IndexedVcf(reader), contig_names)
+        } else {
+            check_unindexed_file_size(vcf_path)?;
+            let header = vcf::io::reader::Builder::default()
+                .build_from_path(vcf_path)?
+                .read_header()?;
+            (header, VariantReader::Unindexed, Vec::new())
+        };
+
+        Ok(Self {
+            vcf_path: vcf_path.to_string(),
+            header,
+            reader,
+            contig_names,
+        })
+    }
+
+    /// Whether variants can be queried by region.
+    /// Unindexed files are always read in full.
+    pub fn is_indexed(&self) -> bool {
+        !matches!(self.reader, VariantReader::Unindexed)
+    }
+
+    /// Read variants overlapping region. Unindexed files return all variants.
     pub fn read_variants(
-        &self,
+        &mut self,
+        region: &Region,
         contig_header: &ContigHeader,
     ) -> Result<SortedIntervalCollection<Variant>, TGVError> {
-        let mut vcf =
-            vcf::io::reader::Builder::default().build_from_path(self.vcf_path.as_str())?;
-        vcf.read_header()?;
+        let noodles_region = match &self.reader {
+            VariantReader::Unindexed => return self.read_all_variants(contig_header),
+            _ => match self.query_region(region, contig_header)? {
+                Some(noodles_region) => noodles_region,
+                None => return SortedIntervalCollection::new(Vec::new()),
+            },
+        };
+
+        let records = match &mut self.reader {
+            VariantReader::Unindexed => Vec::new(), // Handled above.
+            VariantReader::IndexedVcf(reader) => reader
+                .query(&self.header, &noodles_region)?
+                .map(|record| RecordBuf::try_from_variant_record(&self.header, &record?))
+                .collect::<Result<Vec<_>, _>>()?,
+            VariantReader::IndexedBcf(reader) => reader
+                .query(&self.header, &noodles_region)?
+                .map(|record| RecordBuf::try_from_variant_record(&self.header, &record?))
+                .collect::<Result<Vec<_>, _>>()?,
+        };
 
-        let variants: Vec<Variant> = vcf
-            .records()
+        let variants = records
+            .into_iter()
             .enumerate()
-            .map(|(index, record)| Variant::new(record?, index, contig_header))
+            .map(|(index, record)| Variant::new(record, index, contig_header))
             .collect::<Result<Vec<Variant>, _>>()?;
 
-        // lookup
-        // contig_index -> {varaiant start -> variant ids}
+        SortedIntervalCollection::new(variants)
+    }
+
+    /// Region named as in the indexed file. None if the contig is not in the file.
+    fn query_region(
+        &self,
+        region: &Region,
+        contig_header: &ContigHeader,
+    ) -> Result<Option<noodles::core::Region>, TGVError> {
+        self.contig_names
+            .iter()
+            .find(|name| {
+                contig_header
+                    .try_get_index_by_str(name)
+                    .is_ok_and(|index| index == region.contig_index())
+            })
+            .map(|name| region.noodles_region(name))
+            .transpose()
+    }
 
-        // let mut variant_lookup: HashMap<usize, BTreeMap<u64, Vec<usize>>> = HashMap::new();
+    fn read_all_variants(
+        &self,
+        contig_header: &ContigHeader,
+    ) -> Result<SortedIntervalCollection<Variant>, TGVError> {
+        let records = if is_bcf(&self.vcf_path) {
+            let mut reader = bcf::io::reader::Builder::default().build_from_path(&self.vcf_path)?;
+            // Skip the header. It is already parsed in self.header.
+            reader.read_header()?;
+            reader
+                .record_bufs(&self.header)
+                .collect::<Result<Vec<_>, _>>()?
+        } else {
+            let mut reader = vcf::io::reader::Builder::default().build_from_path(&self.vcf_path)?;
+            reader.read_header()?;
+            reader
+                .record_bufs(&self.header)
+                .collect::<Result<Vec<_>, _>>()?
+        };
 
-        // for (i, variant) in variants.iter().enumerate() {
-        //     variant_lookup
-        //         .entry(variant.contig_index)
-        //         .and_modify(|vs| {
-        //             vs.entry(variant.start())
-        //                 .and_modify(|vvs| vvs.push(i))
-        //                 .or_insert(vec![i]);
-        //         })
-        //         .or_insert(BTreeMap::from([(variant.start(), vec![i])]));
-        // }
+        let mut variants = records
+            .into_iter()
+            .enumerate()
+            .map(|(index, record)| Variant::new(record, index, contig_header))
+            .collect::<Result<Vec<Variant>, _>>()?;
+        variants.sort_by_key(|variant| (variant.contig_index(), variant.start(), variant.end()));
 
         SortedIntervalCollection::new(variants)
     }
 }
+
+pub fn is_bcf(path: &str) -> bool {
+    path.to_lowercase().ends_with(".bcf")
+}
+
+fn index_exists(path: &str, extensions: &[&str]) -> bool {
+    extensions
+        .iter()
+        .any(|extension| Path::new(&format!("{}.{}", path, extension)).exists())
+}
+
+fn check_unindexed_file_size(path: &str) -> Result<(), TGVError> {
+    let size = std::fs::metadata(path)?.len();
+    if size > MAX_UNINDEXED_FILE_SIZE {
+        return Err(TGVError::IOError(format!(
+            "Variant file {} is too large to load without an index. Compress it with bgzip and index it with `tabix -p vcf` (VCF) or `bcftools index` (BCF).",
+            path
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contig_header::ContigSource;
+    use crate::intervals::Focus;
+    use crate::reference::Reference;
+    use crate::test_utils::test_data;
+    use noodles::vcf::variant::io::Write as _;
+    use rstest::rstest;
+    use std::io::Write;
+
+    fn contig_header() -> ContigHeader {
+        let mut contig_header = ContigHeader::new(Reference::NoReference);
+        contig_header.update_or_add_contig(
+            "20".to_string(),
+            Some(62435964),
+            Vec::new(),
+            ContigSource::Track,
+        );
+        contig_header
+    }
+
+    /// Write simple.vcf as a sorted, indexed, bgzipped VCF and BCF.
+    fn write_indexed_copies(dir: &Path) -> (String, String) {
+        let mut reader = vcf::io::reader::Builder::default()
+            .build_from_path(test_data("simple.vcf"))
+            .unwrap();
+        let header = reader.read_header().unwrap();
+        let mut records = reader
+            .record_bufs(&header)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        records.sort_by_key(|record| record.variant_start());
+
+        let vcf_path = dir.join("simple.vcf.gz");
+        let mut writer =
+            vcf::io::Writer::new(bgzf::io::Writer::new(File::create(&vcf_path).unwrap()));
+        writer.write_header(&header).unwrap();
+        for record in records.iter() {
+            writer.write_variant_record(&header, record).unwrap();
+        }
+        writer.into_inner().finish().unwrap().flush().unwrap();
+        let index = vcf::fs::index(&vcf_path).unwrap();
+        noodles::tabix::fs::write(dir.join("simple.vcf.gz.tbi"), &index).unwrap();
+
+        let bcf_path = dir.join("simple.bcf");
+        let mut writer = bcf::io::Writer::new(File::create(&bcf_path).unwrap());
+        writer.write_header(&header).unwrap();
+        for record in records.iter() {
+            writer.write_variant_record(&header, record).unwrap();
+        }
+        writer.try_finish().unwrap();
+        drop(writer);
+        let index = bcf::fs::index(&bcf_path).unwrap();
+        noodles::csi::fs::write(dir.join("simple.bcf.csi"), &index).unwrap();
+
+        (
+            vcf_path.to_str().unwrap().to_string(),
+            bcf_path.to_str().unwrap().to_string(),
+        )
+    }
+
+    #[rstest]
+    #[case(14370, 1, vec![14370])]
+    #[case(17330, 80000, vec![14370, 17330, 88108])]
+    #[case(1200000, 100000, vec![1110696, 1230237])]
+    #[case(500000, 100, vec![])]
+    fn test_indexed_query_matches_full_load(
+        #[case] position: u64,
+        #[case] half_width: u64,
+        #[case] expected_starts: Vec<u64>,
+    ) {
+        let dir = tempfile::tempdir().unwrap();
+        let (vcf_path, bcf_path) = write_indexed_copies(dir.path());
+        let contig_header = contig_header();
+        let region = Region {
+            focus: Focus {
+                contig_index: 0,
+                position,
+            },
+            half_width,
+        };
+
+        let mut unindexed = VariantRepository::new(&test_data("simple.vcf")).unwrap();
+        assert!(!unindexed.is_indexed());
+        let all_variants = unindexed.read_variants(&region, &contig_header).unwrap();
+        assert_eq!(all_variants.intervals.len(), 5);
+        let full_load_starts = all_variants
+            .overlapping(0, region.start(), region.end())
+            .unwrap()
+            .iter()
+            .map(|variant| variant.start())
+            .collect::<Vec<u64>>();
+        assert_eq!(full_load_starts, expected_starts);
+
+        for path in [vcf_path, bcf_path] {
+            let mut indexed = VariantRepository::new(&path).unwrap();
+            assert!(indexed.is_indexed());
+            let variants = indexed.read_variants(&region, &contig_header).unwrap();
+            let starts = variants
+                .intervals
+                .iter()
+                .map(|variant| variant.start())
+                .collect::<Vec<u64>>();
+            assert_eq!(starts, expected_starts, "{}", path);
+        }
+    }
+
+    #[test]
+    fn test_describe() {
+        let mut repository = VariantRepository::new(&test_data("simple.vcf")).unwrap();
+        let variants = repository
+            .read_variants(&Region::default(), &contig_header())
+            .unwrap();
+        let variant = variants
+            .intervals
+            .iter()
+            .find(|variant| variant.start() == 1110696)
+            .unwrap();
+
+        assert_eq!(
+            variant.describe(&repository.header),
+            vec![
+                "Variant: 20:1110696 A>G,T QUAL=67 FILTER=PASS ID=rs6040355",
+                "  NS=2: Number of Samples With Data",
+                "  DP=10: Total Depth",
+                "  AF=0.333,0.667: Allele Frequency",
+                "  AA=T: Ancestral Allele",
+                "  DB: dbSNP membership, build 129",
+                "  NA00001: GT=1|2 DP=6 GQ=21",
+                "  NA00002: GT=2|1 DP=0 GQ=2",
+                "  NA00003: GT=2/2 DP=4 GQ=35",
+            ]
+        );
+    }
+
+    #[rstest]
+    #[case(14370, vec![
+        Some((Zygosity::HomRef, true)),
+        Some((Zygosity::Het, true)),
+        Some((Zygosity::HomAlt, false)),
+    ])]
+    #[case(1110696, vec![
+        Some((Zygosity::Het, true)),
+        Some((Zygosity::Het, true)),
+        Some((Zygosity::HomAlt, false)),
+    ])]
+    fn test_sample_genotype(#[case] start: u64, #[case] expected: Vec<Option<(Zygosity, bool)>>) {
+        let mut repository = VariantRepository::new(&test_data("simple.vcf")).unwrap();
+        let variants = repository
+            .read_variants(&Region::default(), &contig_header())
+            .unwrap();
+        let variant = variants
+            .intervals
+            .iter()
+            .find(|variant| variant.start() == start)
+            .unwrap();
+
+        let genotypes = (0..4)
+            .map(|i| {
+                variant
+                    .sample_genotype(i)
+                    .map(|genotype| (genotype.zygosity, genotype.phased))
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(genotypes[..3], expected[..]);
+        assert_eq!(genotypes[3], None);
+    }
+
+    #[rstest]
+    #[case(100, 600, Some(StructuralVariantType::Deletion), None)]
+    #[case(1000, 1300, Some(StructuralVariantType::Duplication), None)]
+    #[case(2000, 2400, Some(StructuralVariantType::Inversion), None)]
+    #[case(3000, 3000, Some(StructuralVariantType::Insertion), None)]
+    #[case(5000, 5000, Some(StructuralVariantType::Breakend), Some(("2", 7000)))]
+    #[case(6000, 6000, None, None)]
+    fn test_structural_variant(
+        #[case] start: u64,
+        #[case] end: u64,
+        #[case] sv_type: Option<StructuralVariantType>,
+        #[case] mate: Option<(&str, u64)>,
+    ) {
+        let mut contig_header = ContigHeader::new(Reference::NoReference);
+        for name in ["1", "2"] {
+            contig_header.update_or_add_contig(
+                name.to_string(),
+                Some(1000000),
+                Vec::new(),
+                ContigSource::Track,
+            );
+        }
+        let mut repository = VariantRepository::new(&test_data("sv.vcf")).unwrap();
+        let variants = repository
+            .read_variants(&Region::default(), &contig_header)
+            .unwrap();
+        let variant = variants
+            .intervals
+            .iter()
+            .find(|variant| variant.contig_index == 0 && variant.start() == start)
+            .unwrap();
+
+        assert_eq!(variant.end(), end);
+        assert_eq!(variant.sv_type, sv_type);
+        assert_eq!(
+            variant.mate,
+            mate.map(|(contig, position)| BreakendMate {
+                contig: contig.to_string(),
+                position,
+            })
+        );
+    }
+
+    #[rstest]
+    #[case("G]17:198982]", Some(("17", 198982)))]
+    #[case("]13:123456]T", Some(("13", 123456)))]
+    #[case("[chr17:198983[A", Some(("chr17", 198983)))]
+    #[case("A[HLA-A*01:01:01:01:1000[", Some(("HLA-A*01:01:01:01", 1000)))]
+    #[case("<DEL>", None)]
+    #[case("A[17[", None)]
+    fn test_breakend_mate(#[case] allele: &str, #[case] expected: Option<(&str, u64)>) {
+        assert_eq!(
+            BreakendMate::from_allele(allele),
+            expected.map(|(contig, position)| BreakendMate {
+                contig: contig.to_string(),
+                position,
+            })
+        );
+    }
+
+    #[test]
+    fn test_unknown_contig_is_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        let (vcf_path, _) = write_indexed_copies(dir.path());
+        let mut contig_header = contig_header();
+        contig_header.update_or_add_contig("21".to_string(), None, Vec::new(), ContigSource::Track);
+        let region = Region {
+            focus: Focus {
+                contig_index: 1,
+                position: 14370,
+            },
+            half_width: 100,
+        };
+
+        let mut indexed = VariantRepository::new(&vcf_path).unwrap();
+        assert!(
+            indexed
+                .read_variants(&region, &contig_header)
+                .unwrap()
+                .intervals
+                .is_empty()
+        );
+    }
+}
@@ -14,8 +14,9 @@ use crate::{
 use gv_core::{
     error::TGVError,
     intervals::{Focus, GenomeInterval, Region},
-    message::ExportFormat,
+    message::{ExportFormat, Scroll},
     repository::Repository,
+    signal::{SignalFormat, SignalScale},
     state::State,
 };
 
@@ -46,7 +47,34 @@ impl App {
         // Gather resources before initializing the state.
         let (mut repository, contig_header) = Repository::new(&settings.core).await?;
 
-        let state = State::new(settings.core.reference.clone(), contig_header)?;
+        let n_alignments = repository.alignment_repositories.len();
+        let n_variant_samples = repository
+            .variant_repository
+            .as_ref()
+            .map(|variant_repository| variant_repository.header.sample_names().len())
+            .unwrap_or(0);
+        let n_signals = repository.signal_repositories.len();
+        let mut state = State::new(
+            settings.core.reference.clone(),
+            contig_header,
+            n_alignments,
+            n_signals,
+        )?;
+        state.min_insert_size = settings.core.min_insert_size;
+        state.max_insert_size = settings.core.max_insert_size;
+        state.allele_frequency_threshold = settings.core.allele_frequency_threshold;
+        state.quality_weighted_allele_frequency = settings.core.quality_weighted_allele_frequency;
+        state.modification_thresholds = settings.core.modification_thresholds;
+        // bedMethyl values are percentages.
+        for (signal, signal_repository) in state
+            .signals
+            .iter_mut()
+            .zip(repository.signal_repositories.iter())
+        {
+            if signal_repository.format == SignalFormat::BedMethyl {
+                signal.display.scale = SignalScale::Fixed(100.0);
+            }
+        }
         let focus = state.default_focus(&mut repository).await?;
 
         // TODO: go to foucs?
@@ -54,8 +82,8 @@ impl App {
 
         Ok(Self {
             exit: false,
-            layout: MainLayout::new(&settings),
-            alignment_view: AlignmentView::new(focus),
+            layout: MainLayout::new(&settings, n_variant_samples),
+            alignment_view: AlignmentView::new(focus, n_alignments),
             state,
             settings: settings.clone(),
             repository,
@@ -170,7 +198,9 @@ impl App {
                 Message::Core(gv_core::message::Message::Quit) => self.exit = true,
 
                 Message::Core(gv_core::message::Message::Scroll(scroll)) => {
-                    self.alignment_view.scroll(scroll, &self.state.alignment);
+                    if let Some(alignment) = self.state.alignments.get(self.alignment_view.panel) {
+                        self.alignment_view.scroll(scroll, alignment);
+                    }
                 }
 
                 Message::Core(gv_core::message::Message::Zoom(zoom)) => {
@@ -183,6 +213,9 @@ impl App {
                 Message::Core(gv_core::message::Message::SetAlignmentOption(options)) => {
                     self.state
                         .set_alignment_change(&self.alignment_view.focus, options)?;
+                    if self.state.modification_motif().is_some() {
+                        self.load_data().await?
+                    }
                 }
 
                 Message::Core(gv_core::message::Message::Message(message)) => {
@@ -200,6 +233,26 @@ impl App {
                     }
                 }
 
+                Message::Core(gv_core::message::Message::SetVariantSamples(samples)) => {
+                    self.state.set_variant_samples(samples)?;
+                    self.alignment_view.scroll_genotypes(
+                        Scroll::Position(self.alignment_view.genotype_y),
+                        self.state.variant_samples.len(),
+                    );
+                }
+
+                Message::Core(gv_core::message::Message::SetSignalDisplay(index, options)) => {
+                    self.state.set_signal_display(index, options)?;
+                }
+
+                Message::Core(gv_core::message::Message::SetJunctionMinCount(min_count)) => {
+                    self.state.junction_min_count = min_count;
+                }
+
+                Message::Core(gv_core::message::Message::SetAlignmentDensity(density)) => {
+                    self.alignment_view.density = density;
+                }
+
                 Message::SwitchScene(scene) => {
                     self.scene = scene;
                 }
@@ -210,6 +263,11 @@ impl App {
                     self.registers.current = register
                 }
                 Message::ClearAllKeyRegisters => self.registers.clear(),
+                Message::SelectAlignmentPanel(panel) => self.alignment_view.select_panel(panel),
+                Message::NextAlignmentPanel => self.alignment_view.next_panel(),
+                Message::ScrollGenotypes(scroll) => self
+                    .alignment_view
+                    .scroll_genotypes(scroll, self.state.variant_samples.len()),
             }
         }
 
@@ -223,28 +281,45 @@ impl App {
         //
         let region = self.alignment_view.region(&self.layout.main_area);
 
+        // Motif filters of base modifications and the methylation track need the reference
+        // wherever reads are shown.
+        let sequence_cache_region =
+            if self.alignment_view.zoom <= AlignmentView::MAX_ZOOM_TO_DISPLAY_SEQUENCES {
+                Some(self.alignment_view.sequence_cache_region(region.clone()))
+            } else if self.alignment_view.zoom <= AlignmentView::MAX_ZOOM_TO_DISPLAY_ALIGNMENTS
+                && (self.settings.methylation || self.state.modification_motif().is_some())
+            {
+                Some(self.alignment_view.alignment_cache_region(region.clone()))
+            } else {
+                None
+            };
+
         if let Some(sequence_service) = self.repository.sequence_service.as_mut()
-            && self.alignment_view.zoom <= AlignmentView::MAX_ZOOM_TO_DISPLAY_SEQUENCES
+            && let Some(sequence_cache_region) = sequence_cache_region
             && !self.state.sequence.has_complete_data(&region)
         {
             self.state
-                .load_sequence_data(
-                    &self.alignment_view.sequence_cache_region(region.clone()),
-                    sequence_service,
-                )
+                .load_sequence_data(&sequence_cache_region, sequence_service)
                 .await?;
         }
 
-        if let Some(alignment_repository) = self.repository.alignment_repository.as_mut()
-            && self.alignment_view.zoom <= AlignmentView::MAX_ZOOM_TO_DISPLAY_ALIGNMENTS
-            && !self.state.alignment.has_complete_data(&region)
-        {
-            self.state
-                .load_alignment_data(
-                    &self.alignment_view.alignment_cache_region(region.clone()),
-                    alignment_repository,
-                )
-                .await?;
+        if self.alignment_view.zoom <= AlignmentView::MAX_ZOOM_TO_DISPLAY_ALIGNMENTS {
+            for (index, alignment_repository) in self
+                .repository
+                .alignment_repositories
+                .iter_mut()
+                .enumerate()
+            {
+                if !self.state.alignments[index].has_complete_data(&region) {
+                    self.state
+                        .load_alignment_data(
+                            &self.alignment_view.alignment_cache_region(region.clone()),
+                            index,
+                            alignment_repository,
+                        )
+                        .await?;
+                }
+            }
         }
 
         if let Some(track_service) = self.repository.track_service.as_mut()
@@ -261,17 +336,45 @@ impl App {
         }
 
         if let Some(variant_repository) = self.repository.variant_repository.as_mut()
-            && !self.state.variant_loaded
+            && !self.state.variant_loaded_region.has_complete_data(&region)
         {
             self.state
-                .load_variant_data(&region, variant_repository)
+                .load_variant_data(
+                    &self.alignment_view.variant_cache_region(region.clone()),
+                    variant_repository,
+                )
                 .await?;
         }
 
         if let Some(bed_repository) = self.repository.bed_repository.as_mut()
-            && !self.state.bed_loaded
+            && !self.state.bed_loaded_region.has_complete_data(&region)
+        {
+            self.state
+                .load_bed_data(
+                    &self.alignment_view.bed_cache_region(region.clone()),
+                    bed_repository,
+                )
+                .await?;
+        }
+
+        // bigWig zoom levels follow the view zoom.
+        for (index, signal_repository) in self
+            .repository
+            .signal_repositories
+            .iter_mut()
+            .enumerate()
         {
-            self.state.load_bed_data(&region, bed_repository).await?;
+            let reduction_level = signal_repository.reduction_level(self.alignment_view.zoom);
+            if !self.state.signals[index].has_complete_data(&region, reduction_level) {
+                self.state
+                    .load_signal_data(
+                        &self.alignment_view.signal_cache_region(region.clone()),
+                        index,
+                        reduction_level,
+                        signal_repository,
+                    )
+                    .await?;
+            }
         }
 
         // Cytobands
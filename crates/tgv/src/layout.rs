@@ -3,22 +3,35 @@ use gv_core::{
     alignment::Alignment,
     error::TGVError,
     intervals::{Focus, GenomeInterval, Region},
-    message::{Scroll, Zoom},
+    message::{AlignmentDensity, Scroll, Zoom},
 };
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use std::path::Path;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AreaType {
     Cytoband,
     Coordinate,
-    Coverage,
-    Alignment,
+    /// Coverage of the nth alignment panel.
+    Coverage(usize),
+    /// Reads of the nth alignment panel.
+    Alignment(usize),
+    /// Splice junctions of the nth alignment panel.
+    Junction(usize),
+    /// 5mC fraction per CpG of the nth alignment panel.
+    Methylation(usize),
     Sequence,
+    /// Amino acids of the gene in view and of the genome reading frames.
+    Translation,
     GeneTrack,
     Console,
     Error,
     Variant,
+    /// One genotype row per VCF sample.
+    Genotype,
     Bed,
+    /// The nth signal track (bigWig / bedGraph).
+    Signal(usize),
 }
 
 impl AreaType {
@@ -26,7 +39,14 @@ impl AreaType {
     fn resizeable(&self) -> bool {
         // TODO: improve resizing code to allow more intuitive and flexible actions.
         match self {
-            AreaType::Alignment | AreaType::Variant | AreaType::Bed | AreaType::Error => true,
+            AreaType::Alignment(_)
+            | AreaType::Variant
+            | AreaType::Genotype
+            | AreaType::Bed
+            | AreaType::Signal(_)
+            | AreaType::Junction(_)
+            | AreaType::Methylation(_)
+            | AreaType::Error => true,
             _ => false,
         }
     }
@@ -126,7 +146,11 @@ impl LayoutNode {
         }
     }
 
-    pub fn root(settings: &Settings) -> Self {
+    /// Maximum initial height of the genotype rows. Resize to see more samples at once.
+    const MAX_GENOTYPE_ROWS: usize = 8;
+
+    /// n_variant_samples: number of samples in the VCF file.
+    pub fn root(settings: &Settings, n_variant_samples: usize) -> Self {
         let mut children = vec![];
         if settings.core.reference.needs_track() {
             children.extend(vec![LayoutNode::Area {
@@ -142,12 +166,6 @@ impl LayoutNode {
             }]);
         }
 
-        if settings.core.bam_path.is_some() {
-            children.push(LayoutNode::Area {
-                constraint: Constraint::Length(6),
-                area_type: AreaType::Coverage,
-            });
-        }
         if settings.core.vcf_path.is_some() {
             children.push(LayoutNode::Area {
                 constraint: Constraint::Length(1),
@@ -155,25 +173,78 @@ impl LayoutNode {
             });
         }
 
+        if n_variant_samples > 0 {
+            children.push(LayoutNode::Area {
+                constraint: Constraint::Length(usize::min(
+                    n_variant_samples,
+                    Self::MAX_GENOTYPE_ROWS,
+                ) as u16),
+                area_type: AreaType::Genotype,
+            });
+        }
+
         if settings.core.bed_path.is_some() {
+            // Features on the first line, names on the second.
             children.push(LayoutNode::Area {
-                constraint: Constraint::Length(1),
+                constraint: Constraint::Length(2),
                 area_type: AreaType::Bed,
             });
         }
 
-        children.extend(vec![LayoutNode::Area {
-            constraint: Constraint::Fill(1),
-            area_type: AreaType::Alignment,
-        }]);
+        // Signal tracks sit right above the reads.
+        for i in 0..settings.core.signal_paths.len() {
+            children.push(LayoutNode::Area {
+                constraint: Constraint::Length(4),
+                area_type: AreaType::Signal(i),
+            });
+        }
+
+        // Each alignment file has a coverage and an alignment panel.
+        for i in 0..settings.core.bam_paths.len() {
+            children.push(LayoutNode::Area {
+                constraint: Constraint::Length(6),
+                area_type: AreaType::Coverage(i),
+            });
+            if settings.methylation {
+                children.push(LayoutNode::Area {
+                    constraint: Constraint::Length(3),
+                    area_type: AreaType::Methylation(i),
+                });
+            }
+            if settings.junctions {
+                children.push(LayoutNode::Area {
+                    constraint: Constraint::Length(3),
+                    area_type: AreaType::Junction(i),
+                });
+            }
+            children.push(LayoutNode::Area {
+                constraint: Constraint::Fill(1),
+                area_type: AreaType::Alignment(i),
+            });
+        }
+
+        if settings.core.bam_paths.is_empty() {
+            // Fill the space between the tracks.
+            children.push(LayoutNode::Area {
+                constraint: Constraint::Fill(1),
+                area_type: AreaType::Alignment(0),
+            });
+        }
 
         if settings.core.reference.needs_sequence() {
             children.extend(vec![LayoutNode::Area {
                 constraint: Constraint::Length(1),
                 area_type: AreaType::Sequence,
             }]);
+            if settings.translation_frames > 0 {
+                // One row for the gene, one per frame.
+                children.push(LayoutNode::Area {
+                    constraint: Constraint::Length(settings.translation_frames as u16 + 1),
+                    area_type: AreaType::Translation,
+                });
+            }
         }
-        if settings.core.reference.needs_track() {
+        if settings.core.reference.needs_track() || settings.core.annotation_path.is_some() {
             children.extend(vec![LayoutNode::Area {
                 constraint: Constraint::Length(2),
                 area_type: AreaType::GeneTrack,
@@ -210,7 +281,18 @@ enum MousePosition {
 pub struct AlignmentView {
     pub focus: Focus,
     pub zoom: u64,
-    pub y: usize,
+
+    /// Top track # of each alignment panel.
+    pub ys: Vec<usize>,
+
+    /// The alignment panel that scrolling applies to.
+    pub panel: usize,
+
+    /// Top sample # of the genotype rows.
+    pub genotype_y: usize,
+
+    /// How densely alignment tracks are drawn.
+    pub density: AlignmentDensity,
 }
 
 /// States for the alignment view
@@ -218,11 +300,14 @@ impl AlignmentView {
     pub const MAX_ZOOM_TO_DISPLAY_ALIGNMENTS: u64 = 32;
     pub const MAX_ZOOM_TO_DISPLAY_SEQUENCES: u64 = 2;
 
-    pub fn new(focus: Focus) -> Self {
+    pub fn new(focus: Focus, n_panels: usize) -> Self {
         AlignmentView {
             focus,
             zoom: 1,
-            y: 0,
+            ys: vec![0; n_panels],
+            panel: 0,
+            genotype_y: 0,
+            density: AlignmentDensity::default(),
         }
     }
     const ALIGNMENT_CACHE_RATIO: u64 = 3;
@@ -252,12 +337,69 @@ impl AlignmentView {
         }
     }
 
+    const VARIANT_CACHE_RATIO: u64 = 10;
+
+    pub fn variant_cache_region(&self, region: Region) -> Region {
+        Region {
+            focus: region.focus,
+            half_width: region.half_width * Self::VARIANT_CACHE_RATIO,
+        }
+    }
+
+    const BED_CACHE_RATIO: u64 = 10;
+
+    pub fn bed_cache_region(&self, region: Region) -> Region {
+        Region {
+            focus: region.focus,
+            half_width: region.half_width * Self::BED_CACHE_RATIO,
+        }
+    }
+
+    const SIGNAL_CACHE_RATIO: u64 = 3;
+
+    pub fn signal_cache_region(&self, region: Region) -> Region {
+        Region {
+            focus: region.focus,
+            half_width: region.half_width * Self::SIGNAL_CACHE_RATIO,
+        }
+    }
+
+    /// Scroll the current panel.
     pub fn scroll(&mut self, scroll: Scroll, alignment: &Alignment) {
+        let Some(y) = self.ys.get_mut(self.panel) else {
+            return;
+        };
+        match scroll {
+            Scroll::Up(n) => *y = y.saturating_sub(n),
+            Scroll::Down(n) => *y = usize::min(y.saturating_add(n), alignment.depth()),
+            Scroll::Position(position) => *y = position,
+            Scroll::Bottom => *y = alignment.depth().saturating_sub(1),
+        }
+    }
+
+    /// Scroll the genotype rows.
+    pub fn scroll_genotypes(&mut self, scroll: Scroll, n_samples: usize) {
+        let y = &mut self.genotype_y;
         match scroll {
-            Scroll::Up(n) => self.y = self.y.saturating_sub(n),
-            Scroll::Down(n) => self.y = usize::min(self.y.saturating_add(n), alignment.depth()),
-            Scroll::Position(y) => self.y = y,
-            Scroll::Bottom => self.y = alignment.depth().saturating_sub(1),
+            Scroll::Up(n) => *y = y.saturating_sub(n),
+            Scroll::Down(n) => *y = y.saturating_add(n),
+            Scroll::Position(position) => *y = position,
+            Scroll::Bottom => *y = n_samples,
+        }
+        *y = usize::min(*y, n_samples.saturating_sub(1));
+    }
+
+    /// Set the panel that scrolling applies to.
+    pub fn select_panel(&mut self, panel: usize) {
+        if panel < self.ys.len() {
+            self.panel = panel;
+        }
+    }
+
+    /// Cycle to the next panel.
+    pub fn next_panel(&mut self) {
+        if !self.ys.is_empty() {
+            self.panel = (self.panel + 1) % self.ys.len();
         }
     }
 
@@ -308,10 +450,12 @@ impl AlignmentView {
         Ok(())
     }
 
-    /// Set the top track # of the viewing window.
+    /// Set the top track # of the viewing window of a panel.
     /// 0-based.
-    pub fn set_y(&mut self, y: usize, depth: usize) {
-        self.y = usize::min(y, depth.saturating_sub(1))
+    pub fn set_y(&mut self, panel: usize, y: usize, depth: usize) {
+        if let Some(panel_y) = self.ys.get_mut(panel) {
+            *panel_y = usize::min(y, depth.saturating_sub(1))
+        }
     }
 
     /// Check if the viewing window overlaps with [left, right].
@@ -321,16 +465,24 @@ impl AlignmentView {
         left <= self.right(area) && right >= self.left(area)
     }
 
-    /// Top track # of the viewing window.
+    /// Top track # of the viewing window of a panel.
     /// 0-based, inclusive.
-    pub fn top(&self) -> usize {
-        self.y
+    pub fn top(&self, panel: usize) -> usize {
+        self.ys.get(panel).copied().unwrap_or(0)
+    }
+
+    /// Number of tracks drawn on each line.
+    fn tracks_per_line(&self) -> usize {
+        match self.density {
+            AlignmentDensity::Squished => 2,
+            AlignmentDensity::Expanded | AlignmentDensity::Collapsed => 1,
+        }
     }
 
-    /// Bottom track # of the viewing window.
+    /// Bottom track # of the viewing window of a panel.
     /// 0-based, exclusive.
-    pub fn bottom(&self, area: &Rect) -> usize {
-        self.y + area.height as usize
+    pub fn bottom(&self, panel: usize, area: &Rect) -> usize {
+        self.top(panel) + area.height as usize * self.tracks_per_line()
     }
 
     /// Move the viewing window be within the contig range.
@@ -360,8 +512,8 @@ impl AlignmentView {
 
     /// Check if the viewing window overlaps with [top, bottom).
     /// y: 0-based.
-    pub fn overlaps_y(&self, y: usize, area: &Rect) -> bool {
-        (self.top()..self.bottom(area)).contains(&y)
+    pub fn overlaps_y(&self, panel: usize, y: usize, area: &Rect) -> bool {
+        (self.top(panel)..self.bottom(panel, area)).contains(&y)
     }
 
     /// Returns the onscreen x coordinate in the area. Example:
@@ -399,29 +551,43 @@ impl AlignmentView {
         Some((left, left + self.zoom - 1))
     }
 
-    /// Given an onscreen x position, return the genome coordinate range (1-based, inclusive) at that x location.
-    pub fn coordinate_of_onscreen_y(&self, y: u16, area: &Rect) -> Option<usize> {
-        if y < area.top() || y >= area.bottom() {
+    /// Given an onscreen y position, return the track range (0-based, inclusive) at that y location.
+    /// Squished: two tracks per line. Collapsed: lines are alleles, not tracks, so None.
+    pub fn coordinates_of_onscreen_y(
+        &self,
+        panel: usize,
+        y: u16,
+        area: &Rect,
+    ) -> Option<(usize, usize)> {
+        if y < area.top() || y >= area.bottom() || self.density == AlignmentDensity::Collapsed {
             return None;
         }
 
-        Some(self.top() + (y - area.top()) as usize)
+        let top = self.top(panel) + (y - area.top()) as usize * self.tracks_per_line();
+        Some((top, top + self.tracks_per_line() - 1))
     }
 
-    /// Returns the onscreen y coordinate in the area. Example
+    /// Returns the onscreen y coordinate in the area. Squished: two tracks share a line.
     /// y: 0-based.
-    pub fn onscreen_y_coordinate(&self, y: usize, area: &Rect) -> OnScreenCoordinate {
-        let self_top = self.top();
-        let self_bottom = self.bottom(area);
+    pub fn onscreen_y_coordinate(&self, panel: usize, y: usize, area: &Rect) -> OnScreenCoordinate {
+        let self_top = self.top(panel);
+        let self_bottom = self.bottom(panel, area);
 
         if y < self_top {
             OnScreenCoordinate::Left(self_top - y)
         } else if y >= self_bottom {
             OnScreenCoordinate::Right(y - self_bottom) // Note that this is different from the x coordinate. TODO: think about this.
         } else {
-            OnScreenCoordinate::OnScreen(y - self_top)
+            OnScreenCoordinate::OnScreen((y - self_top) / self.tracks_per_line())
         }
     }
+
+    /// Whether track y is drawn on the upper half of its line. Always true unless squished.
+    /// y: 0-based.
+    pub fn is_upper_half(&self, panel: usize, y: usize) -> bool {
+        y.saturating_sub(self.top(panel))
+            .is_multiple_of(self.tracks_per_line())
+    }
 }
 
 /// Main page layout
@@ -431,14 +597,41 @@ pub struct MainLayout {
     pub main_area: Rect,
 
     pub areas: Vec<(AreaType, Rect)>,
+
+    /// Label of each alignment panel: the alignment file name.
+    pub alignment_labels: Vec<String>,
+
+    /// Label of each signal track: the signal file name.
+    pub signal_labels: Vec<String>,
+}
+
+/// File name of a path, for track labels.
+fn file_label(path: &str) -> String {
+    Path::new(path)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string())
 }
 
 impl MainLayout {
-    pub fn new(settings: &Settings) -> Self {
+    /// n_variant_samples: number of samples in the VCF file.
+    pub fn new(settings: &Settings, n_variant_samples: usize) -> Self {
         MainLayout {
-            root: LayoutNode::root(settings),
+            root: LayoutNode::root(settings, n_variant_samples),
             main_area: Rect::default(),
             areas: Vec::new(),
+            alignment_labels: settings
+                .core
+                .bam_paths
+                .iter()
+                .map(|(bam_path, _)| file_label(bam_path))
+                .collect(),
+            signal_labels: settings
+                .core
+                .signal_paths
+                .iter()
+                .map(|signal_path| file_label(signal_path))
+                .collect(),
         }
     }
     /// Update the area. If the area size changed, terminal refresh is needed.
@@ -661,6 +854,7 @@ pub fn resize_node(
     Ok(())
 }
 
+#[derive(Debug, PartialEq)]
 pub enum OnScreenCoordinate {
     /// Coordinate on left side of the screen.
     /// The last pixel is 1.
@@ -760,3 +954,40 @@ pub fn linear_scale(
     Ok(new_start
         + (original_x as f64 / (original_length) as f64 * (new_end - new_start) as f64) as u16)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(AlignmentDensity::Expanded, Some((13, 13)), OnScreenCoordinate::OnScreen(3))]
+    #[case(AlignmentDensity::Squished, Some((16, 17)), OnScreenCoordinate::OnScreen(1))]
+    #[case(AlignmentDensity::Collapsed, None, OnScreenCoordinate::OnScreen(3))]
+    fn test_onscreen_y(
+        #[case] density: AlignmentDensity,
+        #[case] expected_tracks: Option<(usize, usize)>,
+        #[case] expected_onscreen_y: OnScreenCoordinate,
+    ) {
+        let area = Rect::new(0, 5, 20, 4);
+        let mut alignment_view = AlignmentView::new(
+            Focus {
+                contig_index: 0,
+                position: 100,
+            },
+            1,
+        );
+        alignment_view.density = density;
+        alignment_view.set_y(0, 10, 100);
+
+        // 4th line of the area
+        assert_eq!(
+            alignment_view.coordinates_of_onscreen_y(0, 8, &area),
+            expected_tracks
+        );
+        assert_eq!(
+            alignment_view.onscreen_y_coordinate(0, 13, &area),
+            expected_onscreen_y
+        );
+    }
+}
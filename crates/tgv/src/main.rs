@@ -145,6 +145,30 @@ mod tests {
         Some("covid.sorted.bam"),
         Some("-g tests/data/cache/wuhCor1/wuhCor1.2bit --offline")
     )]
+    #[case(
+        Some("covid.sorted.bam"),
+        Some("tests/data/covid.sorted.cram -g tests/data/covid.fa --offline")
+    )]
+    #[case(
+        Some("covid.sorted.bam"),
+        Some("-g tests/data/covid.fa -v tests/data/covid.vcf --offline")
+    )]
+    #[case(
+        Some("covid.sorted.bam"),
+        Some("-g tests/data/covid.fa -b tests/data/covid.bed --offline")
+    )]
+    #[case(
+        Some("covid.sorted.bam"),
+        Some("-g tests/data/covid.fa -a tests/data/covid.gff3 -r S --offline")
+    )]
+    #[case(
+        Some("covid.sorted.bam"),
+        Some("-g tests/data/covid.fa -w tests/data/covid.bedgraph -r MN908947.3:100 --offline")
+    )]
+    #[case(
+        Some("covid.sorted.bam"),
+        Some("-g tests/data/covid.fa --junctions --offline")
+    )]
     #[tokio::test]
     async fn integration_test(#[case] bam_path: Option<&str>, #[case] args: Option<&str>) {
         let snapshot_name = match (bam_path, args) {
@@ -3,7 +3,7 @@ pub use gv_core::message::{Movement, Scroll};
 use strum::Display;
 
 /// TGV messages
-#[derive(Debug, Clone, Eq, PartialEq, Display)]
+#[derive(Debug, Clone, PartialEq, Display)]
 pub enum Message {
     Core(gv_core::message::Message),
 
@@ -12,6 +12,15 @@ pub enum Message {
     SwitchKeyRegister(KeyRegisterType),
 
     ClearAllKeyRegisters,
+
+    /// Scroll the nth alignment panel from now on.
+    SelectAlignmentPanel(usize),
+
+    /// Scroll the next alignment panel from now on.
+    NextAlignmentPanel,
+
+    /// Scroll the genotype rows.
+    ScrollGenotypes(Scroll),
 }
 
 impl Message {
@@ -1,9 +1,16 @@
 use crate::{
     layout::{AlignmentView, AreaType, LayoutNode, MainLayout},
     message::{Message, Movement, Scroll},
+    rendering::{format_signal_value, translation_rows},
 };
 use crossterm::event;
-use gv_core::{alignment::BaseCoverage, error::TGVError, intervals::Region, state::State};
+use gv_core::{
+    alignment::{BaseCoverage, MethylationSite},
+    error::TGVError,
+    intervals::{GenomeInterval, Region},
+    message::AlignmentDensity,
+    state::State,
+};
 use itertools::Itertools;
 
 pub struct MouseRegister {
@@ -59,7 +66,38 @@ impl MouseRegister {
                     {
                         self.resizing = true;
                     }
-                    self.mouse_down_area_type = *area_type
+                    self.mouse_down_area_type = *area_type;
+
+                    match area_type {
+                        AreaType::Alignment(panel) | AreaType::Coverage(panel) => {
+                            messages.push(Message::SelectAlignmentPanel(*panel));
+                        }
+
+                        // Clicking a breakend (BND) goes to its mate.
+                        AreaType::Variant if !self.resizing => {
+                            if let Some((left_coordinate, right_coordinate)) =
+                                alignment_view.coordinates_of_onscreen_x(event.column, area)
+                                && let Some(mate) = state
+                                    .variants
+                                    .overlapping(
+                                        alignment_view.focus.contig_index,
+                                        left_coordinate,
+                                        right_coordinate,
+                                    )?
+                                    .into_iter()
+                                    .find_map(|variant| variant.mate.as_ref())
+                            {
+                                messages.push(
+                                    Movement::ContigNamePosition(
+                                        mate.contig.clone(),
+                                        mate.position,
+                                    )
+                                    .into(),
+                                );
+                            }
+                        }
+                        _ => {}
+                    }
                 }
             }
 
@@ -77,7 +115,7 @@ impl MouseRegister {
                 } else {
                     // move alignment
                     match self.mouse_down_area_type {
-                        AreaType::Alignment | AreaType::Coverage => {
+                        AreaType::Alignment(_) | AreaType::Coverage(_) => {
                             if event.column < self.mouse_drag_x {
                                 messages.push(Movement::Right(1).into())
                             } else if event.column > self.mouse_drag_x {
@@ -108,16 +146,38 @@ impl MouseRegister {
                     layout.get_area_type_at_position(event.column, event.row)
                 {
                     match area_type {
-                        AreaType::Alignment => {
-                            if let (Some((left_coordinate, right_coordinate)), Some(y_coordinate)) = (
-                                &alignment_view.coordinates_of_onscreen_x(event.column, area),
-                                &alignment_view.coordinate_of_onscreen_y(event.row, area),
+                        AreaType::Alignment(panel) => {
+                            if let (Some(alignment), Some((left_coordinate, right_coordinate))) = (
+                                state.alignments.get(*panel),
+                                alignment_view.coordinates_of_onscreen_x(event.column, area),
                             ) {
-                                if let Some(read) = state.alignment.read_overlapping(
-                                    *left_coordinate,
-                                    *right_coordinate,
-                                    *y_coordinate,
-                                ) {
+                                if alignment_view.density == AlignmentDensity::Collapsed {
+                                    // The consensus on the first line, other alleles below.
+                                    if let Some((coordinate, alleles)) =
+                                        alignment.alleles_in(left_coordinate, right_coordinate)
+                                        && let Some((base, count)) =
+                                            alleles.get((event.row - area.y) as usize)
+                                    {
+                                        messages.push(Message::message(format!(
+                                            "{}: {} ({} of {} reads)",
+                                            coordinate,
+                                            *base as char,
+                                            count,
+                                            alignment.coverage_at(coordinate).total
+                                        )));
+                                    }
+                                } else if let Some(read) = alignment_view
+                                    .coordinates_of_onscreen_y(*panel, event.row, area)
+                                    .and_then(|(top, bottom)| {
+                                        (top..=bottom).find_map(|y| {
+                                            alignment.read_overlapping(
+                                                left_coordinate,
+                                                right_coordinate,
+                                                y,
+                                            )
+                                        })
+                                    })
+                                {
                                     messages.push(Message::Core(
                                         gv_core::message::Message::Message(read.describe()?),
                                     ))
@@ -141,13 +201,60 @@ impl MouseRegister {
                             }
                         }
 
-                        AreaType::Coverage => {
+                        AreaType::Translation => {
                             if let Some((left_coordinate, right_coordinate)) =
                                 alignment_view.coordinates_of_onscreen_x(event.column, area)
                             {
+                                let row = (event.row - area.y) as usize;
+                                let rows = translation_rows(
+                                    state,
+                                    &alignment_view.region(area),
+                                    area.height as usize - 1,
+                                );
+                                if let Some((label, codons)) = rows.get(row) {
+                                    codons
+                                        .iter()
+                                        .filter(|codon| {
+                                            codon.positions.iter().any(|position| {
+                                                (left_coordinate..=right_coordinate)
+                                                    .contains(position)
+                                            })
+                                        })
+                                        .for_each(|codon| {
+                                            let amino_acid = format!(
+                                                "{} ({})",
+                                                codon.amino_acid as char,
+                                                String::from_utf8_lossy(&codon.bases)
+                                            );
+                                            // The first row is the reading frame of a gene.
+                                            let message = if row == 0 {
+                                                format!(
+                                                    "{} p.{}: {}",
+                                                    label, codon.number, amino_acid
+                                                )
+                                            } else {
+                                                format!(
+                                                    "Frame {}, {}-{}: {}",
+                                                    label,
+                                                    codon.positions.iter().min().unwrap(),
+                                                    codon.positions.iter().max().unwrap(),
+                                                    amino_acid
+                                                )
+                                            };
+                                            messages.push(Message::message(message));
+                                        });
+                                }
+                            }
+                        }
+
+                        AreaType::Coverage(panel) => {
+                            if let (Some(alignment), Some((left_coordinate, right_coordinate))) = (
+                                state.alignments.get(*panel),
+                                alignment_view.coordinates_of_onscreen_x(event.column, area),
+                            ) {
                                 let mut total_coverage: BaseCoverage = BaseCoverage::default();
                                 (left_coordinate..=right_coordinate).for_each(|coordinate| {
-                                    total_coverage.add(state.alignment.coverage_at(coordinate))
+                                    total_coverage.add(alignment.coverage_at(coordinate))
                                 });
 
                                 let message = if left_coordinate == right_coordinate {
@@ -164,10 +271,56 @@ impl MouseRegister {
                                 messages.push(Message::message(message));
                             }
                         }
+                        AreaType::Junction(panel) => {
+                            if let (Some(alignment), Some((left_coordinate, right_coordinate))) = (
+                                state.alignments.get(*panel),
+                                alignment_view.coordinates_of_onscreen_x(event.column, area),
+                            ) {
+                                alignment
+                                    .junctions(state.junction_min_count)
+                                    .filter(|junction| {
+                                        junction.start <= right_coordinate + 1
+                                            && junction.end + 1 >= left_coordinate
+                                    })
+                                    .for_each(|junction| {
+                                        messages.push(Message::message(junction.describe()));
+                                    });
+                            }
+                        }
+                        AreaType::Methylation(panel) => {
+                            if let (Some(alignment), Some((left_coordinate, right_coordinate))) = (
+                                state.alignments.get(*panel),
+                                alignment_view.coordinates_of_onscreen_x(event.column, area),
+                            ) {
+                                if let Some(message) = describe_methylation(
+                                    alignment.methylation_in(left_coordinate, right_coordinate),
+                                    left_coordinate,
+                                    right_coordinate,
+                                ) {
+                                    messages.push(Message::message(message));
+                                }
+                                // Allele-specific methylation: one line per read group.
+                                for (label, sites) in alignment
+                                    .group_methylation_in(left_coordinate, right_coordinate)
+                                {
+                                    if let Some(message) = describe_methylation(
+                                        sites,
+                                        left_coordinate,
+                                        right_coordinate,
+                                    ) {
+                                        messages.push(Message::message(format!(
+                                            "{}: {}",
+                                            label, message
+                                        )));
+                                    }
+                                }
+                            }
+                        }
                         AreaType::Variant => {
-                            if let Some((left_coordinate, right_coordinate)) =
-                                alignment_view.coordinates_of_onscreen_x(event.column, area)
-                            {
+                            if let (Some((left_coordinate, right_coordinate)), Some(header)) = (
+                                alignment_view.coordinates_of_onscreen_x(event.column, area),
+                                state.variant_header.as_ref(),
+                            ) {
                                 state
                                     .variants
                                     .overlapping(
@@ -177,11 +330,46 @@ impl MouseRegister {
                                     )?
                                     .into_iter()
                                     .for_each(|variant| {
-                                        messages.push(Message::message(variant.describe()));
+                                        messages.extend(
+                                            variant
+                                                .describe(header)
+                                                .into_iter()
+                                                .map(Message::message),
+                                        );
                                     });
                             }
                         }
 
+                        AreaType::Genotype => {
+                            let row = alignment_view.genotype_y + (event.row - area.y) as usize;
+                            if let (
+                                Some((left_coordinate, right_coordinate)),
+                                Some(header),
+                                Some(sample_index),
+                            ) = (
+                                alignment_view.coordinates_of_onscreen_x(event.column, area),
+                                state.variant_header.as_ref(),
+                                state.variant_samples.get(row),
+                            ) {
+                                for variant in state.variants.overlapping(
+                                    alignment_view.focus.contig_index,
+                                    left_coordinate,
+                                    right_coordinate,
+                                )? {
+                                    if let Some(description) =
+                                        variant.describe_sample(header, *sample_index)
+                                    {
+                                        messages.push(Message::message(format!(
+                                            "{}:{} {}",
+                                            state.contig_name(&alignment_view.focus)?,
+                                            variant.start(),
+                                            description
+                                        )));
+                                    }
+                                }
+                            }
+                        }
+
                         AreaType::Bed => {
                             if let Some((left_coordinate, right_coordinate)) =
                                 alignment_view.coordinates_of_onscreen_x(event.column, area)
@@ -199,14 +387,65 @@ impl MouseRegister {
                                     });
                             }
                         }
+
+                        AreaType::Signal(index) => {
+                            if let (Some(signal), Some((left_coordinate, right_coordinate))) = (
+                                state.signals.get(*index),
+                                alignment_view.coordinates_of_onscreen_x(event.column, area),
+                            ) && let Some(value) = signal.binned_values(
+                                alignment_view.focus.contig_index,
+                                &[(left_coordinate, right_coordinate)],
+                            )?[0]
+                            {
+                                let label = layout
+                                    .signal_labels
+                                    .get(*index)
+                                    .cloned()
+                                    .unwrap_or_default();
+                                let message = if left_coordinate == right_coordinate {
+                                    format!(
+                                        "{} {}: {}",
+                                        label,
+                                        left_coordinate,
+                                        format_signal_value(value)
+                                    )
+                                } else {
+                                    format!(
+                                        "{} {} - {}: {}",
+                                        label,
+                                        left_coordinate,
+                                        right_coordinate,
+                                        format_signal_value(value)
+                                    )
+                                };
+
+                                messages.push(Message::message(message));
+                            }
+                        }
                         _ => {}
                     }
                 }
             }
 
-            event::MouseEventKind::ScrollDown => messages.push(Scroll::Down(1).into()),
+            event::MouseEventKind::ScrollDown | event::MouseEventKind::ScrollUp => {
+                let scroll = if event.kind == event::MouseEventKind::ScrollDown {
+                    Scroll::Down(1)
+                } else {
+                    Scroll::Up(1)
+                };
 
-            event::MouseEventKind::ScrollUp => messages.push(Scroll::Up(1).into()),
+                // Scroll the panel under the mouse.
+                match layout.get_area_type_at_position(event.column, event.row) {
+                    Some((AreaType::Genotype, _)) => {
+                        messages.push(Message::ScrollGenotypes(scroll));
+                    }
+                    Some((AreaType::Alignment(panel) | AreaType::Coverage(panel), _)) => {
+                        messages.push(Message::SelectAlignmentPanel(*panel));
+                        messages.push(scroll.into());
+                    }
+                    _ => messages.push(scroll.into()),
+                }
+            }
 
             event::MouseEventKind::ScrollLeft => messages.push(Movement::Left(1).into()),
 
@@ -218,3 +457,22 @@ impl MouseRegister {
         Ok(messages)
     }
 }
+
+/// Pooled 5mC calls of the CpGs in [left, right]. None: no calls.
+fn describe_methylation(sites: &[MethylationSite], left: u64, right: u64) -> Option<String> {
+    let (modified, valid_coverage) = MethylationSite::pool(sites);
+    if valid_coverage == 0 {
+        return None;
+    }
+
+    let calls = format!(
+        "5mC {} / {} calls ({:.0}%)",
+        modified,
+        valid_coverage,
+        100.0 * modified as f64 / valid_coverage as f64
+    );
+    Some(match sites {
+        [site] => format!("{}: {}", site.position, calls),
+        _ => format!("{} - {}: {} at {} CpGs", left, right, calls, sites.len()),
+    })
+}
@@ -202,6 +202,10 @@ impl Registers {
                 .into_iter()
                 .map(|m| m.into())
                 .collect_vec()),
+            KeyCode::Tab => {
+                self.clear();
+                Ok(vec![Message::NextAlignmentPanel])
+            }
 
             _ => {
                 self.clear();
@@ -4,21 +4,26 @@ use crate::{
 };
 use gv_core::{
     alignment::{
-        BaseModification, ModificationType, RenderingContext, RenderingContextKind,
+        AlignedRead, Alignment, BaseModification, ModificationMotif, ModificationThresholds,
+        ModificationType, PairAnomaly, PairOrientation, RenderingContext, RenderingContextKind,
         RenderingContextModifier,
     },
     error::TGVError,
-    message::AlignmentDisplayOption,
+    message::{AlignmentColorBy, AlignmentDensity, AlignmentDisplayOption},
+    sequence::Sequence,
     state::State,
+    strand::Strand,
 };
 use ratatui::{buffer::Buffer, layout::Rect, style::{Color, Style}};
+use std::cmp::Reverse;
 use std::collections::HashMap;
 
-/// Render an alignment on the alignment area.
+/// Render the alignment of a panel on the alignment area.
 pub fn render_alignment(
     area: &Rect,
     buf: &mut Buffer,
     state: &State,
+    panel: usize,
     alignment_view: &AlignmentView,
     pallete: &Palette,
 ) -> Result<(), TGVError> {
@@ -26,89 +31,473 @@ pub fn render_alignment(
         return Ok(());
     }
 
+    let Some(alignment) = state.alignments.get(panel) else {
+        return Ok(());
+    };
+
+    if alignment_view.density == AlignmentDensity::Collapsed {
+        render_collapsed(area, buf, alignment, alignment_view, pallete);
+        return Ok(());
+    }
+
     let display_as_pairs = state
         .alignment_options
         .iter()
         .any(|option| *option == AlignmentDisplayOption::ViewAsPairs);
-    if display_as_pairs && state.alignment.read_pairs.is_none() {
+    if display_as_pairs && alignment.read_pairs.is_none() {
         return Err(TGVError::StateError(
             "Read pairs are not calculated before rendering.".to_string(),
         ));
     }
 
-    let show_modifications = state
+    // Some(motif): base modifications are shown, in the motif context if any.
+    let modification_motif = state
+        .alignment_options
+        .iter()
+        .rev()
+        .find_map(|option| match option {
+            AlignmentDisplayOption::ShowBaseModifications(motif) => Some(motif.as_ref()),
+            _ => None,
+        });
+
+    // Discordant pairs are flagged in the paired view by default.
+    let color_by = state
         .alignment_options
         .iter()
-        .any(|option| *option == AlignmentDisplayOption::ShowBaseModifications);
+        .rev()
+        .find_map(|option| match option {
+            AlignmentDisplayOption::ColorBy(color_by) => Some(color_by),
+            _ => None,
+        })
+        .or(display_as_pairs.then_some(&AlignmentColorBy::Discordant));
+    let insert_size_bounds = state.insert_size_bounds(panel);
+
+    let mark_split_reads = state
+        .alignment_options
+        .contains(&AlignmentDisplayOption::MarkSplitReads);
 
     if display_as_pairs {
-        state
-            .alignment
+        alignment
             .read_pairs
             .as_ref()
             .unwrap()
             .iter()
-            .zip(state.alignment.show_pairs.as_ref().unwrap().iter())
+            .zip(alignment.show_pairs.as_ref().unwrap().iter())
             .try_for_each(|(read_pair, show_pair)| {
                 if *show_pair {
-                    let y = state.alignment.ys[read_pair.read_1_index];
+                    let y = alignment.ys[read_pair.read_1_index];
+                    let match_color = read_color(
+                        &alignment.reads[read_pair.read_1_index],
+                        insert_size_bounds,
+                        color_by,
+                        pallete,
+                    );
                     read_pair.rendering_contexts.iter().try_for_each(|context| {
                         // Paired mode: modifications not supported yet; pass None.
-                        render_contexts(context, y, buf, alignment_view, area, pallete, None)
+                        render_contexts(
+                            context,
+                            panel,
+                            y,
+                            buf,
+                            alignment_view,
+                            area,
+                            pallete,
+                            match_color,
+                            None,
+                        )
                     })
                 } else {
                     Ok(())
                 }
             })?;
     } else {
-        state
-            .alignment
+        alignment
             .ys_index
             .iter()
             .enumerate()
             .try_for_each(|(y, read_indexes)| {
                 read_indexes.iter().try_for_each(|read_index| {
-                    let read = &state.alignment.reads[*read_index];
-                    let mods: Option<&HashMap<u64, Vec<BaseModification>>> =
-                        if show_modifications && !read.base_modifications.is_empty() {
-                            Some(&read.base_modifications)
-                        } else {
-                            None
-                        };
+                    let read = &alignment.reads[*read_index];
+                    let mods = match modification_motif {
+                        Some(motif) if !read.base_modifications.is_empty() => {
+                            Some(ModificationColoring {
+                                modifications: &read.base_modifications,
+                                motif,
+                                reference_sequence: &state.sequence,
+                                thresholds: &state.modification_thresholds,
+                            })
+                        }
+                        _ => None,
+                    };
+                    let match_color = read_color(read, insert_size_bounds, color_by, pallete);
                     read.rendering_contexts.iter().try_for_each(|context| {
-                        render_contexts(context, y, buf, alignment_view, area, pallete, mods)
+                        render_contexts(
+                            context,
+                            panel,
+                            y,
+                            buf,
+                            alignment_view,
+                            area,
+                            pallete,
+                            match_color,
+                            mods,
+                        )
                     })
                 })
             })?
     };
+
+    // Marks take a whole cell, which squished lines share between two tracks.
+    if mark_split_reads && alignment_view.density == AlignmentDensity::Expanded {
+        let (left, right) = (alignment_view.left(area), alignment_view.right(area));
+        for (y, read_indexes) in alignment.ys_index.iter().enumerate() {
+            for read_index in read_indexes {
+                let read = &alignment.reads[*read_index];
+                let has_offscreen_segment = read.supplementary_alignments().iter().any(|segment| {
+                    state
+                        .contig_header
+                        .try_get_index_by_str(&segment.contig_name)
+                        .ok()
+                        != Some(alignment.contig_index)
+                        || segment.end() < left
+                        || segment.start > right
+                });
+                if has_offscreen_segment {
+                    render_split_mark(read, panel, y, buf, alignment_view, area, pallete);
+                }
+            }
+        }
+    }
+
+    for (y, label) in alignment.group_labels.iter() {
+        if let OnScreenCoordinate::OnScreen(onscreen_y) =
+            alignment_view.onscreen_y_coordinate(panel, *y, area)
+        {
+            let style = Style::default().fg(pallete.GROUP_LABEL_COLOR);
+            let y = area.y + onscreen_y as u16;
+            buf.set_string(area.x, y, "─".repeat(area.width as usize), style);
+            buf.set_string(area.x + 1, y, format!(" {} ", label), style);
+        }
+    }
+
+    if let Some(color_by) = color_by {
+        render_pair_legend(
+            area,
+            buf,
+            &pair_legend(color_by, insert_size_bounds),
+            pallete,
+        );
+    }
+
     Ok(())
 }
 
+/// Collapsed view: the consensus of each column on the first line, other alleles below.
+/// Columns show their most variable position.
+fn render_collapsed(
+    area: &Rect,
+    buf: &mut Buffer,
+    alignment: &Alignment,
+    alignment_view: &AlignmentView,
+    pallete: &Palette,
+) {
+    for x in area.left()..area.right() {
+        let Some((left, right)) = alignment_view.coordinates_of_onscreen_x(x, area) else {
+            continue;
+        };
+        let Some((coordinate, alleles)) = alignment.alleles_in(left, right) else {
+            continue;
+        };
+        let reference_base = alignment.coverage_at(coordinate).reference_base;
+
+        for (y, (base, _)) in alleles.iter().take(area.height as usize).enumerate() {
+            let (symbol, fg) = if base.eq_ignore_ascii_case(&reference_base) {
+                ('-', pallete.MATCH_FG_COLOR)
+            } else {
+                (*base as char, pallete.mismatch_color(*base))
+            };
+            buf.set_string(
+                x,
+                area.y + y as u16,
+                symbol.to_string(),
+                Style::default().bg(pallete.MATCH_COLOR).fg(fg),
+            );
+        }
+    }
+}
+
+/// Mark the clipped end of a read whose other alignments (SA tag) are outside the view.
+fn render_split_mark(
+    read: &AlignedRead,
+    panel: usize,
+    y: usize,
+    buf: &mut Buffer,
+    alignment_view: &AlignmentView,
+    area: &Rect,
+    pallete: &Palette,
+) {
+    // The other alignments are usually the clipped part of the read.
+    let (leading_clip, trailing_clip) = read.clip_lengths();
+    let (coordinate, symbol) = if leading_clip > trailing_clip {
+        (read.start, "«")
+    } else {
+        (read.end, "»")
+    };
+
+    if let (OnScreenCoordinate::OnScreen(x), OnScreenCoordinate::OnScreen(onscreen_y)) = (
+        alignment_view.onscreen_x_coordinate(coordinate, area),
+        alignment_view.onscreen_y_coordinate(panel, y, area),
+    ) {
+        buf.set_string(
+            area.x + x as u16,
+            area.y + onscreen_y as u16,
+            symbol,
+            Style::default()
+                .bg(pallete.SPLIT_READ_MARK)
+                .fg(pallete.MATCH_FG_COLOR),
+        );
+    }
+}
+
+/// Legend of discordant pair colors in a color-by mode.
+fn pair_legend(
+    color_by: &AlignmentColorBy,
+    insert_size_bounds: Option<(u64, u64)>,
+) -> Vec<(String, PairAnomaly)> {
+    let mut legend = Vec::new();
+    if matches!(
+        color_by,
+        AlignmentColorBy::InsertSize | AlignmentColorBy::Discordant
+    ) {
+        if let Some((lower, upper)) = insert_size_bounds {
+            if upper < u64::MAX {
+                legend.push((format!(">{}bp", upper), PairAnomaly::LargeInsert));
+            }
+            if lower > 0 {
+                legend.push((format!("<{}bp", lower), PairAnomaly::SmallInsert));
+            }
+        }
+        legend.push(("other contig".to_string(), PairAnomaly::MateOnOtherContig));
+    }
+    if matches!(
+        color_by,
+        AlignmentColorBy::PairOrientation | AlignmentColorBy::Discordant
+    ) {
+        for orientation in [
+            PairOrientation::RL,
+            PairOrientation::LL,
+            PairOrientation::RR,
+        ] {
+            legend.push((
+                orientation.to_string(),
+                PairAnomaly::Orientation(orientation),
+            ));
+        }
+    }
+    legend
+}
+
+/// Render the legend at the top right of the alignment area.
+fn render_pair_legend(
+    area: &Rect,
+    buf: &mut Buffer,
+    legend: &[(String, PairAnomaly)],
+    pallete: &Palette,
+) {
+    let width: usize = legend.iter().map(|(label, _)| label.len() + 2).sum();
+    if legend.is_empty() || width > area.width as usize {
+        return;
+    }
+
+    let mut x = area.right() - width as u16;
+    for (label, anomaly) in legend {
+        let text = format!(" {} ", label);
+        buf.set_string(
+            x,
+            area.y,
+            &text,
+            Style::default()
+                .bg(pallete.pair_anomaly_color(anomaly))
+                .fg(pallete.MATCH_FG_COLOR),
+        );
+        x += text.len() as u16;
+    }
+}
+
+/// Background color of the aligned bases of a read.
+fn read_color(
+    read: &AlignedRead,
+    insert_size_bounds: Option<(u64, u64)>,
+    color_by: Option<&AlignmentColorBy>,
+    pallete: &Palette,
+) -> Color {
+    let Some(color_by) = color_by else {
+        return pallete.MATCH_COLOR;
+    };
+
+    match color_by {
+        AlignmentColorBy::Strand => strand_color(&read.strand(), pallete),
+
+        AlignmentColorBy::FirstOfPairStrand => {
+            // Second reads take the strand of the mate.
+            let strand = if read.flags.is_segmented() && read.flags.is_last_segment() {
+                if read.flags.is_mate_reverse_complemented() {
+                    Strand::Reverse
+                } else {
+                    Strand::Forward
+                }
+            } else {
+                read.strand()
+            };
+            strand_color(&strand, pallete)
+        }
+
+        AlignmentColorBy::InsertSize => read
+            .insert_size_anomaly(insert_size_bounds)
+            .map_or(pallete.MATCH_COLOR, |anomaly| {
+                pallete.pair_anomaly_color(&anomaly)
+            }),
+
+        AlignmentColorBy::PairOrientation => read
+            .orientation_anomaly()
+            .map_or(pallete.MATCH_COLOR, |anomaly| {
+                pallete.pair_anomaly_color(&anomaly)
+            }),
+
+        AlignmentColorBy::Discordant => read
+            .pair_anomaly(insert_size_bounds)
+            .map_or(pallete.MATCH_COLOR, |anomaly| {
+                pallete.pair_anomaly_color(&anomaly)
+            }),
+
+        AlignmentColorBy::MappingQuality => match read.mapping_quality() {
+            255 => pallete.MATCH_COLOR,
+            mapping_quality => {
+                pallete.MAPPING_QUALITY[usize::min(
+                    mapping_quality as usize / 15,
+                    pallete.MAPPING_QUALITY.len() - 1,
+                )]
+            }
+        },
+
+        AlignmentColorBy::Tag(tag) => match <&[u8; 2]>::try_from(tag.as_bytes())
+            .ok()
+            .and_then(|tag| read.tag_value(tag))
+        {
+            Some(value) => tag_value_color(&value, pallete),
+            None => pallete.MATCH_COLOR,
+        },
+    }
+}
+
+fn strand_color(strand: &Strand, pallete: &Palette) -> Color {
+    match strand {
+        Strand::Forward => pallete.READ_FORWARD,
+        Strand::Reverse => pallete.READ_REVERSE,
+    }
+}
+
+/// A stable color for each tag value.
+fn tag_value_color(value: &str, pallete: &Palette) -> Color {
+    let hash = value.bytes().fold(0usize, |hash, byte| {
+        hash.wrapping_mul(31).wrapping_add(byte as usize)
+    });
+    pallete.TAG_VALUES[hash % pallete.TAG_VALUES.len()]
+}
+
 fn render_contexts(
     context: &RenderingContext,
+    panel: usize,
     y: usize,
     buf: &mut Buffer,
     alignment_view: &AlignmentView,
     area: &Rect,
     pallete: &Palette,
-    base_modifications: Option<&HashMap<u64, Vec<BaseModification>>>,
+    match_color: Color,
+    base_modifications: Option<ModificationColoring>,
 ) -> Result<(), TGVError> {
-    if let Some(onscreen_contexts) =
-        get_read_rendering_info(context, y, alignment_view, area, pallete, base_modifications)?
-    {
+    let onscreen_y = match alignment_view.onscreen_y_coordinate(panel, y, area) {
+        OnScreenCoordinate::OnScreen(y_start) => y_start as u16,
+        _ => return Ok(()),
+    };
+
+    if let Some(onscreen_contexts) = get_read_rendering_info(
+        context,
+        onscreen_y,
+        alignment_view,
+        area,
+        pallete,
+        match_color,
+        base_modifications,
+    )? {
+        let upper_half = alignment_view.is_upper_half(panel, y);
         for onscreen_context in onscreen_contexts {
-            buf.set_string(
-                area.x + onscreen_context.x,
-                area.y + onscreen_context.y,
-                onscreen_context.string,
-                onscreen_context.style,
-            );
+            match alignment_view.density {
+                AlignmentDensity::Squished => {
+                    let Some(color) = half_block_color(&onscreen_context.style, pallete) else {
+                        continue;
+                    };
+                    // The view can span one more base than the area width; clip to the area.
+                    let left = area.x + onscreen_context.x;
+                    let right = u16::min(
+                        left.saturating_add(onscreen_context.string.chars().count() as u16),
+                        area.right(),
+                    );
+                    for x in left..right {
+                        set_half_block(buf, x, area.y + onscreen_context.y, color, upper_half);
+                    }
+                }
+                _ => {
+                    buf.set_string(
+                        area.x + onscreen_context.x,
+                        area.y + onscreen_context.y,
+                        onscreen_context.string,
+                        onscreen_context.style,
+                    );
+                }
+            }
         }
     }
 
     Ok(())
 }
 
+/// Color of a cell in the squished view: the mismatch or gap if any, otherwise the read background.
+fn half_block_color(style: &Style, pallete: &Palette) -> Option<Color> {
+    match (style.fg, style.bg) {
+        (fg, None) => fg,
+        (fg, Some(bg)) if bg == pallete.background => fg,
+        (Some(fg), Some(_)) if fg != pallete.MATCH_FG_COLOR => Some(fg),
+        (_, bg) => bg,
+    }
+}
+
+/// Squished view: draw a track on the upper or lower half of a cell, keeping the other half.
+/// Similar to the half-block trick in render_sequence_at_2x.
+fn set_half_block(buf: &mut Buffer, x: u16, y: u16, color: Color, upper_half: bool) {
+    let Some(cell) = buf.cell_mut((x, y)) else {
+        return;
+    };
+    match (cell.symbol(), upper_half) {
+        ("▀", true) | ("▄", false) => {
+            cell.set_fg(color);
+        }
+        ("▀", false) => {
+            cell.set_bg(color);
+        }
+        ("▄", true) => {
+            let lower = cell.fg;
+            cell.set_symbol("▀").set_fg(color).set_bg(lower);
+        }
+        (_, true) => {
+            cell.set_symbol("▀").set_fg(color);
+        }
+        (_, false) => {
+            cell.set_symbol("▄").set_fg(color);
+        }
+    }
+}
+
 struct OnScreenRenderingContext {
     x: u16,
     y: u16,
@@ -118,42 +507,54 @@ struct OnScreenRenderingContext {
     style: Style,
 }
 
+/// Base modifications of a read and how to color them.
+#[derive(Clone, Copy)]
+struct ModificationColoring<'a> {
+    modifications: &'a HashMap<u64, Vec<BaseModification>>,
+
+    /// Only calls in this reference context are colored.
+    motif: Option<&'a ModificationMotif>,
+
+    reference_sequence: &'a Sequence,
+
+    thresholds: &'a ModificationThresholds,
+}
+
 /// Return the background color for a reference position from the modification map.
-/// Returns `None` when the position has no modification data.
-fn mod_bg_at(
-    pos: u64,
-    mods: &HashMap<u64, Vec<BaseModification>>,
-    pallete: &Palette,
-) -> Option<Color> {
-    mods.get(&pos).and_then(|mod_list| {
-        // Prefer 5mC, then 5hmC, then 6mA.
+/// Returns `None` when the position has no modification data, or none in the motif context.
+fn mod_bg_at(pos: u64, mods: &ModificationColoring, pallete: &Palette) -> Option<Color> {
+    mods.modifications.get(&pos).and_then(|mod_list| {
+        // The most probable modification. Ties: prefer 5mC, then the MM tag order.
         mod_list
             .iter()
-            .find(|m| matches!(m.modification_type, ModificationType::FiveMC))
-            .or_else(|| {
-                mod_list
-                    .iter()
-                    .find(|m| matches!(m.modification_type, ModificationType::FiveHMC))
+            .filter(|m| {
+                mods.motif.is_none_or(|motif| {
+                    motif.matches(pos, &m.modification_type, mods.reference_sequence)
+                })
+            })
+            .min_by_key(|m| {
+                (
+                    Reverse(m.probability),
+                    m.modification_type != ModificationType::FiveMC,
+                )
+            })
+            .map(|m| {
+                pallete.modification_color(&m.modification_type, m.probability, mods.thresholds)
             })
-            .or_else(|| mod_list.first())
-            .map(|m| pallete.modification_color(&m.modification_type, m.probability))
     })
 }
 
-/// Get rendering info for an aligned read context.
+/// Get rendering info for an aligned read context on an onscreen row.
+/// match_color: background of the aligned bases.
 fn get_read_rendering_info(
     context: &RenderingContext,
-    y: usize,
+    onscreen_y: u16,
     alignment_view: &AlignmentView,
     area: &Rect,
     pallete: &Palette,
-    base_modifications: Option<&HashMap<u64, Vec<BaseModification>>>,
+    match_color: Color,
+    base_modifications: Option<ModificationColoring>,
 ) -> Result<Option<Vec<OnScreenRenderingContext>>, TGVError> {
-    let onscreen_y = match alignment_view.onscreen_y_coordinate(y, area) {
-        OnScreenCoordinate::OnScreen(y_start) => y_start as u16,
-        _ => return Ok(None),
-    };
-
     let start_onscreen_coordinate = alignment_view.onscreen_x_coordinate(context.start, area);
     let end_onscreen_coordinate = alignment_view.onscreen_x_coordinate(context.end, area);
 
@@ -175,7 +576,7 @@ fn get_read_rendering_info(
                 // Per-position rendering so each cell can have its own
                 // modification background colour.
                 for pos in context.start..=context.end {
-                    let bg = mod_bg_at(pos, mods, pallete).unwrap_or(pallete.MATCH_COLOR);
+                    let bg = mod_bg_at(pos, &mods, pallete).unwrap_or(match_color);
                     if let OnScreenCoordinate::OnScreen(cell_x) =
                         alignment_view.onscreen_x_coordinate(pos, area)
                     {
@@ -192,9 +593,7 @@ fn get_read_rendering_info(
                     x: onscreen_x,
                     y: onscreen_y,
                     string: "-".repeat(length as usize),
-                    style: Style::default()
-                        .bg(pallete.MATCH_COLOR)
-                        .fg(pallete.MATCH_FG_COLOR),
+                    style: Style::default().bg(match_color).fg(pallete.MATCH_FG_COLOR),
                 });
             }
         }
@@ -277,11 +676,8 @@ fn get_read_rendering_info(
                     // When showing modifications, preserve the modification
                     // background at this position while changing only the fg.
                     let base_style = if let Some(mods) = base_modifications {
-                        let bg =
-                            mod_bg_at(*coordinate, mods, pallete).unwrap_or(pallete.MATCH_COLOR);
-                        Style::default()
-                            .bg(bg)
-                            .fg(pallete.mismatch_color(*base))
+                        let bg = mod_bg_at(*coordinate, &mods, pallete).unwrap_or(match_color);
+                        Style::default().bg(bg).fg(pallete.mismatch_color(*base))
                     } else {
                         output
                             .first()
@@ -315,3 +711,73 @@ fn get_read_rendering_info(
 
     Ok(Some(output))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rendering::DARK_THEME;
+    use gv_core::intervals::Focus;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(AlignmentColorBy::Strand, Some((100, 500)), vec![])]
+    #[case(AlignmentColorBy::InsertSize, Some((100, 500)), vec![">500bp", "<100bp", "other contig"])]
+    #[case(AlignmentColorBy::InsertSize, None, vec!["other contig"])]
+    #[case(AlignmentColorBy::InsertSize, Some((0, 500)), vec![">500bp", "other contig"])]
+    #[case(AlignmentColorBy::PairOrientation, None, vec!["RL", "LL", "RR"])]
+    #[case(AlignmentColorBy::Discordant, Some((100, u64::MAX)), vec!["<100bp", "other contig", "RL", "LL", "RR"])]
+    fn test_pair_legend(
+        #[case] color_by: AlignmentColorBy,
+        #[case] insert_size_bounds: Option<(u64, u64)>,
+        #[case] expected: Vec<&str>,
+    ) {
+        assert_eq!(
+            pair_legend(&color_by, insert_size_bounds)
+                .into_iter()
+                .map(|(label, _)| label)
+                .collect::<Vec<_>>(),
+            expected
+        );
+    }
+
+    #[rstest]
+    #[case(50)]
+    #[case(80)]
+    #[case(81)]
+    fn test_squished_read_fills_area(#[case] width: u16) {
+        let area = Rect::new(0, 0, width, 2);
+        let mut buf = Buffer::empty(area);
+        let mut alignment_view = AlignmentView::new(
+            Focus {
+                contig_index: 0,
+                position: 1000,
+            },
+            1,
+        );
+        alignment_view.density = AlignmentDensity::Squished;
+        // Ends on the last base of the view, which is one column past the area at even widths.
+        let context = RenderingContext {
+            start: 1,
+            end: alignment_view.right(&area),
+            kind: RenderingContextKind::Match,
+            modifiers: Vec::new(),
+        };
+
+        render_contexts(
+            &context,
+            0,
+            0,
+            &mut buf,
+            &alignment_view,
+            &area,
+            &DARK_THEME,
+            DARK_THEME.MATCH_COLOR,
+            None,
+        )
+        .unwrap();
+
+        for x in area.left()..area.right() {
+            assert_eq!(buf[(x, 0)].symbol(), "▀");
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use crate::{
     layout::AlignmentView,
-    rendering::{colors::Palette, intervals::render_simple_intervals},
+    rendering::{colors::Palette, intervals::render_simple_intervals, track::render_genes},
 };
 use gv_core::{alignment::Alignment, error::TGVError, intervals::GenomeInterval, state::State};
 use ratatui::{buffer::Buffer, layout::Rect};
@@ -17,17 +17,35 @@ pub fn render_bed(
         state
             .bed_intervals
             .overlapping(region.contig_index(), region.start(), region.end())?; // FIXME: wasteful calculation here
+
+    // Stranded records (BED6+) are drawn like gene models. The rest are plain intervals.
+    let (genes, intervals): (Vec<_>, Vec<_>) = intervals
+        .into_iter()
+        .map(|interval| (interval.gene(), interval))
+        .partition(|(gene, _)| gene.is_some());
+
     if !intervals.is_empty() {
-        let first_color_index = intervals[0].index % 2;
+        let first_color_index = intervals[0].1.index % 2;
         render_simple_intervals(
             area,
             buf,
-            intervals,
+            intervals
+                .into_iter()
+                .map(|(_, interval)| interval)
+                .collect(),
             alignment_view,
             vec![pallete.BED1, pallete.BED2],
             first_color_index,
         )?;
     }
 
+    render_genes(
+        area,
+        buf,
+        genes.iter().filter_map(|(gene, _)| gene.as_ref()),
+        alignment_view,
+        pallete,
+    );
+
     Ok(())
 }
@@ -1,5 +1,6 @@
+use gv_core::alignment::{PairAnomaly, PairOrientation};
 use gv_core::cytoband::Stain;
-use gv_core::modification::ModificationType;
+use gv_core::modification::{ModificationThresholds, ModificationType};
 use ratatui::style::{Color, palette::tailwind};
 
 // Background
@@ -31,6 +32,24 @@ pub struct Palette {
     pub MISMATCH_T: Color,
     pub MISMATCH_N: Color,
 
+    // Read colors (color-by)
+    pub READ_FORWARD: Color,
+    pub READ_REVERSE: Color,
+    pub INSERT_SIZE_LARGE: Color,
+    pub INSERT_SIZE_SMALL: Color,
+    pub MATE_OTHER_CONTIG: Color,
+    pub PAIR_RL: Color,
+    pub PAIR_LL: Color,
+    pub PAIR_RR: Color,
+    /// MAPQ colors from low to high.
+    pub MAPPING_QUALITY: [Color; 5],
+    /// Colors of tag values.
+    pub TAG_VALUES: [Color; 8],
+    /// Read group labels
+    pub GROUP_LABEL_COLOR: Color,
+    /// Reads with other alignments (SA tag) outside the view
+    pub SPLIT_READ_MARK: Color,
+
     // Coverage
     pub COVERAGE_ALT: Color,
     pub COVERAGE_A: Color,
@@ -63,12 +82,46 @@ pub struct Palette {
     pub BASE_T: Color,
     pub BASE_N: Color,
 
+    // Translation: alternating codon backgrounds, start (Met) and stop codons
+    pub CODON_1: Color,
+    pub CODON_2: Color,
+    pub START_CODON: Color,
+    pub STOP_CODON: Color,
+
     // Intervals
     pub VCF1: Color,
     pub VCF2: Color,
     pub BED1: Color,
     pub BED2: Color,
 
+    // Signal tracks (bigWig / bedGraph)
+    pub SIGNAL: Color,
+    /// Heatmap colors from low to high values.
+    pub SIGNAL_HEATMAP: [Color; 5],
+
+    // Splice junctions, by transcript strand
+    pub JUNCTION_FORWARD: Color,
+    pub JUNCTION_REVERSE: Color,
+    pub JUNCTION_UNKNOWN: Color,
+
+    // Genotypes
+    pub GENOTYPE_HOM_REF: Color,
+    pub GENOTYPE_HET: Color,
+    pub GENOTYPE_HOM_ALT: Color,
+    pub GENOTYPE_NO_CALL: Color,
+    /// Phasing marks
+    pub GENOTYPE_FG_COLOR: Color,
+
+    // Structural variants
+    pub SV_DEL: Color,
+    pub SV_DUP: Color,
+    pub SV_INV: Color,
+    pub SV_INS: Color,
+    pub SV_CNV: Color,
+    pub SV_BND: Color,
+    /// Structural variant glyphs
+    pub SV_FG_COLOR: Color,
+
     // Gene track
     pub EXON_BACKGROUND_COLOR: Color,
     pub EXON_FOREGROUND_COLOR: Color,
@@ -87,6 +140,8 @@ pub struct Palette {
     pub MOD_5HMC: Color,
     /// 6mA: purple
     pub MOD_6MA: Color,
+    /// BrdU and other modifications: pink
+    pub MOD_OTHER: Color,
 }
 
 impl Palette {
@@ -121,21 +176,44 @@ impl Palette {
         }
     }
 
+    pub fn pair_anomaly_color(&self, anomaly: &PairAnomaly) -> Color {
+        match anomaly {
+            PairAnomaly::LargeInsert => self.INSERT_SIZE_LARGE,
+            PairAnomaly::SmallInsert => self.INSERT_SIZE_SMALL,
+            PairAnomaly::MateOnOtherContig => self.MATE_OTHER_CONTIG,
+            PairAnomaly::Orientation(PairOrientation::RL) => self.PAIR_RL,
+            PairAnomaly::Orientation(PairOrientation::LL) => self.PAIR_LL,
+            PairAnomaly::Orientation(PairOrientation::RR) => self.PAIR_RR,
+            PairAnomaly::Orientation(PairOrientation::LR) => self.MATCH_COLOR,
+        }
+    }
+
     /// Returns the background color for a base modification given its type and
     /// probability (0-255 from the ML tag, where 255 = fully modified).
-    pub fn modification_color(&self, mod_type: &ModificationType, probability: u8) -> Color {
+    /// Methylcytosines are graded by probability with the thresholds;
+    /// other modifications below the low threshold are unmodified.
+    pub fn modification_color(
+        &self,
+        mod_type: &ModificationType,
+        probability: u8,
+        thresholds: &ModificationThresholds,
+    ) -> Color {
         match mod_type {
-            ModificationType::FiveMC => {
-                if probability >= 179 {
+            ModificationType::FiveMC | ModificationType::FourMC => {
+                if probability >= thresholds.high {
                     self.MOD_5MC_HIGH
-                } else if probability >= 77 {
+                } else if probability >= thresholds.low {
                     self.MOD_5MC_MED
                 } else {
                     self.MOD_5MC_LOW
                 }
             }
-            ModificationType::FiveHMC => self.MOD_5HMC,
+            _ if probability < thresholds.low => self.MOD_5MC_LOW,
+            ModificationType::FiveHMC | ModificationType::FiveFC | ModificationType::FiveCaC => {
+                self.MOD_5HMC
+            }
             ModificationType::SixMA => self.MOD_6MA,
+            ModificationType::BrdU | ModificationType::Other(_) => self.MOD_OTHER,
         }
     }
 
@@ -196,6 +274,35 @@ pub const DARK_THEME: Palette = Palette {
     MISMATCH_T: Color::LightYellow,
     MISMATCH_N: Color::LightMagenta,
 
+    // Read colors (same hues as IGV)
+    READ_FORWARD: tailwind::RED.c800,
+    READ_REVERSE: tailwind::BLUE.c800,
+    INSERT_SIZE_LARGE: tailwind::RED.c700,
+    INSERT_SIZE_SMALL: tailwind::BLUE.c700,
+    MATE_OTHER_CONTIG: tailwind::AMBER.c700,
+    PAIR_RL: tailwind::GREEN.c700,
+    PAIR_LL: tailwind::TEAL.c700,
+    PAIR_RR: tailwind::INDIGO.c700,
+    MAPPING_QUALITY: [
+        tailwind::GRAY.c900,
+        tailwind::GRAY.c800,
+        tailwind::GRAY.c700,
+        tailwind::GRAY.c600,
+        tailwind::GRAY.c500,
+    ],
+    TAG_VALUES: [
+        tailwind::RED.c700,
+        tailwind::BLUE.c700,
+        tailwind::GREEN.c700,
+        tailwind::AMBER.c700,
+        tailwind::PURPLE.c700,
+        tailwind::TEAL.c700,
+        tailwind::PINK.c700,
+        tailwind::LIME.c700,
+    ],
+    GROUP_LABEL_COLOR: tailwind::GRAY.c400,
+    SPLIT_READ_MARK: tailwind::FUCHSIA.c600,
+
     COVERAGE_ALT: Color::Red,
     COVERAGE_A: Color::LightRed,
     COVERAGE_T: Color::LightYellow,
@@ -226,12 +333,49 @@ pub const DARK_THEME: Palette = Palette {
     BASE_T: tailwind::YELLOW.c300,
     BASE_N: tailwind::GRAY.c300,
 
+    // Translation
+    CODON_1: tailwind::SLATE.c300,
+    CODON_2: tailwind::SLATE.c400,
+    START_CODON: tailwind::GREEN.c500,
+    STOP_CODON: tailwind::RED.c500,
+
     // Intervals
     VCF1: tailwind::VIOLET.c900,
     VCF2: tailwind::VIOLET.c400,
     BED1: tailwind::INDIGO.c900,
     BED2: tailwind::INDIGO.c400,
 
+    // Signal tracks
+    SIGNAL: tailwind::SKY.c500,
+    SIGNAL_HEATMAP: [
+        tailwind::SKY.c950,
+        tailwind::SKY.c800,
+        tailwind::SKY.c600,
+        tailwind::SKY.c400,
+        tailwind::SKY.c200,
+    ],
+
+    // Splice junctions (same hues as IGV sashimi plots)
+    JUNCTION_FORWARD: tailwind::RED.c400,
+    JUNCTION_REVERSE: tailwind::BLUE.c400,
+    JUNCTION_UNKNOWN: tailwind::GRAY.c400,
+
+    // Genotypes (same hues as IGV)
+    GENOTYPE_HOM_REF: tailwind::GRAY.c500,
+    GENOTYPE_HET: tailwind::BLUE.c600,
+    GENOTYPE_HOM_ALT: tailwind::CYAN.c400,
+    GENOTYPE_NO_CALL: tailwind::GRAY.c800,
+    GENOTYPE_FG_COLOR: tailwind::WHITE,
+
+    // Structural variants
+    SV_DEL: tailwind::RED.c700,
+    SV_DUP: tailwind::BLUE.c700,
+    SV_INV: tailwind::AMBER.c600,
+    SV_INS: tailwind::FUCHSIA.c700,
+    SV_CNV: tailwind::GREEN.c700,
+    SV_BND: tailwind::PINK.c600,
+    SV_FG_COLOR: tailwind::WHITE,
+
     // Gene track
     EXON_BACKGROUND_COLOR: tailwind::BLUE.c800,
     EXON_FOREGROUND_COLOR: tailwind::WHITE,
@@ -245,4 +389,5 @@ pub const DARK_THEME: Palette = Palette {
     MOD_5MC_LOW: tailwind::BLUE.c700,      // <30% — cool blue (unmethylated)
     MOD_5HMC: tailwind::TEAL.c500,         // 5hmC — teal
     MOD_6MA: tailwind::PURPLE.c600,        // 6mA — purple
+    MOD_OTHER: tailwind::PINK.c500,        // BrdU and other codes — pink
 };
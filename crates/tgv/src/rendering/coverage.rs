@@ -15,15 +15,20 @@ use gv_core::{
     state::State,
 };
 
-use crate::{layout::AlignmentView, rendering::Palette};
+use crate::{
+    layout::{AlignmentView, MainLayout},
+    rendering::Palette,
+};
 const MIN_AREA_WIDTH: u16 = 2;
 const MIN_AREA_HEIGHT: u16 = 1;
 
-/// Render the coverage barplot.
+/// Render the coverage barplot of an alignment panel, labelled with the file name.
 pub fn render_coverage(
     area: &Rect,
     buf: &mut Buffer,
     state: &State,
+    panel: usize,
+    layout: &MainLayout,
     alignment_view: &AlignmentView,
     palette: &Palette,
 ) -> Result<(), TGVError> {
@@ -31,28 +36,47 @@ pub fn render_coverage(
         return Ok(());
     }
 
-    let mut binned_coverage = calculate_binned_coverage(
-        &state.alignment,
+    let Some(alignment) = state.alignments.get(panel) else {
+        return Ok(());
+    };
+
+    let binned_coverage = calculate_binned_coverage(
+        alignment,
         alignment_view.left(area),
         alignment_view.right(area),
         area.width as usize,
+        state.allele_frequency_threshold,
+        state.quality_weighted_allele_frequency,
     )?;
 
-    println!("{}", binned_coverage[0].len());
-
     let y_max: usize = round_up_max_coverage(
         (0..binned_coverage[0].len())
-            .map(|i| binned_coverage[0][i] + binned_coverage[1][i])
+            .map(|i| binned_coverage.iter().map(|stack| stack[i]).sum())
             .max()
             .unwrap_or(0),
     );
-    StackedSparkline::default()
-        .add_data(binned_coverage.remove(0), palette.COVERAGE_ALT)
-        .add_data(binned_coverage.remove(0), palette.COVERAGE_TOTAL)
+    let colors = [
+        palette.COVERAGE_A,
+        palette.COVERAGE_C,
+        palette.COVERAGE_G,
+        palette.COVERAGE_T,
+        palette.COVERAGE_ALT,
+        palette.COVERAGE_TOTAL,
+    ];
+    binned_coverage
+        .into_iter()
+        .zip(colors)
+        .fold(StackedSparkline::default(), |sparkline, (stack, color)| {
+            sparkline.add_data(stack, color)
+        })
         .max(y_max)
         .render(*area, buf);
 
-    buf.set_string(area.x, area.y, format!("[0-{}]", y_max,), Style::default());
+    let label = match layout.alignment_labels.get(panel) {
+        Some(label) => format!("[0-{}] {}", y_max, label),
+        None => format!("[0-{}]", y_max),
+    };
+    buf.set_string(area.x, area.y, label, Style::default());
 
     Ok(())
 }
@@ -121,12 +145,20 @@ fn get_linear_space(left: u64, right: u64, n_bins: usize) -> Result<Vec<(u64, u6
 }
 
 /// Calculate the binned coverage in [left_bound, right_bound].
+/// Positions whose alt-allele frequency is at least allele_frequency_threshold are highlighted.
 /// 1-based, inclusive.
+///
+/// Stacks, bottom to top:
+/// 0-3: A, C, G, T of highlighted positions (1x zoom)
+/// 4: alt alleles of highlighted positions (binned)
+/// 5: everything else
 fn calculate_binned_coverage(
     alignment: &Alignment,
     left: u64,
     right: u64,
     n_bins: usize,
+    allele_frequency_threshold: f64,
+    quality_weighted: bool,
 ) -> Result<Vec<Vec<usize>>, TGVError> {
     if right < left {
         return Err(TGVError::ValueError("Right is less than left".to_string()));
@@ -139,21 +171,17 @@ fn calculate_binned_coverage(
     if right - left + 1 == n_bins as u64 {
         // 1x zoom. Not need to calulate binned coverage.
 
-        // Stack 0: alt allele if above a threshold
-        // Stack 1: non-alt alleles
-        let mut output = vec![vec![0; n_bins]; 2];
+        let mut output = vec![vec![0; n_bins]; 6];
         (left..right + 1).enumerate().for_each(|(i, x)| {
             let coverage = alignment.coverage_at(x);
-            let max_alt_depth = coverage.max_alt_depth().unwrap_or(0);
 
-            if max_alt_depth * BaseCoverage::MAX_DISPLAY_ALLELE_FREQUENCY_RECIPROCOL
-                > coverage.total
-            {
-                output[0][i] = max_alt_depth;
-                output[1][i] = coverage.total - max_alt_depth;
+            if is_highlighted(coverage, allele_frequency_threshold, quality_weighted) {
+                for (stack, base) in [b'A', b'C', b'G', b'T'].into_iter().enumerate() {
+                    output[stack][i] = coverage.count(base);
+                }
+                output[5][i] = coverage.N;
             } else {
-                output[0][i] = 0;
-                output[1][i] = coverage.total;
+                output[5][i] = coverage.total;
             }
         });
         return Ok(output);
@@ -161,20 +189,38 @@ fn calculate_binned_coverage(
 
     let linear_space = get_linear_space(left, right, n_bins)?;
 
-    let mut output = vec![vec![0; linear_space.len()]; 2];
+    let mut output = vec![vec![0; linear_space.len()]; 6];
     linear_space
         .into_iter()
         .enumerate()
         .for_each(|(i, (bin_left, bin_right))| {
-            (bin_left..bin_right + 1).for_each(|x| output[1][i] += alignment.coverage_at(x).total);
+            (bin_left..bin_right + 1).for_each(|x| {
+                let coverage = alignment.coverage_at(x);
+                if is_highlighted(coverage, allele_frequency_threshold, quality_weighted) {
+                    let alt_depth = coverage.total - coverage.count(coverage.reference_base);
+                    output[4][i] += alt_depth;
+                    output[5][i] += coverage.total - alt_depth;
+                } else {
+                    output[5][i] += coverage.total;
+                }
+            });
         });
 
     Ok(output)
 }
 
+fn is_highlighted(
+    coverage: &BaseCoverage,
+    allele_frequency_threshold: f64,
+    quality_weighted: bool,
+) -> bool {
+    coverage.total > 0
+        && coverage.alt_allele_frequency(quality_weighted) >= allele_frequency_threshold
+}
+
 /// Stacked sparkline with multiple colors.
 /// TODO: move this to a separate crate.
-struct StackedSparkline {
+pub(crate) struct StackedSparkline {
     max: Option<usize>,
 
     data: Vec<(Vec<usize>, Color)>, // bottom, top
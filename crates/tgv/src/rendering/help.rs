@@ -29,8 +29,13 @@ pub fn render_help(area: &Rect, buf: &mut Buffer) -> Result<(), TGVError> {
  |y / p|           Move left / right faster
  |w / b / W / B|   Beginning of the next exon / last exon / next gene / last gene
  |e / ge / E / gE| End of the next exon / last exon / next gene / last gene
+ |] / [|           Beginning of the next / last BED feature
+ |J / gJ|          Beginning of the next / last splice junction
+ |a / ga|          Next / last position with an alt allele above the frequency threshold
+ |S / <num>S|      Go to the first / _num_-th other alignment (SA tag) of the read at the cursor
  |z / o|           Zoom in / out
  |{{ / }}|         Move up / down faster
+ |<TAB>|           Scroll the next alignment panel (with multiple alignment files)
 
  |<num><key>|      Repeat movements. Examples:
      - 5h: Move right by 5 bases
@@ -40,16 +45,49 @@ pub fn render_help(area: &Rect, buf: &mut Buffer) -> Result<(), TGVError> {
  |:_pos_|          Go to position on same contig.       Example: :1000
  |:_contig_:_pos_| Go to position on a contig.          Example: 17:7572659
  |:_gene_|         Go to _gene_                         Example: :KRAS
- |filter base(_pos_) = _base_|   Filter by base         Example: :filter base(123)=A
- |:paired|                       View reads as pairs
- |:mod|                          Color bases by 5mC/5hmC modification probability (MM/ML tags)
+ |filter _expression_|           Filter reads           Example: :filter mapq>=20 and not flag&1024
+     Terms: base(_pos_)=_base_, mapq>=_n_ (also <=, >, <, =), flag&_mask_, flag&_mask_=_value_,
+            flag=_n_, strand=+/-, tag(_tag_)=_value_, start(_a_,_b_), end(_a_,_b_), overlaps(_a_,_b_)
+     Combine with and / or / not and parentheses.
+ |sort _options_|               Sort reads             Example: :sort base, mapq desc
+     Options: base(_pos_), strand(_pos_), start, mapq, readname, length, insertsize,
+              matecontig, readgroup, sample, readorder, tag(_tag_). Append desc to reverse.
+ |:paired|                       View reads as pairs    Example: :paired sort base
+ |color-by _mode_|               Color reads            Example: :color-by tag(HP)
+     Modes: strand, firstofpair, insertsize, pairorientation, discordant, mapq, tag(_tag_).
+     :paired colors discordant pairs (insert size, mate contig, LL/RR/RL) by default.
+ |group-by _mode_|               Stack reads in groups  Example: :group-by hp sort base
+     Modes: hp, readgroup, sample, strand, matecontig, base(_pos_) (base at _pos_, default: current position), tag(_tag_).
+     With --methylation, the methylation track shows each group; the status bar shows the 5mC difference in view.
+ |:splits|                       Mark reads with other alignments (SA tag) outside the view with « / »
+ |:display _mode_|               Track density          Example: :display squished
+     Modes: expanded (one track per line), squished (two tracks per line), collapsed (consensus and other alleles).
+ |:mod [_motif_]|                Color bases by modification probability (MM/ML tags)  Example: :mod CHG
+     With a motif (IUPAC codes, e.g. CG, CHG, GATC), only calls in that reference context are colored.
  |:clear|                        Reset alignment display options
+ |:samples _names_|              Show / reorder VCF genotype rows  Example: :samples child,mother,father
+     :samples with no names shows all samples. Scroll the genotype rows with the mouse.
+ |:mate|                         Go to the mate of the nearest breakend (BND). Clicking a BND also works.
+ |:signal [_n_] _options_|       Draw signal tracks     Example: :signal 2 heatmap log
+     Options: bar, heatmap, auto, max _value_, log, linear. Without _n_: all signal tracks.
+ |:junctions [_n_]|              Show splice junctions with at least _n_ reads (tgv --junctions)
  |:export html <path>|           Save current view as HTML (colours preserved)
  |:export svg  <path>|           Save current view as SVG vector image
  |:export text <path>|           Save current view as plain text
 
  CLI options:
  |--min-mapq <N>|  Skip reads with mapping quality below N (default: 0 = no filter)
+ |--min-base-quality <N>|  Don't count bases with base quality below N in the coverage (default: 0 = no filter)
+ |--min-insert-size <N>, --max-insert-size <N>|  Expected insert sizes (default: estimated from the loaded pairs)
+ |--max-reads-per-window <N> --window <BP>|  Keep at most N reads starting in each BP-wide window (default window: 50)
+     Sampling is deterministic by read name, so mates stay together. Coverage counts all reads, unless filtered.
+ |--allele-frequency-threshold <F>|  Highlight coverage columns with alt-allele frequency >= F (default: 0.2)
+ |--quality-weighted|  Weight allele frequencies by base quality
+ |--methylation|  Show the 5mC fraction of each CpG from the loaded reads below the coverage
+ |-w <pileup.bed.gz>|  Show precomputed modkit bedMethyl 5mC calls at any zoom level (.tbi index: read by region)
+ |--translate|  Show amino acids under the reference when zoomed in: the gene's reading frame and 3 genome frames
+ |--six-frame|  Also translate the 3 reverse frames
+ |--mod-low <F> --mod-high <F>|  Modification calls below F are unmodified, at least F modified (default: 0.3, 0.7)
  ",
         env!("CARGO_PKG_VERSION")
     );
@@ -0,0 +1,161 @@
+use ratatui::{buffer::Buffer, layout::Rect, style::Style};
+
+use gv_core::{alignment::SpliceJunction, error::TGVError, state::State, strand::Strand};
+
+use crate::{
+    layout::{AlignmentView, OnScreenCoordinate},
+    rendering::Palette,
+};
+
+const MIN_AREA_WIDTH: u16 = 2;
+const MIN_AREA_HEIGHT: u16 = 1;
+
+/// Render the splice junctions of an alignment panel as arcs from the donor to the acceptor exon,
+/// labelled with the number of supporting reads.
+/// Junctions with the most reads are placed first. Junctions that don't fit in the area are not drawn.
+pub fn render_junctions(
+    area: &Rect,
+    buf: &mut Buffer,
+    state: &State,
+    panel: usize,
+    alignment_view: &AlignmentView,
+    palette: &Palette,
+) -> Result<(), TGVError> {
+    if area.width < MIN_AREA_WIDTH || area.height < MIN_AREA_HEIGHT {
+        return Ok(());
+    }
+
+    let Some(alignment) = state.alignments.get(panel) else {
+        return Ok(());
+    };
+    if alignment.contig_index != alignment_view.focus.contig_index {
+        return Ok(());
+    }
+
+    let (left, right) = (alignment_view.left(area), alignment_view.right(area));
+    let mut junctions: Vec<&SpliceJunction> = alignment
+        .junctions(state.junction_min_count)
+        .filter(|junction| junction.start <= right + 1 && junction.end + 1 >= left)
+        .collect();
+    junctions.sort_by_key(|junction| std::cmp::Reverse(junction.count));
+
+    // Arc ends: the last base of the donor exon and the first base of the acceptor exon.
+    let ends: Vec<(Column, Column)> = junctions
+        .iter()
+        .map(|junction| {
+            (
+                column(alignment_view, junction.start.saturating_sub(1), area),
+                column(alignment_view, junction.end + 1, area),
+            )
+        })
+        .collect();
+    let rows = stack_spans(
+        &ends
+            .iter()
+            .map(|(start, end)| (start.x, end.x))
+            .collect::<Vec<_>>(),
+        area.height as usize,
+    );
+
+    for ((junction, (start, end)), row) in junctions.iter().zip(ends.iter()).zip(rows) {
+        let Some(row) = row else {
+            continue;
+        };
+        let color = match junction.strand {
+            Some(Strand::Forward) => palette.JUNCTION_FORWARD,
+            Some(Strand::Reverse) => palette.JUNCTION_REVERSE,
+            None => palette.JUNCTION_UNKNOWN,
+        };
+        let style = Style::default().fg(color);
+        let y = area.y + row as u16;
+
+        for x in start.x..=end.x {
+            let symbol = if x == start.x && start.onscreen {
+                "╭"
+            } else if x == end.x && end.onscreen {
+                "╮"
+            } else {
+                "─"
+            };
+            buf[(area.x + x as u16, y)]
+                .set_symbol(symbol)
+                .set_style(style);
+        }
+
+        // Read count in the middle of the arc.
+        let label = junction.count.to_string();
+        if end.x - start.x > label.len() + 1 {
+            let x = (start.x + end.x + 1 - label.len()) / 2;
+            buf.set_string(area.x + x as u16, y, label, style);
+        }
+    }
+
+    Ok(())
+}
+
+/// Column of an arc end, clamped to the area.
+struct Column {
+    x: usize,
+
+    /// Whether the arc end is on screen.
+    onscreen: bool,
+}
+
+fn column(alignment_view: &AlignmentView, coordinate: u64, area: &Rect) -> Column {
+    let last = area.width as usize - 1;
+    match alignment_view.onscreen_x_coordinate(coordinate, area) {
+        OnScreenCoordinate::OnScreen(x) if x <= last => Column { x, onscreen: true },
+        OnScreenCoordinate::Left(_) => Column {
+            x: 0,
+            onscreen: false,
+        },
+        _ => Column {
+            x: last,
+            onscreen: false,
+        },
+    }
+}
+
+/// Row of each onscreen span [start, end] (inclusive), filling top rows first.
+/// Spans on the same row are separated by at least one column. None: the span doesn't fit in n_rows.
+fn stack_spans(spans: &[(usize, usize)], n_rows: usize) -> Vec<Option<usize>> {
+    let mut rows: Vec<Vec<(usize, usize)>> = Vec::new();
+    spans
+        .iter()
+        .map(|(start, end)| {
+            let fits = |row: &Vec<(usize, usize)>| {
+                row.iter().all(|(s, e)| *end + 1 < *s || *start > *e + 1)
+            };
+            match rows.iter().position(fits) {
+                Some(i) => {
+                    rows[i].push((*start, *end));
+                    Some(i)
+                }
+                None if rows.len() < n_rows => {
+                    rows.push(vec![(*start, *end)]);
+                    Some(rows.len() - 1)
+                }
+                None => None,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(vec![(0, 10), (12, 20), (5, 15)], 2, vec![Some(0), Some(0), Some(1)])]
+    #[case(vec![(0, 10), (11, 20)], 2, vec![Some(0), Some(1)])]
+    #[case(vec![(0, 10), (5, 15), (8, 9)], 2, vec![Some(0), Some(1), None])]
+    #[case(vec![(0, 10), (5, 15), (16, 20)], 1, vec![Some(0), None, Some(0)])]
+    fn test_stack_spans(
+        #[case] spans: Vec<(usize, usize)>,
+        #[case] n_rows: usize,
+        #[case] expected: Vec<Option<usize>>,
+    ) {
+        assert_eq!(stack_spans(&spans, n_rows), expected);
+    }
+}
@@ -0,0 +1,127 @@
+use ratatui::{buffer::Buffer, layout::Rect, style::Style};
+
+use gv_core::{
+    alignment::{MethylationSite, ModificationThresholds, ModificationType},
+    error::TGVError,
+    state::State,
+};
+
+use crate::{layout::AlignmentView, rendering::Palette};
+
+const MIN_AREA_WIDTH: u16 = 2;
+const MIN_AREA_HEIGHT: u16 = 1;
+
+const BAR_SYMBOLS: [&str; 9] = [" ", "▁", "▂", "▃", "▄", "▅", "▆", "▇", "█"];
+
+/// Render the 5mC fraction of the CpGs in an alignment panel as bars, coloured like modified bases.
+/// Columns spanning several CpGs show the pooled fraction of their calls.
+/// When reads are grouped (e.g. by haplotype), each group gets its own band of rows.
+pub fn render_methylation(
+    area: &Rect,
+    buf: &mut Buffer,
+    state: &State,
+    panel: usize,
+    alignment_view: &AlignmentView,
+    palette: &Palette,
+) -> Result<(), TGVError> {
+    if area.width < MIN_AREA_WIDTH || area.height < MIN_AREA_HEIGHT {
+        return Ok(());
+    }
+
+    let Some(alignment) = state.alignments.get(panel) else {
+        return Ok(());
+    };
+    if alignment.contig_index != alignment_view.focus.contig_index {
+        return Ok(());
+    }
+
+    // CpGs are only known where the reference is loaded.
+    if !state
+        .sequence
+        .has_complete_data(&alignment_view.region(area))
+    {
+        buf.set_string(area.x, area.y, "[5mC: no reference]", Style::default());
+        return Ok(());
+    }
+
+    let group_labels = alignment.methylation_groups().collect::<Vec<_>>();
+    if group_labels.is_empty() {
+        render_bars(
+            area,
+            buf,
+            alignment_view,
+            palette,
+            &state.modification_thresholds,
+            &|left, right| alignment.methylation_in(left, right),
+        );
+        buf.set_string(area.x, area.y, "[5mC 0-100%]", Style::default());
+        return Ok(());
+    }
+
+    // Groups that don't fit are not shown.
+    let band_height = u16::max(area.height / group_labels.len() as u16, 1);
+    for (i, label) in group_labels.iter().enumerate() {
+        let y = area.y + i as u16 * band_height;
+        if y >= area.bottom() {
+            break;
+        }
+        let band = Rect::new(area.x, y, area.width, band_height);
+        render_bars(
+            &band,
+            buf,
+            alignment_view,
+            palette,
+            &state.modification_thresholds,
+            &|left, right| {
+                alignment
+                    .group_methylation_in(left, right)
+                    .nth(i)
+                    .map_or(&[][..], |(_, sites)| sites)
+            },
+        );
+        buf.set_string(band.x, band.y, format!("[5mC {}]", label), Style::default());
+    }
+
+    Ok(())
+}
+
+/// Draw the pooled 5mC fraction of the sites in each column as bars.
+/// sites_in: sites in [left, right].
+fn render_bars<'a>(
+    area: &Rect,
+    buf: &mut Buffer,
+    alignment_view: &AlignmentView,
+    palette: &Palette,
+    thresholds: &ModificationThresholds,
+    sites_in: &dyn Fn(u64, u64) -> &'a [MethylationSite],
+) {
+    let n_levels = area.height as usize * 8;
+    for x in area.left()..area.right() {
+        let Some((left, right)) = alignment_view.coordinates_of_onscreen_x(x, area) else {
+            continue;
+        };
+        let (modified, valid_coverage) = MethylationSite::pool(sites_in(left, right));
+        if valid_coverage == 0 {
+            continue;
+        }
+
+        let fraction = modified as f64 / valid_coverage as f64;
+        let style = Style::default().fg(palette.modification_color(
+            &ModificationType::FiveMC,
+            (fraction * 255.0).round() as u8,
+            thresholds,
+        ));
+
+        // At least one level, so that unmethylated CpGs are visible.
+        let level = usize::max((fraction * n_levels as f64).round() as usize, 1);
+        for row in 0..area.height {
+            let filled = usize::min(level.saturating_sub(row as usize * 8), 8);
+            if filled == 0 {
+                break;
+            }
+            buf[(x, area.bottom() - 1 - row)]
+                .set_symbol(BAR_SYMBOLS[filled])
+                .set_style(style);
+        }
+    }
+}
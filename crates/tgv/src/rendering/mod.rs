@@ -9,9 +9,13 @@ mod cytoband;
 pub mod export;
 mod help;
 mod intervals;
+mod junction;
+mod methylation;
 mod sequence;
+mod signal;
 mod status_bar;
 mod track;
+mod translation;
 mod variants;
 pub use alignment::render_alignment;
 pub use bed::render_bed;
@@ -22,10 +26,14 @@ pub use coordinate::render_coordinates;
 pub use coverage::render_coverage;
 pub use cytoband::render_cytobands;
 pub use help::render_help;
+pub use junction::render_junctions;
+pub use methylation::render_methylation;
 pub use sequence::render_sequence;
+pub use signal::{format_signal_value, render_signal};
 pub use status_bar::render_status_bar;
 pub use track::render_track;
-pub use variants::render_variants;
+pub use translation::{render_translation, translation_rows};
+pub use variants::{render_genotypes, render_variants};
 
 use crate::{
     layout::{AlignmentView, AreaType, MainLayout},
@@ -53,14 +61,24 @@ pub fn render_main(
         match area_type {
             AreaType::Cytoband => render_cytobands(rect, buf, state, alignment_view, pallete)?,
             AreaType::Coordinate => render_coordinates(rect, buf, alignment_view, state)?,
-            AreaType::Coverage => {
+            AreaType::Coverage(panel) => {
                 if alignment_view.zoom <= AlignmentView::MAX_ZOOM_TO_DISPLAY_ALIGNMENTS {
-                    render_coverage(rect, buf, state, alignment_view, pallete)?;
+                    render_coverage(rect, buf, state, *panel, layout, alignment_view, pallete)?;
                 }
             }
-            AreaType::Alignment => {
+            AreaType::Alignment(panel) => {
                 if alignment_view.zoom <= AlignmentView::MAX_ZOOM_TO_DISPLAY_ALIGNMENTS {
-                    render_alignment(rect, buf, state, alignment_view, pallete)?;
+                    render_alignment(rect, buf, state, *panel, alignment_view, pallete)?;
+                }
+            }
+            AreaType::Junction(panel) => {
+                if alignment_view.zoom <= AlignmentView::MAX_ZOOM_TO_DISPLAY_ALIGNMENTS {
+                    render_junctions(rect, buf, state, *panel, alignment_view, pallete)?;
+                }
+            }
+            AreaType::Methylation(panel) => {
+                if alignment_view.zoom <= AlignmentView::MAX_ZOOM_TO_DISPLAY_ALIGNMENTS {
+                    render_methylation(rect, buf, state, *panel, alignment_view, pallete)?;
                 }
             }
             AreaType::Sequence => {
@@ -68,6 +86,11 @@ pub fn render_main(
                     render_sequence(rect, buf, state, alignment_view, pallete)?;
                 }
             }
+            AreaType::Translation => {
+                if alignment_view.zoom == 1 {
+                    render_translation(rect, buf, state, alignment_view, pallete)?;
+                }
+            }
             AreaType::GeneTrack => {
                 render_track(rect, buf, state, alignment_view, pallete)?;
             }
@@ -77,10 +100,14 @@ pub fn render_main(
                 }
             }
             AreaType::Error => {
-                render_status_bar(rect, buf, state, alignment_view)?;
+                render_status_bar(rect, buf, state, layout, alignment_view)?;
             }
             AreaType::Variant => render_variants(rect, buf, state, alignment_view, pallete)?,
+            AreaType::Genotype => render_genotypes(rect, buf, state, alignment_view, pallete)?,
             AreaType::Bed => render_bed(rect, buf, state, alignment_view, pallete)?,
+            AreaType::Signal(index) => {
+                render_signal(rect, buf, state, *index, layout, alignment_view, pallete)?
+            }
         };
     }
     Ok(())
@@ -0,0 +1,184 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Style},
+    widgets::Widget,
+};
+
+use gv_core::{
+    error::TGVError,
+    signal::{SignalDisplay, SignalScale, SignalStyle},
+    state::State,
+};
+
+use crate::{
+    layout::{AlignmentView, MainLayout},
+    rendering::{Palette, coverage::StackedSparkline},
+};
+
+const MIN_AREA_WIDTH: u16 = 2;
+const MIN_AREA_HEIGHT: u16 = 1;
+
+/// Render the nth signal track as a bar chart or a heatmap, labelled with the value range and the file name.
+pub fn render_signal(
+    area: &Rect,
+    buf: &mut Buffer,
+    state: &State,
+    index: usize,
+    layout: &MainLayout,
+    alignment_view: &AlignmentView,
+    palette: &Palette,
+) -> Result<(), TGVError> {
+    if area.width < MIN_AREA_WIDTH || area.height < MIN_AREA_HEIGHT {
+        return Ok(());
+    }
+
+    let Some(signal) = state.signals.get(index) else {
+        return Ok(());
+    };
+
+    // One bin per screen column.
+    let bins: Vec<(u64, u64)> = (area.left()..area.right())
+        .filter_map(|x| alignment_view.coordinates_of_onscreen_x(x, area))
+        .collect();
+    let values = signal.binned_values(alignment_view.focus.contig_index, &bins)?;
+
+    let max = match signal.display.scale {
+        SignalScale::Auto => round_up_max_signal(
+            values
+                .iter()
+                .flatten()
+                .fold(0.0, |max, value| f64::max(max, *value)),
+        ),
+        SignalScale::Fixed(max) => max,
+    };
+
+    // Fraction of the plotted range. None: no data.
+    let fractions: Vec<Option<f64>> = values
+        .iter()
+        .map(|value| value.map(|value| plotted_fraction(&signal.display, value, max)))
+        .collect();
+
+    match signal.display.style {
+        SignalStyle::Bar => {
+            let n_levels = area.height as usize * 8;
+            StackedSparkline::default()
+                .add_data(
+                    fractions
+                        .iter()
+                        .map(|fraction| {
+                            (fraction.unwrap_or(0.0) * n_levels as f64).round() as usize
+                        })
+                        .collect(),
+                    palette.SIGNAL,
+                )
+                .max(n_levels)
+                .render(*area, buf);
+        }
+        SignalStyle::Heatmap => {
+            for (i, fraction) in fractions.iter().enumerate() {
+                let Some(fraction) = fraction else {
+                    continue;
+                };
+                let color = heatmap_color(&palette.SIGNAL_HEATMAP, *fraction);
+                for y in area.top()..area.bottom() {
+                    buf[(area.left() + i as u16, y)]
+                        .set_symbol(" ")
+                        .set_style(Style::default().bg(color));
+                }
+            }
+        }
+    }
+
+    let mut label = format!("[0-{}", format_signal_value(max));
+    if signal.display.log_scale {
+        label += " log";
+    }
+    label += "]";
+    if let Some(name) = layout.signal_labels.get(index) {
+        label += &format!(" {}", name);
+    }
+    buf.set_string(area.x, area.y, label, Style::default());
+
+    Ok(())
+}
+
+/// Height of a value relative to the maximum, in [0, 1], after the display transformation.
+fn plotted_fraction(display: &SignalDisplay, value: f64, max: f64) -> f64 {
+    let max = display.transform(max);
+    if max <= 0.0 {
+        return 0.0;
+    }
+    f64::min(display.transform(value) / max, 1.0)
+}
+
+fn heatmap_color(colors: &[Color], fraction: f64) -> Color {
+    let level = (fraction * colors.len() as f64) as usize;
+    colors[usize::min(level, colors.len() - 1)]
+}
+
+/// Round up the maximum signal to two significant digits.
+fn round_up_max_signal(x: f64) -> f64 {
+    if x <= 0.0 {
+        return 1.0;
+    }
+
+    // Scale by powers of ten in the exact direction to avoid floating point errors (e.g. 7.5 / 0.1).
+    let exponent = x.log10().floor() as i32 - 1;
+    let factor = 10f64.powi(exponent.abs());
+    if exponent < 0 {
+        (x * factor - 1e-9).ceil() / factor
+    } else {
+        (x / factor - 1e-9).ceil() * factor
+    }
+}
+
+/// Signal value with at most two decimals.
+pub fn format_signal_value(x: f64) -> String {
+    format!("{:.2}", x)
+        .trim_end_matches('0')
+        .trim_end_matches('.')
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(0.0, 1.0)]
+    #[case(7.5, 7.5)]
+    #[case(10.25, 11.0)]
+    #[case(0.734, 0.74)]
+    #[case(1234.0, 1300.0)]
+    fn test_round_up_max_signal(#[case] input: f64, #[case] expected: f64) {
+        assert_eq!(round_up_max_signal(input), expected);
+    }
+
+    #[rstest]
+    #[case(false, 5.0, 10.0, 0.5)]
+    #[case(false, 20.0, 10.0, 1.0)]
+    #[case(true, 9.0, 99.0, 0.5)]
+    #[case(false, -1.0, 10.0, 0.0)]
+    fn test_plotted_fraction(
+        #[case] log_scale: bool,
+        #[case] value: f64,
+        #[case] max: f64,
+        #[case] expected: f64,
+    ) {
+        let display = SignalDisplay {
+            log_scale,
+            ..Default::default()
+        };
+        assert_eq!(plotted_fraction(&display, value, max), expected);
+    }
+
+    #[rstest]
+    #[case(10.0, "10")]
+    #[case(0.74, "0.74")]
+    #[case(7.5, "7.5")]
+    fn test_format_signal_value(#[case] input: f64, #[case] expected: &str) {
+        assert_eq!(format_signal_value(input), expected);
+    }
+}
@@ -1,14 +1,17 @@
-use gv_core::{error::TGVError, intervals::GenomeInterval, state::State};
+use gv_core::{
+    alignment::MethylationSite, error::TGVError, intervals::GenomeInterval, state::State,
+};
 
 use itertools::Itertools;
 use ratatui::{buffer::Buffer, layout::Rect, style::Style};
 
-use crate::layout::AlignmentView;
+use crate::layout::{AlignmentView, AreaType, MainLayout};
 
 pub fn render_status_bar(
     area: &Rect,
     buf: &mut Buffer,
     state: &State,
+    layout: &MainLayout,
     alignment_view: &AlignmentView,
 ) -> Result<(), TGVError> {
     if area.width < 1 || area.height < 2 {
@@ -35,15 +38,85 @@ pub fn render_status_bar(
         state.contig_name(&alignment_view.focus)?,
         alignment_view.focus.position
     );
-    let mut y_coordinate_string = if state.alignment.depth() == 0 {
+    // Y coordinate of the panel being scrolled.
+    let panel = alignment_view.panel;
+    let alignment = state.alignments.get(panel);
+    let depth = alignment.map(|alignment| alignment.depth()).unwrap_or(0);
+    let mut y_coordinate_string = if depth == 0 {
         "".to_string()
     } else {
-        let y = alignment_view.top() + 1; // Change to 1-base
-        let depth = state.alignment.depth();
+        let y = alignment_view.top(panel) + 1; // Change to 1-base
         let percent = y * 100 / depth;
         format!("{}% ({} / {})", percent, y, depth)
     };
 
+    if state.alignments.len() > 1
+        && let Some(label) = layout.alignment_labels.get(panel)
+    {
+        y_coordinate_string = format!("{}: {}", label, y_coordinate_string);
+    }
+
+    // Downsampling
+    if let Some(alignment) = alignment
+        && alignment.downsampled_reads() > 0
+    {
+        y_coordinate_string = format!(
+            "{} [downsampled: {} / {} reads dropped]",
+            y_coordinate_string,
+            alignment.downsampled_reads(),
+            alignment.reads.len() + alignment.downsampled_reads()
+        );
+    }
+
+    // Without the reference in view, modification calls are shown unfiltered.
+    if let Some(motif) = state.modification_motif()
+        && alignment.is_some()
+        && !state
+            .sequence
+            .has_complete_data(&alignment_view.region(&layout.main_area))
+    {
+        y_coordinate_string = format!(
+            "{} [mod {}: no reference, calls not filtered]",
+            y_coordinate_string, motif
+        );
+    }
+
+    // Allele-specific methylation: 5mC of each read group in view.
+    if let Some(alignment) = alignment
+        && let Some((_, alignment_area)) = layout
+            .areas
+            .iter()
+            .find(|(area_type, _)| *area_type == AreaType::Alignment(panel))
+    {
+        let fractions = alignment
+            .group_methylation_in(
+                alignment_view.left(alignment_area),
+                alignment_view.right(alignment_area),
+            )
+            .filter_map(|(label, sites)| {
+                let (modified, valid_coverage) = MethylationSite::pool(sites);
+                (valid_coverage > 0).then(|| (label, modified as f64 / valid_coverage as f64))
+            })
+            .collect_vec();
+        if let Some((min, max)) = fractions
+            .iter()
+            .map(|(_, fraction)| *fraction)
+            .minmax()
+            .into_option()
+            && fractions.len() > 1
+        {
+            y_coordinate_string = format!(
+                "{} [5mC {}, diff {:.0}%]",
+                y_coordinate_string,
+                fractions
+                    .iter()
+                    .map(|(label, fraction)| format!("{} {:.0}%", label, fraction * 100.0))
+                    .join(", "),
+                (max - min) * 100.0
+            );
+        }
+    }
+
     // Alignment options
 
     if !state.alignment_options.is_empty() {
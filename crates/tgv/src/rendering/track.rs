@@ -36,9 +36,28 @@ pub fn render_track(
         return Ok(());
     }
 
+    render_genes(
+        area,
+        buf,
+        state.track.genes().iter(),
+        alignment_view,
+        pallete,
+    );
+
+    Ok(())
+}
+
+/// Render gene models: features on the first line, labels on the second line.
+pub fn render_genes<'a>(
+    area: &Rect,
+    buf: &mut Buffer,
+    genes: impl Iterator<Item = &'a Gene>,
+    alignment_view: &AlignmentView,
+    pallete: &Palette,
+) {
     let mut right_most_label_onscreen_x = 0;
-    for feature in state.track.genes().iter() {
-        for context in get_rendering_info(&alignment_view, area, feature, pallete) {
+    for feature in genes {
+        for context in get_rendering_info(alignment_view, area, feature, pallete) {
             buf.set_string(
                 context.x + area.x,
                 area.y,
@@ -60,8 +79,6 @@ pub fn render_track(
             }
         }
     }
-
-    Ok(())
 }
 
 const MIN_GENE_ON_SCREEN_LENGTH_TO_SHOW_EXONS: usize = 10;
@@ -0,0 +1,123 @@
+use crate::{
+    layout::{AlignmentView, OnScreenCoordinate},
+    rendering::colors::Palette,
+};
+use gv_core::{
+    error::TGVError,
+    feature::Gene,
+    intervals::{GenomeInterval, Region},
+    state::State,
+    strand::Strand,
+    translation::{Codon, frame_codons, gene_codons},
+};
+use ratatui::{buffer::Buffer, layout::Rect, style::Style};
+
+const MIN_AREA_WIDTH: u16 = 3;
+const MIN_AREA_HEIGHT: u16 = 1;
+
+/// Genome reading frames in row order: +1, +2, +3, then -1, -2, -3.
+const FRAMES: [(u64, Strand); 6] = [
+    (0, Strand::Forward),
+    (1, Strand::Forward),
+    (2, Strand::Forward),
+    (0, Strand::Reverse),
+    (1, Strand::Reverse),
+    (2, Strand::Reverse),
+];
+
+/// Translated rows in region: the reading frame of the coding gene in view (empty if none),
+/// then n_frames genome frames. Rows are labelled by the gene name or the frame, e.g. "+1".
+pub fn translation_rows(
+    state: &State,
+    region: &Region,
+    n_frames: usize,
+) -> Vec<(String, Vec<Codon>)> {
+    let (left, right) = (region.start(), region.end());
+    if state.sequence.contig_index != region.contig_index() {
+        return Vec::new();
+    }
+
+    let gene = coding_gene_in_view(state, region);
+    let mut rows = vec![(
+        gene.map(|gene| gene.name.clone()).unwrap_or_default(),
+        gene.map(|gene| gene_codons(gene, &state.sequence, left, right))
+            .unwrap_or_default(),
+    )];
+    rows.extend(FRAMES.iter().take(n_frames).map(|(frame, strand)| {
+        (
+            format!("{}{}", strand, frame + 1),
+            frame_codons(&state.sequence, left, right, *frame, strand),
+        )
+    }));
+    rows
+}
+
+/// The coding gene at the focus, or the first coding gene in view.
+fn coding_gene_in_view<'a>(state: &'a State, region: &Region) -> Option<&'a Gene> {
+    if state.track.contig_index != region.contig_index() {
+        return None;
+    }
+    state
+        .track
+        .get_gene_at(region.focus.position)
+        .filter(|gene| gene.is_coding())
+        .or_else(|| {
+            state
+                .track
+                .get_features_overlapping(region)
+                .into_iter()
+                .find(|gene| gene.is_coding())
+        })
+}
+
+/// Render amino acids under the reference sequence: one row for the reading frame of the gene in
+/// view, then one row per genome frame. Codons are shaded alternately, with start (M) and stop (*)
+/// codons highlighted. The amino acid is drawn at the middle base of its codon.
+pub fn render_translation(
+    area: &Rect,
+    buf: &mut Buffer,
+    state: &State,
+    alignment_view: &AlignmentView,
+    pallete: &Palette,
+) -> Result<(), TGVError> {
+    if area.width < MIN_AREA_WIDTH || area.height < MIN_AREA_HEIGHT {
+        return Ok(());
+    }
+
+    let region = alignment_view.region(area);
+    let n_frames = area.height as usize - 1;
+    for (row, (_, codons)) in translation_rows(state, &region, n_frames)
+        .iter()
+        .enumerate()
+    {
+        for codon in codons {
+            let style =
+                Style::default()
+                    .fg(pallete.SEQUENCE_FOREGROUND_COLOR)
+                    .bg(if codon.is_stop() {
+                        pallete.STOP_CODON
+                    } else if codon.is_start() {
+                        pallete.START_CODON
+                    } else if codon.number % 2 == 0 {
+                        pallete.CODON_2
+                    } else {
+                        pallete.CODON_1
+                    });
+
+            for (i, position) in codon.positions.iter().enumerate() {
+                if let OnScreenCoordinate::OnScreen(x) =
+                    alignment_view.onscreen_x_coordinate(*position, area)
+                {
+                    let symbol = if i == 1 {
+                        (codon.amino_acid as char).to_string()
+                    } else {
+                        " ".to_string()
+                    };
+                    buf.set_string(area.x + x as u16, area.y + row as u16, symbol, style);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
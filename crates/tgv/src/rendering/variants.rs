@@ -1,10 +1,19 @@
-use gv_core::{error::TGVError, intervals::GenomeInterval, state::State};
+use gv_core::{
+    error::TGVError,
+    intervals::GenomeInterval,
+    state::State,
+    variant::{SampleGenotype, StructuralVariantType, Variant, Zygosity},
+};
 
 use crate::{
-    layout::AlignmentView,
+    layout::{AlignmentView, OnScreenCoordinate},
     rendering::{colors::Palette, intervals::render_simple_intervals},
 };
-use ratatui::{buffer::Buffer, layout::Rect};
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Style},
+};
 
 pub fn render_variants(
     area: &Rect,
@@ -18,12 +27,37 @@ pub fn render_variants(
         state
             .variants
             .overlapping(region.contig_index(), region.start(), region.end())?;
-    if !variants.is_empty() {
-        let first_color_index = variants[0].index % 2;
+    let (structural_variants, small_variants): (Vec<&Variant>, Vec<&Variant>) = variants
+        .into_iter()
+        .partition(|variant| variant.sv_type.is_some());
+
+    // Structural variants first so that small variants inside them stay visible.
+    for variant in structural_variants {
+        let Some(sv_type) = variant.sv_type else {
+            continue;
+        };
+        let onscreen_start = alignment_view.onscreen_x_coordinate(variant.start(), area);
+        let onscreen_end = alignment_view.onscreen_x_coordinate(variant.end(), area);
+        if let Some((x, length)) =
+            OnScreenCoordinate::onscreen_start_and_length(&onscreen_start, &onscreen_end, area)
+        {
+            buf.set_string(
+                area.x + x,
+                area.y,
+                sv_glyph(sv_type).to_string().repeat(length as usize),
+                Style::default()
+                    .bg(sv_color(sv_type, pallete))
+                    .fg(pallete.SV_FG_COLOR),
+            );
+        }
+    }
+
+    if !small_variants.is_empty() {
+        let first_color_index = small_variants[0].index % 2;
         render_simple_intervals(
             area,
             buf,
-            variants,
+            small_variants,
             alignment_view,
             vec![pallete.VCF1, pallete.VCF2],
             first_color_index,
@@ -31,3 +65,104 @@ pub fn render_variants(
     }
     Ok(())
 }
+
+fn sv_color(sv_type: StructuralVariantType, pallete: &Palette) -> Color {
+    match sv_type {
+        StructuralVariantType::Deletion => pallete.SV_DEL,
+        StructuralVariantType::Duplication => pallete.SV_DUP,
+        StructuralVariantType::Inversion => pallete.SV_INV,
+        StructuralVariantType::Insertion => pallete.SV_INS,
+        StructuralVariantType::CopyNumber => pallete.SV_CNV,
+        StructuralVariantType::Breakend => pallete.SV_BND,
+    }
+}
+
+fn sv_glyph(sv_type: StructuralVariantType) -> char {
+    match sv_type {
+        StructuralVariantType::Deletion => '-',
+        StructuralVariantType::Duplication => '=',
+        StructuralVariantType::Inversion => '~',
+        StructuralVariantType::Insertion => 'I',
+        StructuralVariantType::CopyNumber => '#',
+        StructuralVariantType::Breakend => ']',
+    }
+}
+
+/// One row per sample, coloured by genotype. Phased genotypes are marked with |, unphased with /.
+pub fn render_genotypes(
+    area: &Rect,
+    buf: &mut Buffer,
+    state: &State,
+    alignment_view: &AlignmentView,
+    pallete: &Palette,
+) -> Result<(), TGVError> {
+    let Some(header) = state.variant_header.as_ref() else {
+        return Ok(());
+    };
+
+    let region = alignment_view.region(area);
+    let variants =
+        state
+            .variants
+            .overlapping(region.contig_index(), region.start(), region.end())?;
+
+    for (row, sample_index) in state
+        .variant_samples
+        .iter()
+        .skip(alignment_view.genotype_y)
+        .take(area.height as usize)
+        .enumerate()
+    {
+        let y = area.y + row as u16;
+
+        for variant in variants.iter() {
+            let Some(genotype) = variant.sample_genotype(*sample_index) else {
+                continue;
+            };
+
+            let onscreen_start = alignment_view.onscreen_x_coordinate(variant.start(), area);
+            let onscreen_end = alignment_view.onscreen_x_coordinate(variant.end(), area);
+            if let Some((x, length)) =
+                OnScreenCoordinate::onscreen_start_and_length(&onscreen_start, &onscreen_end, area)
+            {
+                buf.set_string(
+                    area.x + x,
+                    y,
+                    format!(
+                        "{}{}",
+                        genotype_glyph(&genotype),
+                        " ".repeat(length.saturating_sub(1) as usize)
+                    ),
+                    Style::default()
+                        .bg(genotype_color(&genotype, pallete))
+                        .fg(pallete.GENOTYPE_FG_COLOR),
+                );
+            }
+        }
+
+        if let Some(sample_name) = header.sample_names().get_index(*sample_index) {
+            buf.set_string(area.x, y, sample_name, Style::default());
+        }
+    }
+
+    Ok(())
+}
+
+fn genotype_color(genotype: &SampleGenotype, pallete: &Palette) -> Color {
+    match genotype.zygosity {
+        Zygosity::HomRef => pallete.GENOTYPE_HOM_REF,
+        Zygosity::Het => pallete.GENOTYPE_HET,
+        Zygosity::HomAlt => pallete.GENOTYPE_HOM_ALT,
+        Zygosity::NoCall => pallete.GENOTYPE_NO_CALL,
+    }
+}
+
+fn genotype_glyph(genotype: &SampleGenotype) -> char {
+    if genotype.zygosity == Zygosity::NoCall {
+        ' '
+    } else if genotype.phased {
+        '|'
+    } else {
+        '/'
+    }
+}
@@ -3,10 +3,12 @@ use crate::{
     rendering::{DARK_THEME, Palette},
 };
 use clap::{Parser, Subcommand, ValueEnum};
+use gv_core::alignment::{BaseCoverage, Downsampling, ModificationThresholds, is_cram};
 use gv_core::error::TGVError;
 use gv_core::message::Movement;
 use gv_core::reference::Reference;
 use gv_core::settings::BackendType;
+use gv_core::signal::SignalFormat;
 use gv_core::tracks::UcscHost;
 
 #[derive(Debug, Clone, Eq, PartialEq, ValueEnum)]
@@ -56,23 +58,37 @@ pub enum Commands {
 #[derive(Parser, Clone)]
 #[command(author, version, about, long_about = None)]
 pub struct Cli {
-    /// BAM file path. Must be sorted and indexed (with .bai file in the same directory).
+    /// BAM or CRAM file paths. Must be sorted and indexed (with .bai / .crai file in the same directory).
+    /// CRAM files are decoded against the reference genome (-g).
+    /// Each file is displayed in its own panel, top to bottom.
     /// If not provided, only reference genome will be displayed.
     #[arg(value_name = "bam_path")]
-    bam_path: Option<String>,
+    bam_paths: Vec<String>,
 
-    /// VCF file path.
+    /// VCF or BCF file path. bgzipped VCFs with a .tbi / .csi index and BCFs with a .csi index are queried by region.
     #[arg(short = 'v', long = "vcf", value_name = "vcf_path")]
     vcf_path: Option<String>,
 
-    /// BED file path
+    /// BED or bigBed file path. bigBed files can be remote (http / https URLs, e.g. from a track hub).
+    /// bgzipped BED files with a .tbi index and bigBed files are queried by region.
     #[arg(short = 'b', long = "bed", value_name = "bed_path")]
     bed_path: Option<String>,
 
-    /// Bai file path.
-    /// If not provided, .bai in the same directory as the BAM file will be used.
+    /// bigWig, bedGraph or modkit bedMethyl signal file path (e.g. ChIP, ATAC or methylation signal). Repeat for multiple tracks.
+    /// bigWig files are queried by region at a zoom level matching the view, bgzipped files with a .tbi index by region.
+    /// Other bedGraph and bedMethyl files are read in full. .bed files are read as bedMethyl.
+    #[arg(short = 'w', long = "bigwig", value_name = "signal_path")]
+    signal_paths: Vec<String>,
+
+    /// GTF or GFF3 gene annotation for a custom reference genome (-g genome.fa / genome.2bit) or --no-reference.
+    /// bgzipped files with a .tbi index are queried by region.
+    #[arg(short = 'a', long = "annotation", value_name = "annotation_path")]
+    annotation_path: Option<String>,
+
+    /// Bai / crai file path. With multiple alignment files, provide one index per file, in the same order.
+    /// If not provided, .bai (.crai for CRAM) in the same directory as the alignment file will be used.
     #[arg(short = 'i', long = "index", value_name = "bai")]
-    bai: Option<String>,
+    bai: Vec<String>,
 
     /// Starting region. Supported formats: [chr]:[pos] (e.g. 12:25398142); [gene] (e.g. TP53).
     /// If not provided, TGV will find a default starting region.
@@ -113,6 +129,60 @@ pub struct Cli {
     #[arg(long = "min-mapq", default_value_t = 0)]
     min_mapq: u8,
 
+    /// Minimum base quality. Bases below this value are not counted in the coverage (0 = no filter).
+    #[arg(long = "min-base-quality", default_value_t = 0)]
+    min_base_quality: u8,
+
+    /// Expected minimum insert size. Smaller pairs are discordant (default: estimated from the loaded pairs).
+    #[arg(long = "min-insert-size")]
+    min_insert_size: Option<u64>,
+
+    /// Expected maximum insert size. Larger pairs are discordant (default: estimated from the loaded pairs).
+    #[arg(long = "max-insert-size")]
+    max_insert_size: Option<u64>,
+
+    /// Downsample deep alignments: keep at most N reads starting in each window (default: keep all reads).
+    /// Coverage still counts all reads.
+    #[arg(long = "max-reads-per-window")]
+    max_reads_per_window: Option<usize>,
+
+    /// Downsampling window width in bases.
+    #[arg(long, default_value_t = Downsampling::DEFAULT_WINDOW)]
+    window: u64,
+
+    /// Highlight coverage columns where at least this fraction of the bases are not the reference.
+    #[arg(long = "allele-frequency-threshold", default_value_t = BaseCoverage::DEFAULT_ALLELE_FREQUENCY_THRESHOLD)]
+    allele_frequency_threshold: f64,
+
+    /// Weigh allele frequencies by base qualities.
+    #[arg(long = "quality-weighted", default_value_t = false)]
+    quality_weighted: bool,
+
+    /// Show a splice junction track below the coverage of each alignment panel (RNA-seq).
+    #[arg(long, default_value_t = false)]
+    junctions: bool,
+
+    /// Show the 5mC fraction of each CpG below the coverage of each alignment panel (MM/ML tags).
+    #[arg(long, default_value_t = false)]
+    methylation: bool,
+
+    /// Show amino-acid rows under the reference sequence when zoomed in: the reading frame of the
+    /// gene in view and the three forward frames.
+    #[arg(long, default_value_t = false)]
+    translate: bool,
+
+    /// Also translate the three reverse frames. Implies --translate.
+    #[arg(long = "six-frame", default_value_t = false)]
+    six_frame: bool,
+
+    /// Base modification calls with a probability below this fraction are shown as unmodified.
+    #[arg(long = "mod-low", default_value_t = 0.3)]
+    mod_low: f64,
+
+    /// Base modification calls with a probability of at least this fraction are shown as modified.
+    #[arg(long = "mod-high", default_value_t = 0.7)]
+    mod_high: f64,
+
     /// Subcommand
     #[command(subcommand)]
     pub command: Option<Commands>,
@@ -159,12 +229,21 @@ impl Cli {
     }
 }
 
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Settings {
     pub core: gv_core::settings::Settings,
     pub initial_state_messages: Vec<Message>,
     pub test_mode: bool,
 
+    /// Show a splice junction track in each alignment panel.
+    pub junctions: bool,
+
+    /// Show an aggregate methylation track in each alignment panel.
+    pub methylation: bool,
+
+    /// Genome reading frames translated under the reference sequence: 0 (no translation rows), 3 or 6.
+    pub translation_frames: usize,
+
     pub debug: bool,
     pub palette: Palette,
 }
@@ -178,6 +257,12 @@ impl Default for Settings {
 
             test_mode: false,
 
+            junctions: false,
+
+            methylation: false,
+
+            translation_frames: 0,
+
             debug: false,
 
             palette: DARK_THEME,
@@ -220,7 +305,7 @@ impl TryFrom<Cli> for Settings {
 
         // Additional validations:
         // 1. If no reference is provided, the initial state messages cannot contain GoToGene
-        if !reference.needs_track() {
+        if !reference.needs_track() && cli.annotation_path.is_none() {
             for m in initial_state_messages.iter() {
                 if let Message::Core(gv_core::message::Message::Move(
                     gv_core::message::Movement::Gene(gene_name),
@@ -234,35 +319,124 @@ impl TryFrom<Cli> for Settings {
             }
         }
 
-        // 2. bam file and reference cannot both be none
-        if cli.bam_path.is_none() && cli.no_reference {
+        // 2. Annotation files replace the gene models of UCSC genomes, which also provide the contigs.
+        if reference.needs_track() && cli.annotation_path.is_some() {
+            return Err(TGVError::CliError(format!(
+                "Annotation files are supported for custom reference genomes (-g genome.fa / genome.2bit) or --no-reference, not {}.",
+                reference.to_string()
+            )));
+        }
+
+        // 3. bam file and reference cannot both be none
+        if cli.bam_paths.is_empty() && cli.no_reference {
             return Err(TGVError::CliError(
                 "Bam file and reference cannot both be none".to_string(),
             ));
         }
 
-        let bam_path = cli.bam_path.map(|bam_path| {
-            let bai_path = cli.bai.unwrap_or(format!("{}.bai", bam_path.clone()));
-            (bam_path, bai_path)
-        });
+        // 4. Index files, if provided, pair with alignment files one by one.
+        if !cli.bai.is_empty() && cli.bai.len() != cli.bam_paths.len() {
+            return Err(TGVError::CliError(format!(
+                "{} index files are provided for {} alignment files. Please provide one index per alignment file.",
+                cli.bai.len(),
+                cli.bam_paths.len()
+            )));
+        }
+
+        // 5. Signal tracks must be bigWig, bedGraph or bedMethyl files.
+        for signal_path in cli.signal_paths.iter() {
+            SignalFormat::from_path(signal_path).map_err(|e| TGVError::CliError(e.to_string()))?;
+        }
+
+        // 6. Insert size bounds
+        if let (Some(min_insert_size), Some(max_insert_size)) =
+            (cli.min_insert_size, cli.max_insert_size)
+            && min_insert_size > max_insert_size
+        {
+            return Err(TGVError::CliError(format!(
+                "--min-insert-size ({}) is larger than --max-insert-size ({})",
+                min_insert_size, max_insert_size
+            )));
+        }
+
+        // 7. Downsampling
+        if cli.max_reads_per_window == Some(0) || cli.window == 0 {
+            return Err(TGVError::CliError(
+                "--max-reads-per-window and --window must be positive".to_string(),
+            ));
+        }
+
+        // 8. Allele frequency threshold
+        if !(0.0..=1.0).contains(&cli.allele_frequency_threshold) {
+            return Err(TGVError::CliError(format!(
+                "--allele-frequency-threshold ({}) must be between 0 and 1",
+                cli.allele_frequency_threshold
+            )));
+        }
+
+        // 9. Base modification thresholds
+        let modification_thresholds =
+            ModificationThresholds::from_fractions(cli.mod_low, cli.mod_high).map_err(|_| {
+                TGVError::CliError(format!(
+                    "--mod-low ({}) and --mod-high ({}) must satisfy 0 <= low <= high <= 1",
+                    cli.mod_low, cli.mod_high
+                ))
+            })?;
+
+        let mut bais = cli.bai.into_iter();
+        let bam_paths = cli
+            .bam_paths
+            .into_iter()
+            .map(|bam_path| {
+                let bai_path = bais.next().unwrap_or_else(|| {
+                    if is_cram(&bam_path) {
+                        format!("{}.crai", bam_path)
+                    } else {
+                        format!("{}.bai", bam_path)
+                    }
+                });
+                (bam_path, bai_path)
+            })
+            .collect();
 
         // cache_dir: expand ~
         let cache_dir = shellexpand::tilde(&cli.cache_dir).to_string();
 
         Ok(Self {
             core: gv_core::settings::Settings {
-                bam_path: bam_path,
+                bam_paths,
                 vcf_path: cli.vcf_path,
                 bed_path: cli.bed_path,
+                signal_paths: cli.signal_paths,
+                annotation_path: cli.annotation_path,
                 reference,
                 backend,
                 ucsc_host: cli.host.into(),
                 cache_dir,
                 min_mapq: cli.min_mapq,
+                min_base_quality: cli.min_base_quality,
+                min_insert_size: cli.min_insert_size,
+                max_insert_size: cli.max_insert_size,
+                downsampling: cli
+                    .max_reads_per_window
+                    .map(|max_reads_per_window| Downsampling {
+                        max_reads_per_window,
+                        window: cli.window,
+                    }),
+                allele_frequency_threshold: cli.allele_frequency_threshold,
+                quality_weighted_allele_frequency: cli.quality_weighted,
+                modification_thresholds,
             },
             initial_state_messages,
 
             test_mode: false,
+            junctions: cli.junctions,
+            methylation: cli.methylation,
+            translation_frames: match (cli.translate, cli.six_frame) {
+                (_, true) => 6,
+                (true, false) => 3,
+                (false, false) => 0,
+            },
             debug: cli.debug,
             palette: DARK_THEME,
         })
@@ -285,7 +459,7 @@ mod tests {
         core: gv_core::settings::Settings {
 
 
-        bam_path: Some(("input.bam".to_string(),"input.bam.bai".to_string())),
+        bam_paths: vec![("input.bam".to_string(),"input.bam.bai".to_string())],
         ..gv_core::settings::Settings::default()
         },
         ..Settings::default()
@@ -294,17 +468,59 @@ mod tests {
         core: gv_core::settings::Settings {
 
 
-        bam_path: Some(("input.bam".to_string(),"input.bam.bai".to_string())),
+        bam_paths: vec![("input.bam".to_string(),"input.bam.bai".to_string())],
         bed_path: Some("some.bed".to_string()),
         ..gv_core::settings::Settings::default()
         },
         ..Settings::default()
     }))]
+    #[case("tgv input.bam -w a.bw --bigwig b.bedGraph", Ok(Settings {
+        core: gv_core::settings::Settings {
+        bam_paths: vec![("input.bam".to_string(),"input.bam.bai".to_string())],
+        signal_paths: vec!["a.bw".to_string(), "b.bedGraph".to_string()],
+        ..gv_core::settings::Settings::default()
+        },
+        ..Settings::default()
+    }))]
+    #[case("tgv input.bam -w some.bb", Err(TGVError::CliError("".to_string())))]
+    #[case("tgv input.bam --junctions", Ok(Settings {
+        core: gv_core::settings::Settings {
+        bam_paths: vec![("input.bam".to_string(),"input.bam.bai".to_string())],
+        ..gv_core::settings::Settings::default()
+        },
+        junctions: true,
+        ..Settings::default()
+    }))]
+    #[case("tgv input.bam --methylation -w calls.bedmethyl.gz", Ok(Settings {
+        core: gv_core::settings::Settings {
+        bam_paths: vec![("input.bam".to_string(),"input.bam.bai".to_string())],
+        signal_paths: vec!["calls.bedmethyl.gz".to_string()],
+        ..gv_core::settings::Settings::default()
+        },
+        methylation: true,
+        ..Settings::default()
+    }))]
+    #[case("tgv input.bam --translate", Ok(Settings {
+        core: gv_core::settings::Settings {
+        bam_paths: vec![("input.bam".to_string(),"input.bam.bai".to_string())],
+        ..gv_core::settings::Settings::default()
+        },
+        translation_frames: 3,
+        ..Settings::default()
+    }))]
+    #[case("tgv input.bam --six-frame", Ok(Settings {
+        core: gv_core::settings::Settings {
+        bam_paths: vec![("input.bam".to_string(),"input.bam.bai".to_string())],
+        ..gv_core::settings::Settings::default()
+        },
+        translation_frames: 6,
+        ..Settings::default()
+    }))]
     #[case("tgv input.bam -v some.vcf", Ok(Settings {
         core: gv_core::settings::Settings {
 
 
-        bam_path: Some(("input.bam".to_string(),"input.bam.bai".to_string())),
+        bam_paths: vec![("input.bam".to_string(),"input.bam.bai".to_string())],
         vcf_path: Some("some.vcf".to_string()),
         ..gv_core::settings::Settings::default()
         },
@@ -314,7 +530,7 @@ mod tests {
         core: gv_core::settings::Settings {
 
 
-        bam_path: Some(("input.bam".to_string(), "input.bam.bai".to_string())),
+        bam_paths: vec![("input.bam".to_string(), "input.bam.bai".to_string())],
         backend: BackendType::Local,
         ..gv_core::settings::Settings::default()
         },
@@ -322,14 +538,14 @@ mod tests {
     }))]
     #[case("tgv input.bam --online", Ok(Settings {
         core: gv_core::settings::Settings {
-        bam_path: Some(("input.bam".to_string(), "input.bam.bai".to_string())),
+        bam_paths: vec![("input.bam".to_string(), "input.bam.bai".to_string())],
         backend: BackendType::Ucsc,
         ..gv_core::settings::Settings::default()},
         ..Settings::default()
     }))]
     #[case("tgv input.bam -r chr1:12345", Ok(Settings {
         core: gv_core::settings::Settings {
-        bam_path: Some(("input.bam".to_string(), "input.bam.bai".to_string())), ..gv_core::settings::Settings::default()},
+        bam_paths: vec![("input.bam".to_string(), "input.bam.bai".to_string())], ..gv_core::settings::Settings::default()},
         initial_state_messages: vec![Movement::ContigNamePosition(
             "chr1".to_string(),
             12345,
@@ -340,27 +556,27 @@ mod tests {
     #[case("tgv input.bam -r chr1:12:12345", Err(TGVError::CliError("".to_string())))]
     #[case("tgv input.bam -r TP53", Ok(Settings {
         core: gv_core::settings::Settings {
-        bam_path: Some(("input.bam".to_string(), "input.bam.bai".to_string())),..gv_core::settings::Settings::default()},
+        bam_paths: vec![("input.bam".to_string(), "input.bam.bai".to_string())],..gv_core::settings::Settings::default()},
         initial_state_messages: vec![Movement::Gene("TP53".to_string()).into()],
         ..Settings::default()
     }))]
     #[case("tgv input.bam -r TP53 -g hg19", Ok(Settings {
         core: gv_core::settings::Settings {
-        bam_path: Some(("input.bam".to_string(), "input.bam.bai".to_string())),
+        bam_paths: vec![("input.bam".to_string(), "input.bam.bai".to_string())],
         reference: Reference::Hg19,..gv_core::settings::Settings::default()},
         initial_state_messages: vec![Movement::Gene("TP53".to_string()).into()],
         ..Settings::default()
     }))]
     #[case("tgv input.bam -r TP53 -g mm39", Ok(Settings {
         core: gv_core::settings::Settings {
-        bam_path: Some(("input.bam".to_string(), "input.bam.bai".to_string())),
+        bam_paths: vec![("input.bam".to_string(), "input.bam.bai".to_string())],
         reference: Reference::UcscGenome("mm39".to_string()),..gv_core::settings::Settings::default()},
         initial_state_messages: vec![Movement::Gene("TP53".to_string()).into()],
         ..Settings::default()
     }))]
     #[case("tgv input.bam -r 1:12345 --no-reference", Ok(Settings {
         core: gv_core::settings::Settings {
-        bam_path: Some(("input.bam".to_string(), "input.bam.bai".to_string())),
+        bam_paths: vec![("input.bam".to_string(), "input.bam.bai".to_string())],
         reference: Reference::NoReference,..gv_core::settings::Settings::default()},
         initial_state_messages: vec![Movement::ContigNamePosition(
             "1".to_string(),
@@ -370,12 +586,96 @@ mod tests {
     }))]
     #[case("tgv input.bam --min-mapq 20", Ok(Settings {
         core: gv_core::settings::Settings {
-        bam_path: Some(("input.bam".to_string(), "input.bam.bai".to_string())),
+        bam_paths: vec![("input.bam".to_string(), "input.bam.bai".to_string())],
         min_mapq: 20,
         ..gv_core::settings::Settings::default()},
         ..Settings::default()
     }))]
+    #[case("tgv input.bam --min-base-quality 13", Ok(Settings {
+        core: gv_core::settings::Settings {
+        bam_paths: vec![("input.bam".to_string(), "input.bam.bai".to_string())],
+        min_base_quality: 13,
+        ..gv_core::settings::Settings::default()},
+        ..Settings::default()
+    }))]
+    #[case("tgv input.bam --min-insert-size 100 --max-insert-size 800", Ok(Settings {
+        core: gv_core::settings::Settings {
+        bam_paths: vec![("input.bam".to_string(), "input.bam.bai".to_string())],
+        min_insert_size: Some(100),
+        max_insert_size: Some(800),
+        ..gv_core::settings::Settings::default()},
+        ..Settings::default()
+    }))]
+    #[case("tgv input.bam --min-insert-size 800 --max-insert-size 100", Err(TGVError::CliError("".to_string())))]
+    #[case("tgv input.bam --max-reads-per-window 100", Ok(Settings {
+        core: gv_core::settings::Settings {
+        bam_paths: vec![("input.bam".to_string(), "input.bam.bai".to_string())],
+        downsampling: Some(Downsampling { max_reads_per_window: 100, window: 50 }),
+        ..gv_core::settings::Settings::default()},
+        ..Settings::default()
+    }))]
+    #[case("tgv input.bam --max-reads-per-window 100 --window 20", Ok(Settings {
+        core: gv_core::settings::Settings {
+        bam_paths: vec![("input.bam".to_string(), "input.bam.bai".to_string())],
+        downsampling: Some(Downsampling { max_reads_per_window: 100, window: 20 }),
+        ..gv_core::settings::Settings::default()},
+        ..Settings::default()
+    }))]
+    #[case("tgv input.bam --max-reads-per-window 0", Err(TGVError::CliError("".to_string())))]
+    #[case("tgv input.bam --allele-frequency-threshold 0.05 --quality-weighted", Ok(Settings {
+        core: gv_core::settings::Settings {
+        bam_paths: vec![("input.bam".to_string(), "input.bam.bai".to_string())],
+        allele_frequency_threshold: 0.05,
+        quality_weighted_allele_frequency: true,
+        ..gv_core::settings::Settings::default()},
+        ..Settings::default()
+    }))]
+    #[case("tgv input.bam --allele-frequency-threshold 2", Err(TGVError::CliError("".to_string())))]
+    #[case("tgv input.bam --mod-low 0.2 --mod-high 0.8", Ok(Settings {
+        core: gv_core::settings::Settings {
+        bam_paths: vec![("input.bam".to_string(), "input.bam.bai".to_string())],
+        modification_thresholds: ModificationThresholds { low: 51, high: 204 },
+        ..gv_core::settings::Settings::default()},
+        ..Settings::default()
+    }))]
+    #[case("tgv input.bam --mod-low 0.8 --mod-high 0.2", Err(TGVError::CliError("".to_string())))]
+    #[case("tgv input.cram --no-reference", Ok(Settings {
+        core: gv_core::settings::Settings {
+        bam_paths: vec![("input.cram".to_string(), "input.cram.crai".to_string())],
+        reference: Reference::NoReference,
+        ..gv_core::settings::Settings::default()},
+        ..Settings::default()
+    }))]
+    #[case("tgv tumor.bam normal.cram", Ok(Settings {
+        core: gv_core::settings::Settings {
+        bam_paths: vec![
+            ("tumor.bam".to_string(), "tumor.bam.bai".to_string()),
+            ("normal.cram".to_string(), "normal.cram.crai".to_string()),
+        ],
+        ..gv_core::settings::Settings::default()},
+        ..Settings::default()
+    }))]
+    #[case("tgv tumor.bam normal.bam -i tumor.bai -i normal.bai", Ok(Settings {
+        core: gv_core::settings::Settings {
+        bam_paths: vec![
+            ("tumor.bam".to_string(), "tumor.bai".to_string()),
+            ("normal.bam".to_string(), "normal.bai".to_string()),
+        ],
+        ..gv_core::settings::Settings::default()},
+        ..Settings::default()
+    }))]
+    #[case("tgv tumor.bam normal.bam -i tumor.bai", Err(TGVError::CliError("".to_string())))]
     #[case("tgv input.bam -r TP53 -g hg19 --no-reference", Err(TGVError::CliError("".to_string())))]
+    #[case("tgv input.bam -r TP53 --no-reference -a genes.gtf", Ok(Settings {
+        core: gv_core::settings::Settings {
+        bam_paths: vec![("input.bam".to_string(), "input.bam.bai".to_string())],
+        annotation_path: Some("genes.gtf".to_string()),
+        reference: Reference::NoReference,
+        ..gv_core::settings::Settings::default()},
+        initial_state_messages: vec![Movement::Gene("TP53".to_string()).into()],
+        ..Settings::default()
+    }))]
+    #[case("tgv input.bam -a genes.gtf", Err(TGVError::CliError("".to_string())))]
     #[case("tgv --no-reference", Err(TGVError::CliError("".to_string())))]
     //#[case("tgv download test-name", Err(TGVError::CliError("".to_string())))]
     // #[case("tgv download test-name --cache-dir /custom/dir", Err(TGVError::CliError("".to_string())))]